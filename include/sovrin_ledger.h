@@ -14,6 +14,10 @@ extern "C" {
     /// sign key (see wallet_sign), and sends signed request message
     /// to validator pool (see write_request).
     ///
+    /// If the pool is opened with "authChecks", auth rules for NYM, ATTRIB, SCHEMA, CLAIM_DEF and NODE
+    /// requests are checked first against submitter role got by GET_NYM (cached for a minute),
+    /// and LedgerSecurityError is returned without sending the request if the pool would reject it.
+    ///
     /// #Params
     /// command_handle: command handle to map callback to caller context.
     /// wallet_handle: wallet handle (created by open_wallet).
@@ -40,9 +44,77 @@ extern "C" {
                                                                               const char*     request_result_json)
                                                          );
     
+    /// Signs request message without submitting it to validator pool.
+    ///
+    /// Signed request can be passed to sovrin_submit_request from another box,
+    /// so the sign key doesn't need to leave the signing one.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// submitter_did: Id of Identity stored in secured Wallet. Must match identifier of the request.
+    /// request_json: Request data json.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Signed request json.
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_sign_request(sovrin_handle_t command_handle,
+                                              sovrin_handle_t wallet_handle,
+                                              const char *    submitter_did,
+                                              const char *    request_json,
+
+                                              void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                   sovrin_error_t  err,
+                                                                   const char*     signed_request_json)
+                                             );
+
+    /// Adds signature of submitter to `signatures` map of request message keyed by submitter did.
+    ///
+    /// Lets an author prepare a transaction (optionally signing it with sovrin_sign_request)
+    /// and an endorser (trust anchor) co-sign it before it's sent by sovrin_submit_request.
+    /// Single `signature` already present in the request is moved to the map under request identifier.
+    /// Every signer signs the same bytes, as `signature` and `signatures` fields aren't signed.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// submitter_did: Id of Identity stored in secured Wallet. Doesn't have to match identifier of the request.
+    /// request_json: Request data json.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Multi-signed request json.
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_multi_sign_request(sovrin_handle_t command_handle,
+                                                    sovrin_handle_t wallet_handle,
+                                                    const char *    submitter_did,
+                                                    const char *    request_json,
+
+                                                    void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                         sovrin_error_t  err,
+                                                                         const char*     signed_request_json)
+                                                   );
+
     /// Publishes request message to validator pool (no signing, unlike sign_and_submit_request).
     ///
     /// The request is sent to the validator pool as is. It's assumed that it's already prepared.
+    /// If the request is signed (see sovrin_sign_request), its signature is verified first against
+    /// verkey of the submitter got by GET_NYM from the same pool, and LedgerSecurityError is returned
+    /// if it doesn't match. The request can be cancelled only after the signature is verified.
+    ///
+    /// Request must have reqId, identifier and protocolVersion set by request builders,
+    /// otherwise PoolLedgerInvalidDataFormat is returned.
     ///
     /// #Params
     /// command_handle: command handle to map callback to caller context.
@@ -66,12 +138,40 @@ extern "C" {
                                                                      const char*     request_result_json)
                                                );
 
+    /// Cancels request previously passed to sovrin_submit_request or sovrin_sign_and_submit_request.
+    ///
+    /// Callback of the cancelled request is called with LedgerRequestCancelled error
+    /// and replies of validator pool for this request are ignored. Request that is still waiting
+    /// for its signature or auth rules check is cancelled without being sent to the pool.
+    /// LedgerNotFound is returned if no pending request has submit_command_handle.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// pool_handle: pool handle (created by open_pool_ledger).
+    /// submit_command_handle: command handle that was passed to submit function.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+
+    extern sovrin_error_t sovrin_cancel_request(sovrin_handle_t command_handle,
+                                                sovrin_handle_t pool_handle,
+                                                sovrin_handle_t submit_command_handle,
+
+                                                void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                     sovrin_error_t  err)
+                                               );
+
     /// Builds a request to get a DDO.
     ///
     /// #Params
     /// command_handle: command handle to map callback to caller context.
     /// submitter_did: Id of Identity stored in secured Wallet.
-    /// target_did: Id of Identity stored in secured Wallet.
+    /// target_did: Id of Identity to get DDO of.
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -128,7 +228,7 @@ extern "C" {
     /// target_did: Id of Identity stored in secured Wallet.
     /// hash: Hash of attribute data
     /// raw: represented as json, where key is attribute name and value is it's value
-    /// enc: Encrypted attribute data
+    /// enc: Encrypted attribute data: base58 encoded nonce followed by ciphertext
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -149,13 +249,73 @@ extern "C" {
                                                                            const char*     request_json)
                                                       );
 
+    /// Encrypts attribute with a fresh symmetric key and builds an ATTRIB request with it as "enc".
+    ///
+    /// The key is stored in the wallet, so GET_ATTRIB replies with the attribute can be decrypted
+    /// by sovrin_resolve_encrypted_attrib. The request is to be signed and submitted as usual.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// target_did: Id of Identity the attribute is set for.
+    /// raw: represented as json, where key is attribute name and value is it's value
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet*
+
+    extern sovrin_error_t sovrin_store_encrypted_attrib(sovrin_handle_t command_handle,
+                                                        sovrin_handle_t wallet_handle,
+                                                        const char *    submitter_did,
+                                                        const char *    target_did,
+                                                        const char *    raw,
+
+                                                        void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                             sovrin_error_t  err,
+                                                                             const char*     request_json)
+                                                        );
+
+    /// Decrypts attribute from GET_ATTRIB reply with the key stored by sovrin_store_encrypted_attrib.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// wallet_handle: wallet handle (created by open_wallet).
+    /// get_attrib_response: response of GET_ATTRIB request with encrypted attribute as "data".
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Raw attribute json, where key is attribute name and value is it's value.
+    ///
+    /// #Errors
+    /// Common*
+    /// Wallet* - WalletNotFoundError if the wallet has no key of the attribute
+    /// Ledger*
+    /// Crypto*
+
+    extern sovrin_error_t sovrin_resolve_encrypted_attrib(sovrin_handle_t command_handle,
+                                                          sovrin_handle_t wallet_handle,
+                                                          const char *    get_attrib_response,
+
+                                                          void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                               sovrin_error_t  err,
+                                                                               const char*     raw)
+                                                          );
+
     /// Builds a GET_ATTRIB request.
     ///
     /// #Params
     /// command_handle: command handle to map callback to caller context.
     /// submitter_did: Id of Identity stored in secured Wallet.
     /// target_did: Id of Identity stored in secured Wallet.
-    /// data: name (attribute name)
+    /// hash: (optional) Hash of hashed attribute, hex encoded sha256
+    /// raw: (optional) Attribute name
+    /// enc: (optional) "enc" of encrypted attribute from the request built by sovrin_store_encrypted_attrib
+    /// Exactly one of hash, raw and enc must be set.
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -167,7 +327,9 @@ extern "C" {
     extern sovrin_error_t sovrin_build_get_attrib_request(sovrin_handle_t command_handle,
                                                           const char *    submitter_did,
                                                           const char *    target_did,
-                                                          const char *    data,
+                                                          const char *    hash,
+                                                          const char *    raw,
+                                                          const char *    enc,
                                                       
                                                           void           (*cb)(sovrin_handle_t xcommand_handle,
                                                                                sovrin_error_t  err,
@@ -202,7 +364,14 @@ extern "C" {
     /// #Params
     /// command_handle: command handle to map callback to caller context.
     /// submitter_did: Id of Identity stored in secured Wallet.
-    /// data: name, version, type, attr_names (ip, port, keys)
+    /// data: schema json:
+    /// {
+    ///     "name": string, Schema name.
+    ///     "version": string, Schema version.
+    ///     "attribute_names": array of strings, Names of schema attributes. "attr_names" is accepted too.
+    /// }
+    /// Schema json used for sovrin_issuer_create_and_store_claim_def can be passed as is,
+    /// its seq_no is ignored as it is assigned by the ledger.
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -225,7 +394,8 @@ extern "C" {
     /// #Params
     /// command_handle: command handle to map callback to caller context.
     /// submitter_did: Id of Identity stored in secured Wallet.
-    /// data: name, version
+    /// dest: Id of Identity that created the schema.
+    /// data: json with name and version of the schema: {"name": string, "version": string}
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -236,6 +406,7 @@ extern "C" {
     
     extern sovrin_error_t sovrin_build_get_schema_request(sovrin_handle_t command_handle,
                                                           const char *    submitter_did,
+                                                          const char *    dest,
                                                           const char *    data,
                                                       
                                                           void           (*cb)(sovrin_handle_t xcommand_handle,
@@ -249,7 +420,8 @@ extern "C" {
     /// command_handle: command handle to map callback to caller context.
     /// submitter_did: Id of Identity stored in secured Wallet.
     /// xref: Seq. number of schema
-    /// data: components of a key in json: N, R, S, Z
+    /// data: claim definition json returned by sovrin_issuer_create_and_store_claim_def.
+    ///       Its schema_seq_no must be equal to xref and its signature_type is used for the request.
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -274,6 +446,8 @@ extern "C" {
     /// command_handle: command handle to map callback to caller context.
     /// submitter_did: Id of Identity stored in secured Wallet.
     /// xref: Seq. number of schema
+    /// signature_type (optional): signature type of claim definition. Defaults to "CL".
+    /// origin: Id of Identity that created the claim definition.
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -285,6 +459,8 @@ extern "C" {
     extern sovrin_error_t sovrin_build_get_claim_def_txn(sovrin_handle_t command_handle,
                                                          const char *    submitter_did,
                                                          const char *    xref,
+                                                         const char *    signature_type,
+                                                         const char *    origin,
                                                      
                                                          void           (*cb)(sovrin_handle_t xcommand_handle,
                                                                               sovrin_error_t  err,
@@ -296,8 +472,18 @@ extern "C" {
     /// #Params
     /// command_handle: command handle to map callback to caller context.
     /// submitter_did: Id of Identity stored in secured Wallet.
-    /// target_did: Id of Identity stored in secured Wallet.
-    /// data: id of a target NYM record
+    /// target_did: Verkey of the node.
+    /// data: NODE txn data json. Absent fields keep their current values on the ledger:
+    /// {
+    ///     "alias": string, Node alias.
+    ///     "client_ip": string (optional), IP for client connections.
+    ///     "client_port": int (optional), Port for client connections, set together with client_ip.
+    ///     "node_ip": string (optional), IP for node connections.
+    ///     "node_port": int (optional), Port for node connections, set together with node_ip.
+    ///     "services": array of strings (optional), Node services, "VALIDATOR" or "OBSERVER".
+    ///                 Empty array demotes the node.
+    ///     "blskey": string (optional), Base58 BLS key of the node.
+    /// }
     /// cb: Callback that takes command result as parameter.
     ///
     /// #Returns
@@ -316,6 +502,252 @@ extern "C" {
                                                                          const char*     request_json)
                                                    );
     
+    /// Builds a POOL_UPGRADE request.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// data: pool upgrade json:
+    /// {
+    ///     "name": string, Upgrade name.
+    ///     "version": string, Version to upgrade to.
+    ///     "action": string, "start" or "cancel".
+    ///     "sha256": string, Hex encoded sha256 of the package.
+    ///     "timeout": int (optional), Timeout of upgrade of each node in minutes.
+    ///     "schedule": json (optional), Map of node verkey to upgrade time, required for "start".
+    ///     "justification": string (optional), Reason of the upgrade.
+    ///     "reinstall": bool (optional), Reinstall the same version. Defaults to false.
+    ///     "force": bool (optional), Apply without consensus. Defaults to false.
+    /// }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_build_pool_upgrade_request(sovrin_handle_t command_handle,
+                                                            const char *    submitter_did,
+                                                            const char *    data,
+
+                                                            void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                 sovrin_error_t  err,
+                                                                                 const char*     request_json)
+                                                           );
+
+    /// Builds a GET_TXN request to get any transaction by its sequence number on the ledger,
+    /// e.g. to resolve "ref" of CLAIM_DEF or to audit issuances recorded by sovrin_wallet_set_seq_no_for_value.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// submitter_did: Id of Identity stored in secured Wallet.
+    /// ledger_type: (optional) "POOL" or "DOMAIN", "DOMAIN" by default.
+    /// seq_no: sequence number of the transaction on the ledger.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Request result as json.
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_build_get_txn_request(sovrin_handle_t command_handle,
+                                                       const char *    submitter_did,
+                                                       const char *    ledger_type,
+                                                       sovrin_i32_t    seq_no,
+
+                                                       void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                            sovrin_error_t  err,
+                                                                            const char*     request_json)
+                                                       );
+
+    /// Parses GET_TXN reply into the transaction as stored on the ledger with its position and type.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// get_txn_response: response of GET_TXN request.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Txn json:
+    /// {
+    ///     "seqNo": int, Seq. number of the txn on the ledger.
+    ///     "txnTime": int (optional), Time the txn was ordered by the pool, seconds since epoch.
+    ///     "txnType": string, Name of txn type (NYM, ATTRIB, SCHEMA, CLAIM_DEF, NODE, POOL_UPGRADE) or its code for other types.
+    ///     "txn": json, The txn without fields nodes add to replies (seqNo, txnTime, auditPath, rootHash).
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+    /// LedgerNotFound is returned if the reply has no data.
+
+    extern sovrin_error_t sovrin_parse_get_txn_response(sovrin_handle_t command_handle,
+                                                        const char *    get_txn_response,
+
+                                                        void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                             sovrin_error_t  err,
+                                                                             const char*     txn_json)
+                                                        );
+
+    /// Parses GET_SCHEMA reply into schema json expected by anoncreds
+    /// (sovrin_issuer_create_and_store_claim_def, sovrin_prover_create_proof, sovrin_verifier_verify_proof).
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// get_schema_response: response of GET_SCHEMA request.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Schema json:
+    /// {
+    ///     "name": string,
+    ///     "version": string,
+    ///     "attribute_names": array of strings,
+    ///     "seq_no": int, Seq. number of SCHEMA txn on the ledger.
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+    /// LedgerNotFound is returned if the reply has no data.
+
+    extern sovrin_error_t sovrin_parse_get_schema_response(sovrin_handle_t command_handle,
+                                                           const char *    get_schema_response,
+
+                                                           void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                sovrin_error_t  err,
+                                                                                const char*     schema_json)
+                                                          );
+
+    /// Parses GET_CLAIM_DEF reply into claim definition json expected by anoncreds
+    /// (sovrin_prover_create_and_store_claim_req, sovrin_prover_create_proof, sovrin_verifier_verify_proof).
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// get_claim_def_response: response of GET_CLAIM_DEF request.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Claim definition json:
+    /// {
+    ///     "public_key": primary public key,
+    ///     "public_key_revocation": revocation public key (optional),
+    ///     "schema_seq_no": int, Seq. number of the schema.
+    ///     "signature_type": string,
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+    /// LedgerNotFound is returned if the reply has no data.
+
+    extern sovrin_error_t sovrin_parse_get_claim_def_response(sovrin_handle_t command_handle,
+                                                              const char *    get_claim_def_response,
+
+                                                              void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                                   sovrin_error_t  err,
+                                                                                   const char*     claim_def_json)
+                                                             );
+
+    /// Parses GET_NYM reply into nym json.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// get_nym_response: response of GET_NYM request.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Nym json:
+    /// {
+    ///     "did": string,
+    ///     "identifier": string, Id of Identity that created the nym.
+    ///     "verkey": string (optional),
+    ///     "role": string (optional), One of "TRUSTEE", "TGB", "STEWARD", "TRUST_ANCHOR".
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+    /// LedgerNotFound is returned if the reply has no data.
+
+    extern sovrin_error_t sovrin_parse_get_nym_response(sovrin_handle_t command_handle,
+                                                        const char *    get_nym_response,
+
+                                                        void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                             sovrin_error_t  err,
+                                                                             const char*     nym_json)
+                                                       );
+
+    /// Assembles DID document from GET_NYM reply and GET_ATTRIB replies of the same DID,
+    /// so agents don't need to parse raw ledger replies.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// get_nym_response: response of GET_NYM request.
+    /// get_attrib_responses (optional): json array of responses of GET_ATTRIB requests (as strings).
+    ///                                  Responses without data are skipped.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// DID document json:
+    /// {
+    ///     "id": string, "did:sov:" prefixed DID.
+    ///     "did": string,
+    ///     "verkey": string (optional), Full verkey, abbreviated one is expanded.
+    ///     "role": string (optional), One of "TRUSTEE", "TGB", "STEWARD", "TRUST_ANCHOR".
+    ///     "endpoint": json (optional), Value of "endpoint" attribute.
+    ///     "service": [{
+    ///         "type": string, "agent" for "ha" of endpoint or type of "service" attribute entry.
+    ///         "serviceEndpoint": string,
+    ///     }],
+    ///     "attributes": json, Map of all attribute names to their values.
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+
+    extern sovrin_error_t sovrin_build_did_document(sovrin_handle_t command_handle,
+                                                    const char *    get_nym_response,
+                                                    const char *    get_attrib_responses,
+
+                                                    void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                         sovrin_error_t  err,
+                                                                         const char*     did_document_json)
+                                                   );
+
+    /// Checks that transaction from write reply is included in the ledger with given root hash,
+    /// so auditors can verify replies without trusting the nodes.
+    ///
+    /// #Params
+    /// command_handle: command handle to map callback to caller context.
+    /// txn_json: transaction json as in "result" of write reply, seqNo is required.
+    ///           Fields added to reply by nodes (seqNo, auditPath, rootHash) aren't part of the ledger leaf.
+    /// audit_path: json array of base58 encoded hashes of RFC 6962 audit path from the leaf to the root,
+    ///             as "auditPath" of write reply.
+    /// root_hash: base58 encoded merkle root of the ledger, as "rootHash" of write reply.
+    /// tree_size: number of transactions in the ledger with given root hash.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// valid: true - if transaction is included in the ledger, false - otherwise
+    ///
+    /// #Errors
+    /// Common*
+
+    extern sovrin_error_t sovrin_verify_txn_inclusion(sovrin_handle_t command_handle,
+                                                      const char *    txn_json,
+                                                      const char *    audit_path,
+                                                      const char *    root_hash,
+                                                      sovrin_i32_t    tree_size,
+
+                                                      void           (*cb)(sovrin_handle_t xcommand_handle,
+                                                                           sovrin_error_t  err,
+                                                                           sovrin_bool_t   valid)
+                                                     );
+
 #ifdef __cplusplus
 }
 #endif
@@ -96,6 +96,12 @@ typedef enum
     // IO error during sending of ledger transactions or catchup process
     LedgerIOError,
     
+    // Request was cancelled by caller before consensus reply was received
+    LedgerRequestCancelled,
+    
+    // Requested data wasn't found on the ledger
+    LedgerNotFound,
+    
     // Crypto errors
     // Invalid structure of any crypto promitives (keys, signatures, seeds and etc...)
     CryptoInvalidStructure = 400,
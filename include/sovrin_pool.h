@@ -25,6 +25,79 @@ extern "C" {
                                                      void            (*cb)(sovrin_handle_t xcommand_handle, sovrin_error_t err)
                                                      );
     
+    /// Returns current state of opened pool ledger.
+    ///
+    /// #Params
+    /// handle: pool handle returned by sovrin_open_pool_ledger.
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Pool status json:
+    /// {
+    ///     "phase": string, One of "CatchingUp", "Ready", "Refreshing", "Terminated", "Failed".
+    ///               Pool is "Refreshing" while it catches up stale domain ledger replica,
+    ///               requests are sent to the nodes meanwhile.
+    ///     "ledgerSize": int, Count of txns in local copy of pool ledger.
+    ///     "rootHash": string, Base58 merkle root hash of local copy of pool ledger.
+    ///     "f": int, Count of faulty nodes the pool tolerates.
+    ///     "nodes": [{
+    ///         "alias": string, Node alias.
+    ///         "state": string, One of "Connecting", "Connected", "Unreachable".
+    ///     }]
+    /// }
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+
+    extern sovrin_error_t sovrin_pool_get_status(sovrin_handle_t command_handle,
+                                                 sovrin_handle_t handle,
+                                                 void            (*cb)(sovrin_handle_t xcommand_handle, sovrin_error_t err, const char* status_json)
+                                                 );
+
+    /// Subscribes to events of opened pool ledger.
+    ///
+    /// event_cb is called for each pool state transition ("PhaseChanged"), pool ledger
+    /// catch-up completion ("CatchupCompleted") and node connection changes
+    /// ("NodeConnected", "NodeDisconnected") until the pool is closed. Node is reported
+    /// disconnected when sending to it fails or it doesn't answer ping in time.
+    ///
+    /// #Params
+    /// handle: pool handle returned by sovrin_open_pool_ledger.
+    /// event_cb: Callback that takes event json as parameter:
+    /// {
+    ///     "event": string, Event type.
+    ///     "node": string (optional), Alias of the node for node events.
+    ///     "status": json, Pool status after the event (see sovrin_pool_get_status).
+    /// }
+    /// cb: Callback that takes command result as parameter.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+
+    extern sovrin_error_t sovrin_pool_subscribe(sovrin_handle_t command_handle,
+                                                sovrin_handle_t handle,
+                                                void            (*event_cb)(sovrin_handle_t xcommand_handle, sovrin_error_t err, const char* event_json),
+                                                void            (*cb)(sovrin_handle_t xcommand_handle, sovrin_error_t err)
+                                                );
+    
+    /// Closes opened pool ledger, opened nodes connections and frees allocated resources.
+    /// Callbacks subscribed by sovrin_pool_subscribe aren't called after the pool is closed.
+    ///
+    /// #Params
+    /// handle: pool handle returned by sovrin_open_pool_ledger.
+    ///
+    /// #Returns
+    /// Error code
+    ///
+    /// #Errors
+    /// Common*
+    /// Ledger*
+
     extern sovrin_error_t sovrin_close_pool_ledger(sovrin_handle_t command_hangle,
                                                    sovrin_handle_t handle,
                                                    void            (*cb)(sovrin_handle_t xcommand_handle, sovrin_error_t err)
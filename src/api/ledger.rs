@@ -14,6 +14,10 @@ use self::libc::c_char;
 /// sign key (see wallet_sign), and sends signed request message
 /// to validator pool (see write_request).
 ///
+/// If the pool is opened with "authChecks", auth rules for NYM, ATTRIB, SCHEMA, CLAIM_DEF and NODE
+/// requests are checked first against submitter role got by GET_NYM (cached for a minute),
+/// and LedgerSecurityError is returned without sending the request if the pool would reject it.
+///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// pool_handle: pool handle (created by open_pool_ledger).
@@ -44,6 +48,7 @@ pub extern fn sovrin_sign_and_submit_request(command_handle: i32,
 
     let result = CommandExecutor::instance()
         .send(Command::Ledger(LedgerCommand::SignAndSubmitRequest(
+            Some(command_handle),
             pool_handle,
             wallet_handle,
             submitter_did,
@@ -58,9 +63,107 @@ pub extern fn sovrin_sign_and_submit_request(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Signs request message without submitting it to validator pool.
+///
+/// Signed request can be passed to sovrin_submit_request from another box,
+/// so the sign key doesn't need to leave the signing one.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// submitter_did: Id of Identity stored in secured Wallet. Must match identifier of the request.
+/// request_json: Request data json.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Signed request json.
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub extern fn sovrin_sign_request(command_handle: i32,
+                                  wallet_handle: i32,
+                                  submitter_did: *const c_char,
+                                  request_json: *const c_char,
+                                  cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                       signed_request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(request_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::SignRequest(
+            wallet_handle,
+            submitter_did,
+            request_json,
+            Box::new(move |result| {
+                let (err, signed_request_json) = result_to_err_code_1!(result, String::new());
+                let signed_request_json = CStringUtils::string_to_cstring(signed_request_json);
+                cb(command_handle, err, signed_request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Adds signature of submitter to `signatures` map of request message keyed by submitter did.
+///
+/// Lets an author prepare a transaction (optionally signing it with sovrin_sign_request)
+/// and an endorser (trust anchor) co-sign it before it's sent by sovrin_submit_request.
+/// Single `signature` already present in the request is moved to the map under request identifier.
+/// Every signer signs the same bytes, as `signature` and `signatures` fields aren't signed.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// submitter_did: Id of Identity stored in secured Wallet. Doesn't have to match identifier of the request.
+/// request_json: Request data json.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Multi-signed request json.
+///
+/// #Errors
+/// Common*
+/// Wallet*
+/// Crypto*
+#[no_mangle]
+pub extern fn sovrin_multi_sign_request(command_handle: i32,
+                                        wallet_handle: i32,
+                                        submitter_did: *const c_char,
+                                        request_json: *const c_char,
+                                        cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                             signed_request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(request_json, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::MultiSignRequest(
+            wallet_handle,
+            submitter_did,
+            request_json,
+            Box::new(move |result| {
+                let (err, signed_request_json) = result_to_err_code_1!(result, String::new());
+                let signed_request_json = CStringUtils::string_to_cstring(signed_request_json);
+                cb(command_handle, err, signed_request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
 /// Publishes request message to validator pool (no signing, unlike sign_and_submit_request).
 ///
 /// The request is sent to the validator pool as is. It's assumed that it's already prepared.
+/// If the request is signed (see sovrin_sign_request), its signature is verified first against
+/// verkey of the submitter got by GET_NYM from the same pool, and LedgerSecurityError is returned
+/// if it doesn't match. The request can be cancelled only after the signature is verified.
+///
+/// Request must have reqId, identifier and protocolVersion set by request builders,
+/// otherwise PoolLedgerInvalidDataFormat is returned.
 ///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
@@ -85,6 +188,7 @@ pub extern fn sovrin_submit_request(command_handle: i32,
 
     let result = CommandExecutor::instance()
         .send(Command::Ledger(LedgerCommand::SubmitRequest(
+            Some(command_handle),
             pool_handle,
             request_json,
             Box::new(move |result| {
@@ -97,13 +201,52 @@ pub extern fn sovrin_submit_request(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Cancels request previously passed to sovrin_submit_request or sovrin_sign_and_submit_request.
+///
+/// Callback of the cancelled request is called with LedgerRequestCancelled error
+/// and replies of validator pool for this request are ignored. Request that is still waiting
+/// for its signature or auth rules check is cancelled without being sent to the pool.
+/// LedgerNotFound is returned if no pending request has submit_command_handle.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// pool_handle: pool handle (created by open_pool_ledger).
+/// submit_command_handle: command handle that was passed to submit function.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Ledger*
+#[no_mangle]
+pub extern fn sovrin_cancel_request(command_handle: i32,
+                                    pool_handle: i32,
+                                    submit_command_handle: i32,
+                                    cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::CancelRequest(
+            pool_handle,
+            submit_command_handle,
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
 
 /// Builds a request to get a DDO.
 ///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
-/// target_did: Id of Identity stored in secured Wallet.
+/// target_did: Id of Identity to get DDO of.
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -142,10 +285,10 @@ pub extern fn sovrin_build_get_ddo_request(command_handle: i32,
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
 /// target_did: Id of Identity stored in secured Wallet.
-/// verkey: verification key
-/// xref: id of a NYM record
-/// data: alias
-/// role: Role of a user NYM record
+/// verkey: (optional) verification key, full or abbreviated (starts with "~")
+/// xref: (optional) id of a NYM record
+/// data: (optional) alias
+/// role: (optional) Role of a user NYM record: TRUSTEE, TGB, STEWARD or TRUST_ANCHOR
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -165,10 +308,10 @@ pub extern fn sovrin_build_nym_request(command_handle: i32,
                                                      request_json: *const c_char)>) -> ErrorCode {
     check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
     check_useful_c_str!(target_did, ErrorCode::CommonInvalidParam3);
-    check_useful_c_str!(verkey, ErrorCode::CommonInvalidParam4);
-    check_useful_c_str!(xref, ErrorCode::CommonInvalidParam5);
-    check_useful_c_str!(data, ErrorCode::CommonInvalidParam6);
-    check_useful_c_str!(role, ErrorCode::CommonInvalidParam7);
+    check_useful_opt_c_str!(verkey, ErrorCode::CommonInvalidParam4);
+    check_useful_opt_c_str!(xref, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_c_str!(data, ErrorCode::CommonInvalidParam6);
+    check_useful_opt_c_str!(role, ErrorCode::CommonInvalidParam7);
     check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam8);
 
     let result = CommandExecutor::instance()
@@ -195,9 +338,10 @@ pub extern fn sovrin_build_nym_request(command_handle: i32,
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
 /// target_did: Id of Identity stored in secured Wallet.
-/// hash: Hash of attribute data
-/// raw: represented as json, where key is attribute name and value is it's value
-/// enc: Encrypted attribute data
+/// hash: (optional) Hash of attribute data, hex encoded sha256
+/// raw: (optional) represented as json, where key is attribute name and value is it's value
+/// enc: (optional) Encrypted attribute data: base58 encoded nonce followed by ciphertext
+/// Exactly one of hash, raw and enc must be set.
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -216,9 +360,9 @@ pub extern fn sovrin_build_attrib_request(command_handle: i32,
                                                         request_json: *const c_char)>) -> ErrorCode {
     check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
     check_useful_c_str!(target_did, ErrorCode::CommonInvalidParam3);
-    check_useful_c_str!(hash, ErrorCode::CommonInvalidParam4);
-    check_useful_c_str!(raw, ErrorCode::CommonInvalidParam5);
-    check_useful_c_str!(enc, ErrorCode::CommonInvalidParam6);
+    check_useful_opt_c_str!(hash, ErrorCode::CommonInvalidParam4);
+    check_useful_opt_c_str!(raw, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_c_str!(enc, ErrorCode::CommonInvalidParam6);
     check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
 
     let result = CommandExecutor::instance()
@@ -238,13 +382,103 @@ pub extern fn sovrin_build_attrib_request(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Encrypts attribute with a fresh symmetric key and builds an ATTRIB request with it as "enc".
+///
+/// The key is stored in the wallet, so GET_ATTRIB replies with the attribute can be decrypted
+/// by sovrin_resolve_encrypted_attrib. The request is to be signed and submitted as usual.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// target_did: Id of Identity the attribute is set for.
+/// raw: represented as json, where key is attribute name and value is it's value
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+/// Wallet*
+#[no_mangle]
+pub extern fn sovrin_store_encrypted_attrib(command_handle: i32,
+                                            wallet_handle: i32,
+                                            submitter_did: *const c_char,
+                                            target_did: *const c_char,
+                                            raw: *const c_char,
+                                            cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                 request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(target_did, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(raw, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::StoreEncryptedAttrib(
+            wallet_handle,
+            submitter_did,
+            target_did,
+            raw,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Decrypts attribute from GET_ATTRIB reply with the key stored by sovrin_store_encrypted_attrib.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// wallet_handle: wallet handle (created by open_wallet).
+/// get_attrib_response: response of GET_ATTRIB request with encrypted attribute as "data".
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Raw attribute json, where key is attribute name and value is it's value.
+///
+/// #Errors
+/// Common*
+/// Wallet* - WalletNotFoundError if the wallet has no key of the attribute
+/// Ledger*
+/// Crypto*
+#[no_mangle]
+pub extern fn sovrin_resolve_encrypted_attrib(command_handle: i32,
+                                              wallet_handle: i32,
+                                              get_attrib_response: *const c_char,
+                                              cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                   raw: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(get_attrib_response, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::ResolveEncryptedAttrib(
+            wallet_handle,
+            get_attrib_response,
+            Box::new(move |result| {
+                let (err, raw) = result_to_err_code_1!(result, String::new());
+                let raw = CStringUtils::string_to_cstring(raw);
+                cb(command_handle, err, raw.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
 /// Builds a GET_ATTRIB request.
 ///
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
 /// target_did: Id of Identity stored in secured Wallet.
-/// data: name (attribute name)
+/// hash: (optional) Hash of hashed attribute, hex encoded sha256
+/// raw: (optional) Attribute name
+/// enc: (optional) "enc" of encrypted attribute from the request built by sovrin_store_encrypted_attrib
+/// Exactly one of hash, raw and enc must be set.
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -252,22 +486,29 @@ pub extern fn sovrin_build_attrib_request(command_handle: i32,
 ///
 /// #Errors
 /// Common*
+#[no_mangle]
 pub extern fn sovrin_build_get_attrib_request(command_handle: i32,
                                        submitter_did: *const c_char,
                                        target_did: *const c_char,
-                                       data: *const c_char,
+                                       hash: *const c_char,
+                                       raw: *const c_char,
+                                       enc: *const c_char,
                                        cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
                                                             request_json: *const c_char)>) -> ErrorCode {
     check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
     check_useful_c_str!(target_did, ErrorCode::CommonInvalidParam3);
-    check_useful_c_str!(data, ErrorCode::CommonInvalidParam4);
-    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_c_str!(hash, ErrorCode::CommonInvalidParam4);
+    check_useful_opt_c_str!(raw, ErrorCode::CommonInvalidParam5);
+    check_useful_opt_c_str!(enc, ErrorCode::CommonInvalidParam6);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam7);
 
     let result = CommandExecutor::instance()
         .send(Command::Ledger(LedgerCommand::BuildGetAttribRequest(
             submitter_did,
             target_did,
-            data,
+            hash,
+            raw,
+            enc,
             Box::new(move |result| {
                 let (err, request_json) = result_to_err_code_1!(result, String::new());
                 let request_json = CStringUtils::string_to_cstring(request_json);
@@ -320,7 +561,14 @@ pub extern fn sovrin_build_get_nym_request(command_handle: i32,
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
-/// data: name, version, type, attr_names (ip, port, keys)
+/// data: schema json:
+/// {
+///     "name": string, Schema name.
+///     "version": string, Schema version.
+///     "attribute_names": array of strings, Names of schema attributes. "attr_names" is accepted too.
+/// }
+/// Schema json used for sovrin_issuer_create_and_store_claim_def can be passed as is,
+/// its seq_no is ignored as it is assigned by the ledger.
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -357,7 +605,8 @@ pub extern fn sovrin_build_schema_request(command_handle: i32,
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
-/// data: name, version
+/// dest: Id of Identity that created the schema.
+/// data: json with name and version of the schema: {"name": string, "version": string}
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -368,16 +617,19 @@ pub extern fn sovrin_build_schema_request(command_handle: i32,
 #[no_mangle]
 pub extern fn sovrin_build_get_schema_request(command_handle: i32,
                                        submitter_did: *const c_char,
+                                       dest: *const c_char,
                                        data: *const c_char,
                                        cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
                                                             request_json: *const c_char)>) -> ErrorCode {
     check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
-    check_useful_c_str!(data, ErrorCode::CommonInvalidParam3);
-    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(dest, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(data, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
 
     let result = CommandExecutor::instance()
         .send(Command::Ledger(LedgerCommand::BuildGetSchemaRequest(
             submitter_did,
+            dest,
             data,
             Box::new(move |result| {
                 let (err, request_json) = result_to_err_code_1!(result, String::new());
@@ -395,7 +647,8 @@ pub extern fn sovrin_build_get_schema_request(command_handle: i32,
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
 /// xref: Seq. number of schema
-/// data: components of a key in json: N, R, S, Z
+/// data: claim definition json returned by sovrin_issuer_create_and_store_claim_def.
+///       Its schema_seq_no must be equal to xref and its signature_type is used for the request.
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -436,6 +689,8 @@ pub extern fn sovrin_build_claim_def_txn(command_handle: i32,
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
 /// xref: Seq. number of schema
+/// signature_type (optional): signature type of claim definition. Defaults to "CL".
+/// origin: Id of Identity that created the claim definition.
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -447,16 +702,22 @@ pub extern fn sovrin_build_claim_def_txn(command_handle: i32,
 pub extern fn sovrin_build_get_claim_def_txn(command_handle: i32,
                                       submitter_did: *const c_char,
                                       xref: *const c_char,
+                                      signature_type: *const c_char,
+                                      origin: *const c_char,
                                       cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
                                                            request_json: *const c_char)>) -> ErrorCode {
     check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
     check_useful_c_str!(xref, ErrorCode::CommonInvalidParam3);
-    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+    check_useful_opt_c_str!(signature_type, ErrorCode::CommonInvalidParam4);
+    check_useful_c_str!(origin, ErrorCode::CommonInvalidParam5);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
 
     let result = CommandExecutor::instance()
         .send(Command::Ledger(LedgerCommand::BuildGetClaimDefRequest(
             submitter_did,
             xref,
+            signature_type,
+            origin,
             Box::new(move |result| {
                 let (err, request_json) = result_to_err_code_1!(result, String::new());
                 let request_json = CStringUtils::string_to_cstring(request_json);
@@ -472,8 +733,18 @@ pub extern fn sovrin_build_get_claim_def_txn(command_handle: i32,
 /// #Params
 /// command_handle: command handle to map callback to caller context.
 /// submitter_did: Id of Identity stored in secured Wallet.
-/// target_did: Id of Identity stored in secured Wallet.
-/// data: id of a target NYM record
+/// target_did: Verkey of the node.
+/// data: NODE txn data json. Absent fields keep their current values on the ledger:
+/// {
+///     "alias": string, Node alias.
+///     "client_ip": string (optional), IP for client connections.
+///     "client_port": int (optional), Port for client connections, set together with client_ip.
+///     "node_ip": string (optional), IP for node connections.
+///     "node_port": int (optional), Port for node connections, set together with node_ip.
+///     "services": array of strings (optional), Node services, "VALIDATOR" or "OBSERVER".
+///                 Empty array demotes the node.
+///     "blskey": string (optional), Base58 BLS key of the node.
+/// }
 /// cb: Callback that takes command result as parameter.
 ///
 /// #Returns
@@ -506,4 +777,357 @@ pub extern fn sovrin_build_node_request(command_handle: i32,
         )));
 
     result_to_err_code!(result)
-}
\ No newline at end of file
+}
+
+/// Builds a POOL_UPGRADE request.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// data: pool upgrade json:
+/// {
+///     "name": string, Upgrade name.
+///     "version": string, Version to upgrade to.
+///     "action": string, "start" or "cancel".
+///     "sha256": string, Hex encoded sha256 of the package.
+///     "timeout": int (optional), Timeout of upgrade of each node in minutes.
+///     "schedule": json (optional), Map of node verkey to upgrade time, required for "start".
+///     "justification": string (optional), Reason of the upgrade.
+///     "reinstall": bool (optional), Reinstall the same version. Defaults to false.
+///     "force": bool (optional), Apply without consensus. Defaults to false.
+/// }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_build_pool_upgrade_request(command_handle: i32,
+                                                submitter_did: *const c_char,
+                                                data: *const c_char,
+                                                cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                     request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(data, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildPoolUpgradeRequest(
+            submitter_did,
+            data,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Builds a GET_TXN request to get any transaction by its sequence number on the ledger,
+/// e.g. to resolve "ref" of CLAIM_DEF or to audit issuances recorded by sovrin_wallet_set_seq_no_for_value.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// submitter_did: Id of Identity stored in secured Wallet.
+/// ledger_type: (optional) "POOL" or "DOMAIN", "DOMAIN" by default.
+/// seq_no: sequence number of the transaction on the ledger.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Request result as json.
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_build_get_txn_request(command_handle: i32,
+                                           submitter_did: *const c_char,
+                                           ledger_type: *const c_char,
+                                           seq_no: i32,
+                                           cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                request_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(submitter_did, ErrorCode::CommonInvalidParam2);
+    check_useful_opt_c_str!(ledger_type, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam5);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildGetTxnRequest(
+            submitter_did,
+            ledger_type,
+            seq_no,
+            Box::new(move |result| {
+                let (err, request_json) = result_to_err_code_1!(result, String::new());
+                let request_json = CStringUtils::string_to_cstring(request_json);
+                cb(command_handle, err, request_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Parses GET_TXN reply into the transaction as stored on the ledger with its position and type.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// get_txn_response: response of GET_TXN request.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Txn json:
+/// {
+///     "seqNo": int, Seq. number of the txn on the ledger.
+///     "txnTime": int (optional), Time the txn was ordered by the pool, seconds since epoch.
+///     "txnType": string, Name of txn type (NYM, ATTRIB, SCHEMA, CLAIM_DEF, NODE, POOL_UPGRADE) or its code for other types.
+///     "txn": json, The txn without fields nodes add to replies (seqNo, txnTime, auditPath, rootHash).
+/// }
+///
+/// #Errors
+/// Common*
+/// Ledger*
+/// LedgerNotFound is returned if the reply has no data.
+#[no_mangle]
+pub extern fn sovrin_parse_get_txn_response(command_handle: i32,
+                                            get_txn_response: *const c_char,
+                                            cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                 txn_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(get_txn_response, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::ParseGetTxnResponse(
+            get_txn_response,
+            Box::new(move |result| {
+                let (err, txn_json) = result_to_err_code_1!(result, String::new());
+                let txn_json = CStringUtils::string_to_cstring(txn_json);
+                cb(command_handle, err, txn_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Parses GET_SCHEMA reply into schema json expected by anoncreds
+/// (sovrin_issuer_create_and_store_claim_def, sovrin_prover_create_proof, sovrin_verifier_verify_proof).
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// get_schema_response: response of GET_SCHEMA request.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Schema json:
+/// {
+///     "name": string,
+///     "version": string,
+///     "attribute_names": array of strings,
+///     "seq_no": int, Seq. number of SCHEMA txn on the ledger.
+/// }
+///
+/// #Errors
+/// Common*
+/// Ledger*
+/// LedgerNotFound is returned if the reply has no data.
+#[no_mangle]
+pub extern fn sovrin_parse_get_schema_response(command_handle: i32,
+                                               get_schema_response: *const c_char,
+                                               cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                    schema_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(get_schema_response, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::ParseGetSchemaResponse(
+            get_schema_response,
+            Box::new(move |result| {
+                let (err, schema_json) = result_to_err_code_1!(result, String::new());
+                let schema_json = CStringUtils::string_to_cstring(schema_json);
+                cb(command_handle, err, schema_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Parses GET_CLAIM_DEF reply into claim definition json expected by anoncreds
+/// (sovrin_prover_create_and_store_claim_req, sovrin_prover_create_proof, sovrin_verifier_verify_proof).
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// get_claim_def_response: response of GET_CLAIM_DEF request.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Claim definition json:
+/// {
+///     "public_key": primary public key,
+///     "public_key_revocation": revocation public key (optional),
+///     "schema_seq_no": int, Seq. number of the schema.
+///     "signature_type": string,
+/// }
+///
+/// #Errors
+/// Common*
+/// Ledger*
+/// LedgerNotFound is returned if the reply has no data.
+#[no_mangle]
+pub extern fn sovrin_parse_get_claim_def_response(command_handle: i32,
+                                                  get_claim_def_response: *const c_char,
+                                                  cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                       claim_def_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(get_claim_def_response, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::ParseGetClaimDefResponse(
+            get_claim_def_response,
+            Box::new(move |result| {
+                let (err, claim_def_json) = result_to_err_code_1!(result, String::new());
+                let claim_def_json = CStringUtils::string_to_cstring(claim_def_json);
+                cb(command_handle, err, claim_def_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Parses GET_NYM reply into nym json.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// get_nym_response: response of GET_NYM request.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Nym json:
+/// {
+///     "did": string,
+///     "identifier": string, Id of Identity that created the nym.
+///     "verkey": string (optional),
+///     "role": string (optional), One of "TRUSTEE", "TGB", "STEWARD", "TRUST_ANCHOR".
+/// }
+///
+/// #Errors
+/// Common*
+/// Ledger*
+/// LedgerNotFound is returned if the reply has no data.
+#[no_mangle]
+pub extern fn sovrin_parse_get_nym_response(command_handle: i32,
+                                            get_nym_response: *const c_char,
+                                            cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                                 nym_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(get_nym_response, ErrorCode::CommonInvalidParam2);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::ParseGetNymResponse(
+            get_nym_response,
+            Box::new(move |result| {
+                let (err, nym_json) = result_to_err_code_1!(result, String::new());
+                let nym_json = CStringUtils::string_to_cstring(nym_json);
+                cb(command_handle, err, nym_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Assembles DID document from GET_NYM reply and GET_ATTRIB replies of the same DID,
+/// so agents don't need to parse raw ledger replies.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// get_nym_response: response of GET_NYM request.
+/// get_attrib_responses (optional): json array of responses of GET_ATTRIB requests (as strings).
+///                                  Responses without data are skipped.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// DID document json:
+/// {
+///     "id": string, "did:sov:" prefixed DID.
+///     "did": string,
+///     "verkey": string (optional), Full verkey, abbreviated one is expanded.
+///     "role": string (optional), One of "TRUSTEE", "TGB", "STEWARD", "TRUST_ANCHOR".
+///     "endpoint": json (optional), Value of "endpoint" attribute.
+///     "service": [{
+///         "type": string, "agent" for "ha" of endpoint or type of "service" attribute entry.
+///         "serviceEndpoint": string,
+///     }],
+///     "attributes": json, Map of all attribute names to their values.
+/// }
+///
+/// #Errors
+/// Common*
+/// Ledger*
+#[no_mangle]
+pub extern fn sovrin_build_did_document(command_handle: i32,
+                                        get_nym_response: *const c_char,
+                                        get_attrib_responses: *const c_char,
+                                        cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                             did_document_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_str!(get_nym_response, ErrorCode::CommonInvalidParam2);
+    check_useful_opt_c_str!(get_attrib_responses, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::BuildDidDocument(
+            get_nym_response,
+            get_attrib_responses,
+            Box::new(move |result| {
+                let (err, did_document_json) = result_to_err_code_1!(result, String::new());
+                let did_document_json = CStringUtils::string_to_cstring(did_document_json);
+                cb(command_handle, err, did_document_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Checks that transaction from write reply is included in the ledger with given root hash,
+/// so auditors can verify replies without trusting the nodes.
+///
+/// #Params
+/// command_handle: command handle to map callback to caller context.
+/// txn_json: transaction json as in "result" of write reply, seqNo is required.
+///           Fields added to reply by nodes (seqNo, auditPath, rootHash) aren't part of the ledger leaf.
+/// audit_path: json array of base58 encoded hashes of RFC 6962 audit path from the leaf to the root,
+///             as "auditPath" of write reply.
+/// root_hash: base58 encoded merkle root of the ledger, as "rootHash" of write reply.
+/// tree_size: number of transactions in the ledger with given root hash.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// valid: true - if transaction is included in the ledger, false - otherwise
+///
+/// #Errors
+/// Common*
+#[no_mangle]
+pub extern fn sovrin_verify_txn_inclusion(command_handle: i32,
+                                          txn_json: *const c_char,
+                                          audit_path: *const c_char,
+                                          root_hash: *const c_char,
+                                          tree_size: i32,
+                                          cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                               valid: bool)>) -> ErrorCode {
+    check_useful_c_str!(txn_json, ErrorCode::CommonInvalidParam2);
+    check_useful_c_str!(audit_path, ErrorCode::CommonInvalidParam3);
+    check_useful_c_str!(root_hash, ErrorCode::CommonInvalidParam4);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam6);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Ledger(LedgerCommand::VerifyTxnInclusion(
+            txn_json,
+            audit_path,
+            root_hash,
+            tree_size,
+            Box::new(move |result| {
+                let (err, valid) = result_to_err_code_1!(result, false);
+                cb(command_handle, err, valid)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
@@ -62,6 +62,19 @@ pub extern fn sovrin_create_pool_ledger_config(command_handle: i32,
 ///                        Use 0 to disable automatic refresh. Defaults to 24*60.
 ///     "networkTimeout": int (optional), Network timeout for communication with nodes in milliseconds.
 ///                       Defaults to 20000.
+///     "clientIdentity": string (optional), Name of client key pair used to authenticate to nodes.
+///                       Key pair is generated on first use and stored with pool ledger configuration,
+///                       so it can be set to DID to use separate identity per DID. Defaults to "default".
+///     "domainReplica": bool (optional), Also catches up domain ledger into local replica verified against
+///                      merkle root agreed by the pool. Replica is stored with pool ledger configuration
+///                      and is refreshed on each open and once it gets older than "replicaMaxAge".
+///                      Defaults to false.
+///     "replicaMaxAge": int (optional), Max age of the replica in seconds for GET_NYM, GET_SCHEMA and
+///                      GET_CLAIM_DEF requests to be answered from it instead of the pool.
+///                      Use 0 to always send them to the pool. Defaults to 300.
+///     "authChecks": bool (optional), Checks auth rules for NYM, ATTRIB, SCHEMA, CLAIM_DEF and NODE
+///                   requests in sign_and_submit_request against submitter role got by GET_NYM
+///                   before sending them, so requests the pool would reject fail fast. Defaults to false.
 /// }
 ///
 /// #Returns
@@ -121,7 +134,101 @@ pub extern fn sovrin_refresh_pool_ledger(command_handle: i32,
     result_to_err_code!(result)
 }
 
+/// Returns current state of opened pool ledger.
+///
+/// #Params
+/// handle: pool handle returned by sovrin_open_pool_ledger.
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Pool status json:
+/// {
+///     "phase": string, One of "CatchingUp", "Ready", "Refreshing", "Terminated", "Failed".
+///               Pool is "Refreshing" while it catches up stale domain ledger replica,
+///               requests are sent to the nodes meanwhile.
+///     "ledgerSize": int, Count of txns in local copy of pool ledger.
+///     "rootHash": string, Base58 merkle root hash of local copy of pool ledger.
+///     "f": int, Count of faulty nodes the pool tolerates.
+///     "nodes": [{
+///         "alias": string, Node alias.
+///         "state": string, One of "Connecting", "Connected", "Unreachable".
+///     }]
+/// }
+///
+/// #Errors
+/// Common*
+/// Ledger*
+#[no_mangle]
+pub extern fn sovrin_pool_get_status(command_handle: i32,
+                                     handle: i32,
+                                     cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                          status_json: *const c_char)>) -> ErrorCode {
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam3);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pool(PoolCommand::GetStatus(
+            handle,
+            Box::new(move |result| {
+                let (err, status_json) = result_to_err_code_1!(result, String::new());
+                let status_json = CStringUtils::string_to_cstring(status_json);
+                cb(command_handle, err, status_json.as_ptr())
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
+/// Subscribes to events of opened pool ledger.
+///
+/// event_cb is called for each pool state transition ("PhaseChanged"), pool ledger
+/// catch-up completion ("CatchupCompleted") and node connection changes
+/// ("NodeConnected", "NodeDisconnected") until the pool is closed. Node is reported
+/// disconnected when sending to it fails or it doesn't answer ping in time.
+///
+/// #Params
+/// handle: pool handle returned by sovrin_open_pool_ledger.
+/// event_cb: Callback that takes event json as parameter:
+/// {
+///     "event": string, Event type.
+///     "node": string (optional), Alias of the node for node events.
+///     "status": json, Pool status after the event (see sovrin_pool_get_status).
+/// }
+/// cb: Callback that takes command result as parameter.
+///
+/// #Returns
+/// Error code
+///
+/// #Errors
+/// Common*
+/// Ledger*
+#[no_mangle]
+pub extern fn sovrin_pool_subscribe(command_handle: i32,
+                                    handle: i32,
+                                    event_cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode,
+                                                               event_json: *const c_char)>,
+                                    cb: Option<extern fn(xcommand_handle: i32, err: ErrorCode)>) -> ErrorCode {
+    check_useful_c_callback!(event_cb, ErrorCode::CommonInvalidParam3);
+    check_useful_c_callback!(cb, ErrorCode::CommonInvalidParam4);
+
+    let result = CommandExecutor::instance()
+        .send(Command::Pool(PoolCommand::Subscribe(
+            handle,
+            Box::new(move |result| {
+                let (err, event_json) = result_to_err_code_1!(result, String::new());
+                let event_json = CStringUtils::string_to_cstring(event_json);
+                event_cb(command_handle, err, event_json.as_ptr())
+            }),
+            Box::new(move |result| {
+                let err = result_to_err_code!(result);
+                cb(command_handle, err)
+            })
+        )));
+
+    result_to_err_code!(result)
+}
+
 /// Closes opened pool ledger, opened nodes connections and frees allocated resources.
+/// Callbacks subscribed by sovrin_pool_subscribe aren't called after the pool is closed.
 ///
 /// #Params
 /// handle: pool handle returned by sovrin_open_pool_ledger.
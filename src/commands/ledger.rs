@@ -1,32 +1,72 @@
+extern crate serde_json;
+extern crate time;
+
+use self::serde_json::Value;
+use self::time::{Duration, Timespec};
+
+use commands::{Command, CommandExecutor};
 use errors::common::CommonError;
+use errors::ledger::LedgerError;
 use errors::pool::PoolError;
 use errors::sovrin::SovrinError;
 
 use services::anoncreds::AnoncredsService;
+use services::ledger::LedgerService;
+use services::ledger::types::NymData;
 use services::pool::PoolService;
 use services::signus::SignusService;
-use services::signus::types::MyDid;
+use services::signus::types::{MyDid, TheirDid};
 use services::wallet::WalletService;
 
 use utils::json::JsonDecodable;
+use utils::sequence::SequenceUtils;
 
 use super::utils::check_wallet_and_pool_handles_consistency;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
+/// How long nyms got for auth rules checks are reused without asking the pool again
+const NYM_CACHE_TTL_SECS: i64 = 60;
+
 pub enum LedgerCommand {
     SignAndSubmitRequest(
+        Option<i32>, // command handle to cancel request by
         i32, // pool handle
         i32, // wallet handle
         String, // submitter did
         String, // request json
         Box<Fn(Result<String, SovrinError>) + Send>),
+    SignRequest(
+        i32, // wallet handle
+        String, // submitter did
+        String, // request json
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    MultiSignRequest(
+        i32, // wallet handle
+        String, // submitter did
+        String, // request json
+        Box<Fn(Result<String, SovrinError>) + Send>),
     SubmitRequest(
+        Option<i32>, // command handle to cancel request by
         i32, // pool handle
         String, // request json
         Box<Fn(Result<String, SovrinError>) + Send>),
+    SubmitSignedRequestGetNymAck(
+        i32, // signature check id
+        String, // signer did
+        Result<String, SovrinError>, // GET_NYM result json or error
+    ),
+    AuthCheckGetNymAck(
+        i32, // auth check id
+        String, // did
+        Result<String, SovrinError>, // GET_NYM result json or error
+    ),
+    CancelRequest(
+        i32, // pool handle
+        i32, // command handle of submitted request
+        Box<Fn(Result<(), SovrinError>) + Send>),
     SubmitAck(
         i32, // cmd_id
         Result<String, SovrinError>, // result json or error
@@ -38,22 +78,34 @@ pub enum LedgerCommand {
     BuildNymRequest(
         String, // submitter did
         String, // target did
-        String, // verkey
-        String, // xref
-        String, // data
-        String, // role
+        Option<String>, // verkey
+        Option<String>, // xref
+        Option<String>, // data
+        Option<String>, // role
         Box<Fn(Result<String, SovrinError>) + Send>),
     BuildAttribRequest(
         String, // submitter did
         String, // target did
-        String, // hash
+        Option<String>, // hash
+        Option<String>, // raw
+        Option<String>, // enc
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    StoreEncryptedAttrib(
+        i32, // wallet handle
+        String, // submitter did
+        String, // target did
         String, // raw
-        String, // enc
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ResolveEncryptedAttrib(
+        i32, // wallet handle
+        String, // get attrib response
         Box<Fn(Result<String, SovrinError>) + Send>),
     BuildGetAttribRequest(
         String, // submitter did
         String, // target did
-        String, // data
+        Option<String>, // hash
+        Option<String>, // raw
+        Option<String>, // enc
         Box<Fn(Result<String, SovrinError>) + Send>),
     BuildGetNymRequest(
         String, // submitter did
@@ -65,6 +117,7 @@ pub enum LedgerCommand {
         Box<Fn(Result<String, SovrinError>) + Send>),
     BuildGetSchemaRequest(
         String, // submitter did
+        String, // dest
         String, // data
         Box<Fn(Result<String, SovrinError>) + Send>),
     BuildClaimDefRequest(
@@ -75,12 +128,82 @@ pub enum LedgerCommand {
     BuildGetClaimDefRequest(
         String, // submitter did
         String, // xref
+        Option<String>, // signature type
+        String, // origin
         Box<Fn(Result<String, SovrinError>) + Send>),
     BuildNodeRequest(
         String, // submitter did
         String, // target_did
         String, // data
-        Box<Fn(Result<String, SovrinError>) + Send>)
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildPoolUpgradeRequest(
+        String, // submitter did
+        String, // data
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildGetTxnRequest(
+        String, // submitter did
+        Option<String>, // ledger type
+        i32, // seq no
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ParseGetTxnResponse(
+        String, // response
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ParseGetSchemaResponse(
+        String, // response
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ParseGetClaimDefResponse(
+        String, // response
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    ParseGetNymResponse(
+        String, // response
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    BuildDidDocument(
+        String, // get nym response
+        Option<String>, // get attrib responses json
+        Box<Fn(Result<String, SovrinError>) + Send>),
+    VerifyTxnInclusion(
+        String, // txn json
+        String, // audit path json
+        String, // root hash
+        i32, // tree size
+        Box<Fn(Result<bool, SovrinError>) + Send>)
+}
+
+/// Signed request waiting for nyms to check auth rules before it's sent
+struct AuthCheck {
+    command_handle: Option<i32>,
+    pool_handle: i32,
+    request_json: String,
+    nyms: HashMap<String, Option<NymData>>,
+    pending_dids: HashSet<String>,
+    /// Pool commands of GET_NYM requests sent for the check
+    get_nym_cmd_ids: Vec<i32>,
+    cb: Box<Fn(Result<String, SovrinError>) + Send>,
+}
+
+/// Signatures of the request waiting for verkeys of its signers
+struct SignatureCheck {
+    command_handle: Option<i32>,
+    pool_handle: i32,
+    request_json: String,
+    verkeys: HashMap<String /* did */, String /* verkey */>,
+    pending_dids: HashSet<String>,
+    /// Pool commands of GET_NYM requests sent for the check
+    get_nym_cmd_ids: Vec<i32>,
+    cb: Box<Fn(Result<String, SovrinError>) + Send>,
+}
+
+/// Stage of the request submitted with command handle, which tells where its callback waits
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PendingRequest {
+    /// Request is received and is being prepared for sending
+    Received,
+    /// Signatures are being verified, callback waits in signature_checks
+    VerifyingSignature(i32 /* check id */),
+    /// Auth rules are being checked, callback waits in auth_checks
+    CheckingAuth(i32 /* check id */),
+    /// Request is sent to the pool, callback waits in send_callbacks
+    Sent(i32 /* cmd id */),
 }
 
 pub struct LedgerCommandExecutor {
@@ -88,39 +211,89 @@ pub struct LedgerCommandExecutor {
     pool_service: Rc<PoolService>,
     signus_service: Rc<SignusService>,
     wallet_service: Rc<WalletService>,
+    ledger_service: Rc<LedgerService>,
 
     send_callbacks: RefCell<HashMap<i32, Box<Fn(Result<String, SovrinError>)>>>,
+    cancel_handles: RefCell<HashMap<(i32 /* pool handle */, i32 /* command handle */), PendingRequest>>,
+    signature_checks: RefCell<HashMap<i32, SignatureCheck>>,
+    auth_checks: RefCell<HashMap<i32, AuthCheck>>,
+    nym_cache: RefCell<HashMap<(i32 /* pool handle */, String /* did */), (Option<NymData>, Timespec)>>,
 }
 
 impl LedgerCommandExecutor {
     pub fn new(anoncreds_service: Rc<AnoncredsService>,
                pool_service: Rc<PoolService>,
                signus_service: Rc<SignusService>,
-               wallet_service: Rc<WalletService>) -> LedgerCommandExecutor {
+               wallet_service: Rc<WalletService>,
+               ledger_service: Rc<LedgerService>) -> LedgerCommandExecutor {
         LedgerCommandExecutor {
             anoncreds_service: anoncreds_service,
             pool_service: pool_service,
             signus_service: signus_service,
             wallet_service: wallet_service,
+            ledger_service: ledger_service,
             send_callbacks: RefCell::new(HashMap::new()),
+            cancel_handles: RefCell::new(HashMap::new()),
+            signature_checks: RefCell::new(HashMap::new()),
+            auth_checks: RefCell::new(HashMap::new()),
+            nym_cache: RefCell::new(HashMap::new()),
         }
     }
 
     pub fn execute(&self, command: LedgerCommand) {
         match command {
-            LedgerCommand::SignAndSubmitRequest(pool_handle, wallet_handle, submitter_did, request_json, cb) => {
+            LedgerCommand::SignAndSubmitRequest(command_handle, pool_handle, wallet_handle, submitter_did, request_json, cb) => {
                 info!(target: "ledger_command_executor", "SignAndSubmitRequest command received");
-                self.sign_and_submit_request(pool_handle, wallet_handle, &submitter_did, &request_json, cb);
+                if let Err(err) = self._start_pending(command_handle, pool_handle) {
+                    return cb(Err(err));
+                }
+                self.sign_and_submit_request(command_handle, pool_handle, wallet_handle, &submitter_did, &request_json, cb);
+            }
+            LedgerCommand::SignRequest(wallet_handle, submitter_did, request_json, cb) => {
+                info!(target: "ledger_command_executor", "SignRequest command received");
+                self.sign_request(wallet_handle, &submitter_did, &request_json, cb);
+            }
+            LedgerCommand::MultiSignRequest(wallet_handle, submitter_did, request_json, cb) => {
+                info!(target: "ledger_command_executor", "MultiSignRequest command received");
+                self.multi_sign_request(wallet_handle, &submitter_did, &request_json, cb);
             }
-            LedgerCommand::SubmitRequest(handle, request_json, cb) => {
+            LedgerCommand::SubmitRequest(command_handle, handle, request_json, cb) => {
                 info!(target: "ledger_command_executor", "SubmitRequest command received");
-                self.submit_request(handle, &request_json, cb);
+                if let Err(err) = self._start_pending(command_handle, handle) {
+                    return cb(Err(err));
+                }
+                self.submit_request(command_handle, handle, &request_json, cb);
+            }
+            LedgerCommand::SubmitSignedRequestGetNymAck(check_id, did, result) => {
+                info!(target: "ledger_command_executor", "SubmitSignedRequestGetNymAck command received");
+                self.submit_signed_request_get_nym_ack(check_id, &did, result);
+            }
+            LedgerCommand::AuthCheckGetNymAck(check_id, did, result) => {
+                info!(target: "ledger_command_executor", "AuthCheckGetNymAck command received");
+                self.auth_check_get_nym_ack(check_id, &did, result);
+            }
+            LedgerCommand::CancelRequest(pool_handle, command_handle, cb) => {
+                info!(target: "ledger_command_executor", "CancelRequest command received");
+                cb(self.cancel_request(pool_handle, command_handle));
             }
             LedgerCommand::SubmitAck(handle, result) => {
                 info!(target: "ledger_command_executor", "SubmitAck command received");
-                self.send_callbacks.borrow_mut().remove(&handle)
-                    .expect("Expect callback to process ack command")
-                    (result);
+                match self.cancel_handles.try_borrow_mut() {
+                    Ok(mut cancel_handles) => cancel_handles.retain(|_, stage| *stage != PendingRequest::Sent(handle)),
+                    Err(err) => { error!("{:?}", err); }
+                }
+                match self.send_callbacks.try_borrow_mut() {
+                    Ok(mut cbs) => {
+                        match cbs.remove(&handle) {
+                            Some(cb) => cb(result),
+                            None => {
+                                error!("Can't process LedgerCommand::SubmitAck for handle {} with result {:?} - appropriate callback not found!",
+                                       handle, result);
+                            }
+                        }
+                    }
+                    Err(err) => { error!("{:?}", err); }
+                }
             }
             LedgerCommand::BuildGetDdoRequest(submitter_did, target_did, cb) => {
                 info!(target: "ledger_command_executor", "BuildGetDdoRequest command received");
@@ -128,15 +301,33 @@ impl LedgerCommandExecutor {
             }
             LedgerCommand::BuildNymRequest(submitter_did, target_did, verkey, xref, data, role, cb) => {
                 info!(target: "ledger_command_executor", "BuildNymRequest command received");
-                self.build_nym_request(&submitter_did, &target_did, &verkey, &xref, &data, &role, cb);
+                self.build_nym_request(&submitter_did, &target_did,
+                                       verkey.as_ref().map(String::as_str),
+                                       xref.as_ref().map(String::as_str),
+                                       data.as_ref().map(String::as_str),
+                                       role.as_ref().map(String::as_str), cb);
             }
             LedgerCommand::BuildAttribRequest(submitter_did, target_did, hash, raw, enc, cb) => {
                 info!(target: "ledger_command_executor", "BuildAttribRequest command received");
-                self.build_attrib_request(&submitter_did, &target_did, &hash, &raw, &enc, cb);
+                self.build_attrib_request(&submitter_did, &target_did,
+                                          hash.as_ref().map(String::as_str),
+                                          raw.as_ref().map(String::as_str),
+                                          enc.as_ref().map(String::as_str), cb);
             }
-            LedgerCommand::BuildGetAttribRequest(submitter_did, target_did, data, cb) => {
+            LedgerCommand::StoreEncryptedAttrib(wallet_handle, submitter_did, target_did, raw, cb) => {
+                info!(target: "ledger_command_executor", "StoreEncryptedAttrib command received");
+                self.store_encrypted_attrib(wallet_handle, &submitter_did, &target_did, &raw, cb);
+            }
+            LedgerCommand::ResolveEncryptedAttrib(wallet_handle, get_attrib_response, cb) => {
+                info!(target: "ledger_command_executor", "ResolveEncryptedAttrib command received");
+                self.resolve_encrypted_attrib(wallet_handle, &get_attrib_response, cb);
+            }
+            LedgerCommand::BuildGetAttribRequest(submitter_did, target_did, hash, raw, enc, cb) => {
                 info!(target: "ledger_command_executor", "BuildGetAttribRequest command received");
-                self.build_get_attrib_request(&submitter_did, &target_did, &data, cb);
+                self.build_get_attrib_request(&submitter_did, &target_did,
+                                              hash.as_ref().map(String::as_str),
+                                              raw.as_ref().map(String::as_str),
+                                              enc.as_ref().map(String::as_str), cb);
             }
             LedgerCommand::BuildGetNymRequest(submitter_did, target_did, cb) => {
                 info!(target: "ledger_command_executor", "BuildGetNymRequest command received");
@@ -146,26 +337,60 @@ impl LedgerCommandExecutor {
                 info!(target: "ledger_command_executor", "BuildSchemaRequest command received");
                 self.build_schema_request(&submitter_did, &data, cb);
             }
-            LedgerCommand::BuildGetSchemaRequest(submitter_did, data, cb) => {
+            LedgerCommand::BuildGetSchemaRequest(submitter_did, dest, data, cb) => {
                 info!(target: "ledger_command_executor", "BuildGetSchemaRequest command received");
-                self.build_get_schema_request(&submitter_did, &data, cb);
+                self.build_get_schema_request(&submitter_did, &dest, &data, cb);
             }
             LedgerCommand::BuildClaimDefRequest(submitter_did, xref, data, cb) => {
                 info!(target: "ledger_command_executor", "BuildClaimDefRequest command received");
                 self.build_issuer_key_request(&submitter_did, &xref, &data, cb);
             }
-            LedgerCommand::BuildGetClaimDefRequest(submitter_did, xref, cb) => {
+            LedgerCommand::BuildGetClaimDefRequest(submitter_did, xref, signature_type, origin, cb) => {
                 info!(target: "ledger_command_executor", "BuildGetClaimDefRequest command received");
-                self.build_get_issuer_key_request(&submitter_did, &xref, cb);
+                self.build_get_issuer_key_request(&submitter_did, &xref,
+                                                  signature_type.as_ref().map(String::as_str), &origin, cb);
             }
             LedgerCommand::BuildNodeRequest(submitter_did, target_did, data, cb) => {
                 info!(target: "ledger_command_executor", "BuildNodeRequest command received");
                 self.build_node_key_request(&submitter_did, &target_did, &data, cb);
             }
+            LedgerCommand::BuildPoolUpgradeRequest(submitter_did, data, cb) => {
+                info!(target: "ledger_command_executor", "BuildPoolUpgradeRequest command received");
+                self.build_pool_upgrade_request(&submitter_did, &data, cb);
+            }
+            LedgerCommand::BuildGetTxnRequest(submitter_did, ledger_type, seq_no, cb) => {
+                info!(target: "ledger_command_executor", "BuildGetTxnRequest command received");
+                self.build_get_txn_request(&submitter_did, ledger_type.as_ref().map(String::as_str), seq_no, cb);
+            }
+            LedgerCommand::ParseGetTxnResponse(response, cb) => {
+                info!(target: "ledger_command_executor", "ParseGetTxnResponse command received");
+                self.parse_get_txn_response(&response, cb);
+            }
+            LedgerCommand::ParseGetSchemaResponse(response, cb) => {
+                info!(target: "ledger_command_executor", "ParseGetSchemaResponse command received");
+                self.parse_get_schema_response(&response, cb);
+            }
+            LedgerCommand::ParseGetClaimDefResponse(response, cb) => {
+                info!(target: "ledger_command_executor", "ParseGetClaimDefResponse command received");
+                self.parse_get_claim_def_response(&response, cb);
+            }
+            LedgerCommand::ParseGetNymResponse(response, cb) => {
+                info!(target: "ledger_command_executor", "ParseGetNymResponse command received");
+                self.parse_get_nym_response(&response, cb);
+            }
+            LedgerCommand::BuildDidDocument(get_nym_response, get_attrib_responses, cb) => {
+                info!(target: "ledger_command_executor", "BuildDidDocument command received");
+                self.build_did_document(&get_nym_response, get_attrib_responses.as_ref().map(String::as_str), cb);
+            }
+            LedgerCommand::VerifyTxnInclusion(txn_json, audit_path, root_hash, tree_size, cb) => {
+                info!(target: "ledger_command_executor", "VerifyTxnInclusion command received");
+                self.verify_txn_inclusion(&txn_json, &audit_path, &root_hash, tree_size, cb);
+            }
         };
     }
 
     fn sign_and_submit_request(&self,
+                               command_handle: Option<i32>,
                                pool_handle: i32,
                                wallet_handle: i32,
                                submitter_did: &str,
@@ -175,7 +400,7 @@ impl LedgerCommandExecutor {
             // FIXME REMOVE
             // FIXME just remove with block after errors refactoring
             let cb = |se: Result<(), SovrinError>| {
-                cb(Err(se.err().unwrap()))
+                self._fail_request(command_handle, pool_handle, &cb, se.err().unwrap())
             };
             //FIXME REMOVE code above and extract next line from the block
             check_wallet_and_pool_handles_consistency!(self.wallet_service, self.pool_service,
@@ -183,90 +408,565 @@ impl LedgerCommandExecutor {
                                                    );
         }
         match self._sign_request(wallet_handle, submitter_did, request_json) {
-            Ok(signed_request) => self.submit_request(pool_handle, signed_request.as_str(), cb),
-            Err(err) => cb(Err(err))
+            Ok(signed_request) => self._check_auth_and_send_request(command_handle, pool_handle, signed_request, cb),
+            Err(err) => self._fail_request(command_handle, pool_handle, &cb, err)
+        }
+    }
+
+    /// Auth rules are checked only if enabled for the pool. Nyms needed for the check are
+    /// taken from the cache or got by GET_NYM requests sent in parallel.
+    fn _check_auth_and_send_request(&self,
+                                    command_handle: Option<i32>,
+                                    pool_handle: i32,
+                                    request_json: String,
+                                    cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        match self.pool_service.auth_checks(pool_handle) {
+            Ok(true) => {}
+            Ok(false) => return self._send_request(command_handle, pool_handle, &request_json, cb),
+            Err(err) => return self._fail_request(command_handle, pool_handle, &cb, SovrinError::PoolError(err))
+        }
+        let dids = match self.ledger_service.auth_check_dids(&request_json) {
+            Ok(dids) => dids,
+            Err(err) => return self._fail_request(command_handle, pool_handle, &cb, SovrinError::from(err))
+        };
+        let mut get_nym_requests: Vec<(String, String)> = Vec::new();
+        let mut nyms: HashMap<String, Option<NymData>> = HashMap::new();
+        for did in &dids {
+            match self._get_cached_nym(pool_handle, did) {
+                Some(nym) => { nyms.insert(did.clone(), nym); }
+                None => match self.ledger_service.build_get_nym_request(&dids[0], did) {
+                    Ok(get_nym_request) => get_nym_requests.push((did.clone(), get_nym_request)),
+                    Err(err) => return self._fail_request(command_handle, pool_handle, &cb, SovrinError::from(err))
+                }
+            }
+        }
+        let mut auth_check = AuthCheck {
+            command_handle: command_handle,
+            pool_handle: pool_handle,
+            request_json: request_json,
+            nyms: nyms,
+            pending_dids: get_nym_requests.iter().map(|&(ref did, _)| did.clone()).collect(),
+            get_nym_cmd_ids: Vec::new(),
+            cb: cb,
+        };
+        if get_nym_requests.is_empty() {
+            return self._finish_auth_check(auth_check);
+        }
+
+        let check_id: i32 = SequenceUtils::get_next_id();
+        for (did, get_nym_request) in get_nym_requests {
+            let cmd_id = self._send_tx(None, pool_handle, &get_nym_request, Box::new(move |result| {
+                CommandExecutor::instance()
+                    .send(Command::Ledger(LedgerCommand::AuthCheckGetNymAck(
+                        check_id,
+                        did.clone(),
+                        result
+                    ))).unwrap();
+            }));
+            auth_check.get_nym_cmd_ids.extend(cmd_id);
+        }
+        match self.auth_checks.try_borrow_mut() {
+            Ok(mut auth_checks) => { auth_checks.insert(check_id, auth_check); }
+            Err(err) => return self._fail_request(command_handle, pool_handle, &auth_check.cb,
+                                                  SovrinError::from(CommonError::InvalidState(format!("{:?}", err))))
+        }
+        self._set_pending(command_handle, pool_handle, PendingRequest::CheckingAuth(check_id));
+    }
+
+    fn auth_check_get_nym_ack(&self,
+                              check_id: i32,
+                              did: &str,
+                              result: Result<String, SovrinError>) {
+        let auth_check = match self.auth_checks.try_borrow_mut() {
+            Ok(mut auth_checks) => auth_checks.remove(&check_id),
+            Err(err) => {
+                error!("{:?}", err);
+                return;
+            }
+        };
+        let mut auth_check = match auth_check {
+            Some(auth_check) => auth_check,
+            None => {
+                // Check has already failed on GET_NYM of other did
+                debug!("Auth check {} isn't pending anymore, GET_NYM result of {:?} is skipped", check_id, did);
+                return;
+            }
+        };
+        let nym = match result.and_then(|response| self.ledger_service.parse_get_nym_data(did, &response).map_err(SovrinError::from)) {
+            Ok(nym) => nym,
+            Err(err) => return self._fail_request(auth_check.command_handle, auth_check.pool_handle, &auth_check.cb, err)
+        };
+        self._cache_nym(auth_check.pool_handle, did, &nym);
+        auth_check.pending_dids.remove(did);
+        auth_check.nyms.insert(did.to_string(), nym);
+
+        if auth_check.pending_dids.is_empty() {
+            return self._finish_auth_check(auth_check);
+        }
+        match self.auth_checks.try_borrow_mut() {
+            Ok(mut auth_checks) => { auth_checks.insert(check_id, auth_check); }
+            Err(err) => self._fail_request(auth_check.command_handle, auth_check.pool_handle, &auth_check.cb,
+                                           SovrinError::from(CommonError::InvalidState(format!("{:?}", err))))
         }
     }
 
+    fn _finish_auth_check(&self, auth_check: AuthCheck) {
+        let AuthCheck { command_handle, pool_handle, request_json, nyms, cb, .. } = auth_check;
+        if self._is_cancelled(command_handle, pool_handle) {
+            debug!("Request with command handle {:?} was cancelled during auth check", command_handle);
+            return;
+        }
+        match self.ledger_service.check_auth_rules(&request_json, &nyms) {
+            Ok(()) => self._send_request(command_handle, pool_handle, &request_json, cb),
+            Err(err) => self._fail_request(command_handle, pool_handle, &cb, SovrinError::from(err))
+        }
+    }
+
+    fn _get_cached_nym(&self, pool_handle: i32, did: &str) -> Option<Option<NymData>> {
+        let now = time::get_time();
+        self.nym_cache.borrow()
+            .get(&(pool_handle, did.to_string()))
+            .and_then(|&(ref nym, cached_at)|
+                if now - cached_at < Duration::seconds(NYM_CACHE_TTL_SECS) { Some(nym.clone()) } else { None })
+    }
+
+    fn _cache_nym(&self, pool_handle: i32, did: &str, nym: &Option<NymData>) {
+        self.nym_cache.borrow_mut().insert((pool_handle, did.to_string()), (nym.clone(), time::get_time()));
+    }
+
+    fn sign_request(&self,
+                    wallet_handle: i32,
+                    submitter_did: &str,
+                    request_json: &str,
+                    cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        let request: Value = match serde_json::from_str(request_json) {
+            Ok(request) => request,
+            Err(err) => return cb(Err(SovrinError::from(CommonError::InvalidParam4(format!("Invalid request json: {}", err)))))
+        };
+        if request["identifier"].as_str() != Some(submitter_did) {
+            return cb(Err(SovrinError::from(CommonError::InvalidParam4(
+                format!("Request identifier {} doesn't match submitter did {:?}", request["identifier"], submitter_did)))));
+        }
+        cb(self._sign_request(wallet_handle, submitter_did, request_json));
+    }
+
     fn _sign_request(&self,
                      wallet_handle: i32,
                      submitter_did: &str,
                      request_json: &str,
     ) -> Result<String, SovrinError> {
-        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", submitter_did))?;
-        let my_did = MyDid::from_json(&my_did_json)
-            .map_err(|err| CommonError::InvalidState(format!("Invalid my_did_json: {}", err.to_string())))?;
-
+        let my_did = self._get_my_did(wallet_handle, submitter_did)?;
         let signed_request = self.signus_service.sign(&my_did, request_json)?;
         Ok(signed_request)
     }
 
+    /// Endorser doesn't have to be the request author, so submitter did isn't checked
+    /// against request identifier here
+    fn multi_sign_request(&self,
+                          wallet_handle: i32,
+                          submitter_did: &str,
+                          request_json: &str,
+                          cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        match serde_json::from_str::<Value>(request_json) {
+            Ok(ref request) if request["identifier"].is_string() => {}
+            Ok(_) => return cb(Err(SovrinError::from(CommonError::InvalidParam4("Request has no identifier".to_string())))),
+            Err(err) => return cb(Err(SovrinError::from(CommonError::InvalidParam4(format!("Invalid request json: {}", err)))))
+        };
+        cb(self._multi_sign_request(wallet_handle, submitter_did, request_json));
+    }
+
+    fn _multi_sign_request(&self,
+                           wallet_handle: i32,
+                           submitter_did: &str,
+                           request_json: &str,
+    ) -> Result<String, SovrinError> {
+        let my_did = self._get_my_did(wallet_handle, submitter_did)?;
+        let signed_request = self.signus_service.multi_sign(&my_did, request_json)?;
+        Ok(signed_request)
+    }
+
+    fn _get_my_did(&self, wallet_handle: i32, did: &str) -> Result<MyDid, SovrinError> {
+        let my_did_json = self.wallet_service.get(wallet_handle, &format!("my_did::{}", did))?;
+        let my_did = MyDid::from_json(&my_did_json)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid my_did_json: {}", err.to_string())))?;
+        Ok(my_did)
+    }
+
+    /// Signed request is sent only after all its signatures are verified against verkeys
+    /// of the signers got by GET_NYM from the same pool. Multi-signed request must be
+    /// signed by its author in addition to other signers.
     fn submit_request(&self,
-                      handle: i32,
+                      command_handle: Option<i32>,
+                      pool_handle: i32,
                       request_json: &str,
                       cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        let request: Value = match serde_json::from_str(request_json) {
+            Ok(request) => request,
+            Err(_) => return self._send_request(command_handle, pool_handle, request_json, cb)
+        };
+        if !request["signature"].is_string() && !request["signatures"].is_object() {
+            return self._send_request(command_handle, pool_handle, request_json, cb);
+        }
+        let identifier = match request["identifier"].as_str() {
+            Some(identifier) => identifier,
+            None => return self._fail_request(command_handle, pool_handle, &cb, SovrinError::from(
+                CommonError::InvalidParam3("Signed request has no identifier".to_string())))
+        };
+        let signer_dids: Vec<String> = match request["signatures"].as_object() {
+            Some(_) if !request["signature"].is_null() => return self._fail_request(command_handle, pool_handle, &cb, SovrinError::from(
+                CommonError::InvalidParam3("Request has both signature and signatures".to_string()))),
+            Some(signatures) if !signatures.contains_key(identifier) => return self._fail_request(command_handle, pool_handle, &cb, SovrinError::from(
+                LedgerError::SecurityError(format!("Multi-signed request has no signature of its author {:?}", identifier)))),
+            Some(signatures) => signatures.keys().cloned().collect(),
+            None => vec![identifier.to_string()]
+        };
+        let mut get_nym_requests: Vec<(String, String)> = Vec::new();
+        for did in signer_dids {
+            match self.ledger_service.build_get_nym_request(identifier, &did) {
+                Ok(get_nym_request) => get_nym_requests.push((did, get_nym_request)),
+                Err(err) => return self._fail_request(command_handle, pool_handle, &cb, SovrinError::from(
+                    CommonError::InvalidParam3(format!("Signed request has invalid signer {:?}: {}", did, err))))
+            }
+        }
+
+        let mut signature_check = SignatureCheck {
+            command_handle: command_handle,
+            pool_handle: pool_handle,
+            request_json: request_json.to_string(),
+            verkeys: HashMap::new(),
+            pending_dids: get_nym_requests.iter().map(|&(ref did, _)| did.clone()).collect(),
+            get_nym_cmd_ids: Vec::new(),
+            cb: cb,
+        };
+        let check_id: i32 = SequenceUtils::get_next_id();
+        for (did, get_nym_request) in get_nym_requests {
+            let cmd_id = self._send_tx(None, pool_handle, &get_nym_request, Box::new(move |result| {
+                CommandExecutor::instance()
+                    .send(Command::Ledger(LedgerCommand::SubmitSignedRequestGetNymAck(
+                        check_id,
+                        did.clone(),
+                        result
+                    ))).unwrap();
+            }));
+            signature_check.get_nym_cmd_ids.extend(cmd_id);
+        }
+        match self.signature_checks.try_borrow_mut() {
+            Ok(mut signature_checks) => { signature_checks.insert(check_id, signature_check); }
+            Err(err) => return self._fail_request(command_handle, pool_handle, &signature_check.cb,
+                                                  SovrinError::from(CommonError::InvalidState(format!("{:?}", err))))
+        }
+        self._set_pending(command_handle, pool_handle, PendingRequest::VerifyingSignature(check_id));
+    }
+
+    fn submit_signed_request_get_nym_ack(&self,
+                                         check_id: i32,
+                                         did: &str,
+                                         result: Result<String, SovrinError>) {
+        let signature_check = match self.signature_checks.try_borrow_mut() {
+            Ok(mut signature_checks) => signature_checks.remove(&check_id),
+            Err(err) => {
+                error!("{:?}", err);
+                return;
+            }
+        };
+        let mut signature_check = match signature_check {
+            Some(signature_check) => signature_check,
+            None => {
+                // Check has already failed on GET_NYM of other signer or was cancelled
+                debug!("Signature check {} isn't pending anymore, GET_NYM result of {:?} is skipped", check_id, did);
+                return;
+            }
+        };
+        if self._is_cancelled(signature_check.command_handle, signature_check.pool_handle) {
+            debug!("Request with command handle {:?} was cancelled during signature verification", signature_check.command_handle);
+            return;
+        }
+        let verkey = result
+            .and_then(|response| self.ledger_service.parse_get_nym_verkey(did, &response).map_err(SovrinError::from))
+            .and_then(|verkey| verkey.ok_or(SovrinError::from(
+                LedgerError::SecurityError(format!("Signer {:?} has no verkey on the ledger", did)))));
+        let verkey = match verkey {
+            Ok(verkey) => verkey,
+            Err(err) => return self._fail_request(signature_check.command_handle, signature_check.pool_handle,
+                                                  &signature_check.cb, err)
+        };
+        signature_check.pending_dids.remove(did);
+        signature_check.verkeys.insert(did.to_string(), verkey);
+
+        if signature_check.pending_dids.is_empty() {
+            let SignatureCheck { command_handle, pool_handle, request_json, verkeys, cb, .. } = signature_check;
+            return match self._verify_request_signatures(&request_json, &verkeys) {
+                Ok(()) => self._send_request(command_handle, pool_handle, &request_json, cb),
+                Err(err) => self._fail_request(command_handle, pool_handle, &cb, err)
+            };
+        }
+        match self.signature_checks.try_borrow_mut() {
+            Ok(mut signature_checks) => { signature_checks.insert(check_id, signature_check); }
+            Err(err) => self._fail_request(signature_check.command_handle, signature_check.pool_handle, &signature_check.cb,
+                                           SovrinError::from(CommonError::InvalidState(format!("{:?}", err))))
+        }
+    }
+
+    /// Single signature is kept in "signature", signatures of multi-signed request are kept
+    /// in "signatures" by signer DID. Each of them signs the request without both fields.
+    fn _verify_request_signatures(&self,
+                                  request_json: &str,
+                                  verkeys: &HashMap<String, String>) -> Result<(), SovrinError> {
+        let request: Value = serde_json::from_str(request_json)
+            .map_err(|err| CommonError::InvalidState(format!("Invalid request json: {}", err)))?;
+        let is_multi_signed = request["signatures"].is_object();
+        for (did, verkey) in verkeys {
+            let their_did = TheirDid::new(did.clone(), None, None, Some(verkey.clone()));
+            let is_valid = if is_multi_signed {
+                self.signus_service.verify_multi_signature(&their_did, request_json)?
+            } else {
+                self.signus_service.verify(&their_did, request_json)?
+            };
+            if !is_valid {
+                return Err(SovrinError::from(LedgerError::SecurityError(
+                    format!("Signature of request doesn't match verkey of signer {:?}", did))));
+            }
+        }
+        Ok(())
+    }
+
+    fn _send_request(&self,
+                     command_handle: Option<i32>,
+                     handle: i32,
+                     request_json: &str,
+                     cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        self._send_tx(command_handle, handle, request_json, cb);
+    }
+
+    /// Returns id of the pool command the request is sent with, None if sending failed
+    fn _send_tx(&self,
+                command_handle: Option<i32>,
+                handle: i32,
+                request_json: &str,
+                cb: Box<Fn(Result<String, SovrinError>) + Send>) -> Option<i32> {
         let x: Result<i32, PoolError> = self.pool_service.send_tx(handle, request_json);
         match x {
-            Ok(cmd_id) => { self.send_callbacks.borrow_mut().insert(cmd_id, cb); }
-            Err(err) => { cb(Err(SovrinError::PoolError(err))); }
+            Ok(cmd_id) => {
+                self.send_callbacks.borrow_mut().insert(cmd_id, cb);
+                self._set_pending(command_handle, handle, PendingRequest::Sent(cmd_id));
+                Some(cmd_id)
+            }
+            Err(err) => {
+                self._fail_request(command_handle, handle, &cb, SovrinError::PoolError(err));
+                None
+            }
+        }
+    }
+
+    /// Command handle is chosen by the caller, so it can't identify two pending requests at once
+    fn _start_pending(&self, command_handle: Option<i32>, pool_handle: i32) -> Result<(), SovrinError> {
+        let command_handle = match command_handle {
+            Some(command_handle) => command_handle,
+            None => return Ok(())
         };
+        let mut cancel_handles = self.cancel_handles.try_borrow_mut()
+            .map_err(|err| CommonError::InvalidState(format!("{:?}", err)))?;
+        if cancel_handles.contains_key(&(pool_handle, command_handle)) {
+            return Err(SovrinError::from(CommonError::InvalidState(
+                format!("Request with command handle {} is already pending in pool {}", command_handle, pool_handle))));
+        }
+        cancel_handles.insert((pool_handle, command_handle), PendingRequest::Received);
+        Ok(())
+    }
+
+    /// GET_NYM requests of the check have no other callers, so they aren't awaited anymore
+    fn _cancel_get_nym_requests(&self, pool_handle: i32, cmd_ids: &[i32]) -> Result<(), SovrinError> {
+        let mut send_callbacks = self.send_callbacks.try_borrow_mut()
+            .map_err(|err| CommonError::InvalidState(format!("{:?}", err)))?;
+        for cmd_id in cmd_ids {
+            if send_callbacks.remove(cmd_id).is_some() {
+                self.pool_service.cancel_tx(pool_handle, *cmd_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn _set_pending(&self, command_handle: Option<i32>, pool_handle: i32, stage: PendingRequest) {
+        if let Some(command_handle) = command_handle {
+            self.cancel_handles.borrow_mut().insert((pool_handle, command_handle), stage);
+        }
+    }
+
+    /// Cancelled request is forgotten, its callback is already called by cancel_request
+    fn _is_cancelled(&self, command_handle: Option<i32>, pool_handle: i32) -> bool {
+        match command_handle {
+            Some(command_handle) => !self.cancel_handles.borrow().contains_key(&(pool_handle, command_handle)),
+            None => false
+        }
+    }
+
+    /// Request failed before it was sent, so there is nothing to cancel anymore
+    fn _fail_request(&self,
+                     command_handle: Option<i32>,
+                     pool_handle: i32,
+                     cb: &Box<Fn(Result<String, SovrinError>) + Send>,
+                     err: SovrinError) {
+        if let Some(command_handle) = command_handle {
+            self.cancel_handles.borrow_mut().remove(&(pool_handle, command_handle));
+        }
+        cb(Err(err))
+    }
+
+    /// Request can be cancelled at any stage: while the signature or auth rules are being
+    /// checked it isn't sent at all, once sent replies of the pool for it are ignored
+    fn cancel_request(&self,
+                      pool_handle: i32,
+                      command_handle: i32) -> Result<(), SovrinError> {
+        let stage = self.cancel_handles.try_borrow_mut()
+            .map_err(|err| CommonError::InvalidState(format!("{:?}", err)))?
+            .remove(&(pool_handle, command_handle))
+            .ok_or(LedgerError::NotFound(
+                format!("No pending request for command handle {} in pool {}", command_handle, pool_handle)))?;
+
+        let cb: Option<Box<Fn(Result<String, SovrinError>)>> = match stage {
+            PendingRequest::Received => {
+                warn!("Request with command handle {} is cancelled before it was prepared", command_handle);
+                None
+            }
+            PendingRequest::VerifyingSignature(check_id) => {
+                let signature_check = self.signature_checks.try_borrow_mut()
+                    .map_err(|err| CommonError::InvalidState(format!("{:?}", err)))?
+                    .remove(&check_id);
+                match signature_check {
+                    Some(signature_check) => {
+                        self._cancel_get_nym_requests(pool_handle, &signature_check.get_nym_cmd_ids)?;
+                        Some(signature_check.cb as Box<Fn(Result<String, SovrinError>)>)
+                    }
+                    None => None
+                }
+            }
+            PendingRequest::CheckingAuth(check_id) => {
+                let auth_check = self.auth_checks.try_borrow_mut()
+                    .map_err(|err| CommonError::InvalidState(format!("{:?}", err)))?
+                    .remove(&check_id);
+                match auth_check {
+                    Some(auth_check) => {
+                        self._cancel_get_nym_requests(pool_handle, &auth_check.get_nym_cmd_ids)?;
+                        Some(auth_check.cb as Box<Fn(Result<String, SovrinError>)>)
+                    }
+                    None => None
+                }
+            }
+            PendingRequest::Sent(cmd_id) => {
+                self.pool_service.cancel_tx(pool_handle, cmd_id)?;
+                self.send_callbacks.try_borrow_mut()
+                    .map_err(|err| CommonError::InvalidState(format!("{:?}", err)))?
+                    .remove(&cmd_id)
+            }
+        };
+        if let Some(cb) = cb {
+            cb(Err(SovrinError::LedgerError(LedgerError::RequestCancelled(
+                format!("Request with command handle {} was cancelled", command_handle)))));
+        }
+        Ok(())
     }
 
     fn build_get_ddo_request(&self,
                              submitter_did: &str,
                              target_did: &str,
                              cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_get_ddo_request(submitter_did, target_did)
+            .map_err(SovrinError::from));
     }
 
     fn build_nym_request(&self,
                          submitter_did: &str,
                          target_did: &str,
-                         verkey: &str,
-                         xref: &str,
-                         data: &str,
-                         role: &str,
+                         verkey: Option<&str>,
+                         xref: Option<&str>,
+                         data: Option<&str>,
+                         role: Option<&str>,
                          cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_nym_request(submitter_did, target_did, verkey, xref, data, role)
+            .map_err(SovrinError::from));
     }
 
     fn build_attrib_request(&self,
                             submitter_did: &str,
                             target_did: &str,
-                            hash: &str,
-                            raw: &str,
-                            enc: &str,
+                            hash: Option<&str>,
+                            raw: Option<&str>,
+                            enc: Option<&str>,
                             cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_attrib_request(submitter_did, target_did, hash, raw, enc)
+            .map_err(SovrinError::from));
+    }
+
+    fn store_encrypted_attrib(&self,
+                              wallet_handle: i32,
+                              submitter_did: &str,
+                              target_did: &str,
+                              raw: &str,
+                              cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._store_encrypted_attrib(wallet_handle, submitter_did, target_did, raw));
+    }
+
+    /// Key is stored before the request is returned, so the attribute can always be decrypted once it's written
+    fn _store_encrypted_attrib(&self,
+                               wallet_handle: i32,
+                               submitter_did: &str,
+                               target_did: &str,
+                               raw: &str) -> Result<String, SovrinError> {
+        let (request, enc_hash, key) = self.ledger_service.build_encrypted_attrib_request(submitter_did, target_did, raw)?;
+        self.wallet_service.set(wallet_handle, &format!("attrib_key::{}", enc_hash), &key)?;
+        Ok(request)
+    }
+
+    fn resolve_encrypted_attrib(&self,
+                                wallet_handle: i32,
+                                get_attrib_response: &str,
+                                cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._resolve_encrypted_attrib(wallet_handle, get_attrib_response));
+    }
+
+    fn _resolve_encrypted_attrib(&self,
+                                 wallet_handle: i32,
+                                 get_attrib_response: &str) -> Result<String, SovrinError> {
+        let (enc, enc_hash) = self.ledger_service.parse_get_attrib_enc(get_attrib_response)?;
+        let key = self.wallet_service.get(wallet_handle, &format!("attrib_key::{}", enc_hash))?;
+        let raw = self.ledger_service.decrypt_attrib(&enc, &key)?;
+        Ok(raw)
     }
 
     fn build_get_attrib_request(&self,
                                 submitter_did: &str,
                                 target_did: &str,
-                                data: &str,
+                                hash: Option<&str>,
+                                raw: Option<&str>,
+                                enc: Option<&str>,
                                 cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_get_attrib_request(submitter_did, target_did, hash, raw, enc)
+            .map_err(SovrinError::from));
     }
 
     fn build_get_nym_request(&self,
                              submitter_did: &str,
                              target_did: &str,
                              cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_get_nym_request(submitter_did, target_did)
+            .map_err(SovrinError::from));
     }
 
     fn build_schema_request(&self,
                             submitter_did: &str,
                             data: &str,
                             cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_schema_request(submitter_did, data)
+            .map_err(SovrinError::from));
     }
 
     fn build_get_schema_request(&self,
                                 submitter_did: &str,
+                                dest: &str,
                                 data: &str,
                                 cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_get_schema_request(submitter_did, dest, data)
+            .map_err(SovrinError::from));
     }
 
     fn build_issuer_key_request(&self,
@@ -274,14 +974,18 @@ impl LedgerCommandExecutor {
                                 xref: &str,
                                 data: &str,
                                 cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_claim_def_request(submitter_did, xref, data)
+            .map_err(SovrinError::from));
     }
 
     fn build_get_issuer_key_request(&self,
                                     submitter_did: &str,
                                     xref: &str,
+                                    signature_type: Option<&str>,
+                                    origin: &str,
                                     cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_get_claim_def_request(submitter_did, xref, signature_type, origin)
+            .map_err(SovrinError::from));
     }
 
     fn build_node_key_request(&self,
@@ -289,6 +993,81 @@ impl LedgerCommandExecutor {
                               target_did: &str,
                               data: &str,
                               cb: Box<Fn(Result<String, SovrinError>) + Send>) {
-        cb(Ok("".to_string()));
+        cb(self.ledger_service.build_node_request(submitter_did, target_did, data)
+            .map_err(SovrinError::from));
     }
-}
\ No newline at end of file
+
+    fn build_pool_upgrade_request(&self,
+                                  submitter_did: &str,
+                                  data: &str,
+                                  cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.build_pool_upgrade_request(submitter_did, data)
+            .map_err(SovrinError::from));
+    }
+
+    fn build_get_txn_request(&self,
+                             submitter_did: &str,
+                             ledger_type: Option<&str>,
+                             seq_no: i32,
+                             cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.build_get_txn_request(submitter_did, ledger_type, seq_no)
+            .map_err(SovrinError::from));
+    }
+
+    fn parse_get_txn_response(&self,
+                              response: &str,
+                              cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.parse_get_txn_response(response)
+            .map_err(SovrinError::from));
+    }
+
+    fn parse_get_schema_response(&self,
+                                 response: &str,
+                                 cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.parse_get_schema_response(response)
+            .map_err(SovrinError::from));
+    }
+
+    fn parse_get_claim_def_response(&self,
+                                    response: &str,
+                                    cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.parse_get_claim_def_response(response)
+            .map_err(SovrinError::from));
+    }
+
+    fn parse_get_nym_response(&self,
+                              response: &str,
+                              cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.ledger_service.parse_get_nym_response(response)
+            .map_err(SovrinError::from));
+    }
+
+    fn build_did_document(&self,
+                          get_nym_response: &str,
+                          get_attrib_responses: Option<&str>,
+                          cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self._build_did_document(get_nym_response, get_attrib_responses));
+    }
+
+    fn verify_txn_inclusion(&self,
+                            txn_json: &str,
+                            audit_path: &str,
+                            root_hash: &str,
+                            tree_size: i32,
+                            cb: Box<Fn(Result<bool, SovrinError>) + Send>) {
+        cb(self.ledger_service.verify_txn_inclusion(txn_json, audit_path, root_hash, tree_size)
+            .map_err(SovrinError::from));
+    }
+
+    fn _build_did_document(&self,
+                           get_nym_response: &str,
+                           get_attrib_responses: Option<&str>) -> Result<String, SovrinError> {
+        let get_attrib_responses: Vec<String> = match get_attrib_responses {
+            Some(get_attrib_responses) => serde_json::from_str(get_attrib_responses)
+                .map_err(|err| CommonError::InvalidParam3(format!("Invalid get_attrib_responses: {}", err.to_string())))?,
+            None => Vec::new()
+        };
+        let did_document = self.ledger_service.build_did_document(get_nym_response, &get_attrib_responses)?;
+        Ok(did_document)
+    }
+}
@@ -16,6 +16,7 @@ use commands::wallet::{WalletCommand, WalletCommandExecutor};
 use errors::common::CommonError;
 
 use services::anoncreds::AnoncredsService;
+use services::ledger::LedgerService;
 use services::pool::PoolService;
 use services::wallet::WalletService;
 use services::signus::SignusService;
@@ -62,9 +63,10 @@ impl CommandExecutor {
                 let pool_service = Rc::new(PoolService::new());
                 let wallet_service = Rc::new(WalletService::new());
                 let signus_service = Rc::new(SignusService::new());
+                let ledger_service = Rc::new(LedgerService::new());
 
                 let anoncreds_command_executor = AnoncredsCommandExecutor::new(anoncreds_service.clone(), pool_service.clone(), wallet_service.clone());
-                let ledger_command_executor = LedgerCommandExecutor::new(anoncreds_service.clone(), pool_service.clone(), signus_service.clone(), wallet_service.clone());
+                let ledger_command_executor = LedgerCommandExecutor::new(anoncreds_service.clone(), pool_service.clone(), signus_service.clone(), wallet_service.clone(), ledger_service.clone());
                 let pool_command_executor = PoolCommandExecutor::new(pool_service.clone());
                 let signus_command_executor = SignusCommandExecutor::new(anoncreds_service.clone(), pool_service.clone(), wallet_service.clone(), signus_service.clone());
                 let wallet_command_executor = WalletCommandExecutor::new(wallet_service.clone());
@@ -1,3 +1,7 @@
+extern crate serde_json;
+
+use self::serde_json::Value;
+
 use errors::sovrin::SovrinError;
 use errors::pool::PoolError;
 
@@ -27,11 +31,19 @@ pub enum PoolCommand {
             Box<Fn(Result<(), SovrinError>) + Send>),
     RefreshAck(i32,
                Result<(), SovrinError>),
+    GetStatus(i32, // pool handle
+              Box<Fn(Result<String, SovrinError>) + Send>),
+    Subscribe(i32, // pool handle
+              Box<Fn(Result<String, SovrinError>) + Send>, // event callback
+              Box<Fn(Result<(), SovrinError>) + Send>),
+    Event(i32, // pool handle
+          String), // event json
 }
 
 pub struct PoolCommandExecutor {
     pool_service: Rc<PoolService>,
     open_callbacks: RefCell<HashMap<i32, Box<Fn(Result<i32, SovrinError>)>>>,
+    event_callbacks: RefCell<HashMap<i32, Vec<Box<Fn(Result<String, SovrinError>)>>>>,
 }
 
 impl PoolCommandExecutor {
@@ -39,6 +51,7 @@ impl PoolCommandExecutor {
         PoolCommandExecutor {
             pool_service: pool_service,
             open_callbacks: RefCell::new(HashMap::new()),
+            event_callbacks: RefCell::new(HashMap::new()),
         }
     }
 
@@ -85,6 +98,28 @@ impl PoolCommandExecutor {
             PoolCommand::RefreshAck(handle, result) => {
                 unimplemented!();
             }
+            PoolCommand::GetStatus(handle, cb) => {
+                info!(target: "pool_command_executor", "GetStatus command received");
+                self.get_status(handle, cb);
+            }
+            PoolCommand::Subscribe(handle, event_cb, cb) => {
+                info!(target: "pool_command_executor", "Subscribe command received");
+                self.subscribe(handle, event_cb, cb);
+            }
+            PoolCommand::Event(handle, event_json) => {
+                info!("Event handle {:?}, event {:?}", handle, event_json);
+                match self.event_callbacks.try_borrow_mut() {
+                    Ok(mut cbs) => {
+                        for cb in cbs.get(&handle).into_iter().flat_map(|cbs| cbs.iter()) {
+                            cb(Ok(event_json.clone()));
+                        }
+                        if PoolCommandExecutor::_is_final_event(&event_json) {
+                            cbs.remove(&handle);
+                        }
+                    }
+                    Err(err) => { error!("{:?}", err); }
+                }
+            }
         };
     }
 
@@ -113,8 +148,44 @@ impl PoolCommandExecutor {
     }
 
     fn close(&self, handle: i32, cb: Box<Fn(Result<(), SovrinError>) + Send>) {
-        // TODO: FIXME: Implement me!!!
-        cb(Ok(()));
+        let result = self.pool_service.close(handle)
+            .map_err(|err| SovrinError::PoolError(err))
+            .and_then(|_| {
+                self.event_callbacks.try_borrow_mut()
+                    .map_err(|err| SovrinError::PoolError(PoolError::from(err)))
+            })
+            .map(|mut cbs| {
+                cbs.remove(&handle);
+            });
+        cb(result)
+    }
+
+    fn get_status(&self, handle: i32, cb: Box<Fn(Result<String, SovrinError>) + Send>) {
+        cb(self.pool_service.get_status(handle).map_err(|err| SovrinError::PoolError(err)))
+    }
+
+    fn subscribe(&self,
+                 handle: i32,
+                 event_cb: Box<Fn(Result<String, SovrinError>) + Send>,
+                 cb: Box<Fn(Result<(), SovrinError>) + Send>) {
+        let result = self.pool_service.get_status(handle)
+            .map_err(|err| SovrinError::PoolError(err))
+            .and_then(|_| {
+                self.event_callbacks.try_borrow_mut()
+                    .map_err(|err| SovrinError::PoolError(PoolError::from(err)))
+            })
+            .map(|mut cbs| {
+                cbs.entry(handle).or_insert(Vec::new()).push(event_cb);
+            });
+        cb(result)
+    }
+
+    /// Worker of the pool sends no events after it's terminated or failed
+    fn _is_final_event(event_json: &str) -> bool {
+        match serde_json::from_str::<Value>(event_json) {
+            Ok(event) => event["status"]["phase"] == "Terminated" || event["status"]["phase"] == "Failed",
+            Err(_) => false,
+        }
     }
 
     fn refresh(&self, handle: i32, cb: Box<Fn(Result<(), SovrinError>) + Send>) {
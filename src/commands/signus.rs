@@ -246,6 +246,7 @@ impl SignusCommandExecutor {
 
                     CommandExecutor::instance()
                         .send(Command::Ledger(LedgerCommand::SubmitRequest(
+                            None,
                             pool_handle,
                             get_nym_request,
                             Box::new(move |result| {
@@ -334,6 +335,7 @@ impl SignusCommandExecutor {
 
                     CommandExecutor::instance()
                         .send(Command::Ledger(LedgerCommand::SubmitRequest(
+                            None,
                             pool_handle,
                             get_nym_request,
                             Box::new(move |result| {
@@ -26,11 +26,11 @@ impl fmt::Display for CommonError {
             CommonError::InvalidParam2(ref description) => write!(f, "Invalid param 2: {}", description),
             CommonError::InvalidParam3(ref description) => write!(f, "Invalid param 3: {}", description),
             CommonError::InvalidParam4(ref description) => write!(f, "Invalid param 4: {}", description),
-            CommonError::InvalidParam5(ref description) => write!(f, "Invalid param 4: {}", description),
-            CommonError::InvalidParam6(ref description) => write!(f, "Invalid param 4: {}", description),
-            CommonError::InvalidParam7(ref description) => write!(f, "Invalid param 4: {}", description),
-            CommonError::InvalidParam8(ref description) => write!(f, "Invalid param 4: {}", description),
-            CommonError::InvalidParam9(ref description) => write!(f, "Invalid param 4: {}", description),
+            CommonError::InvalidParam5(ref description) => write!(f, "Invalid param 5: {}", description),
+            CommonError::InvalidParam6(ref description) => write!(f, "Invalid param 6: {}", description),
+            CommonError::InvalidParam7(ref description) => write!(f, "Invalid param 7: {}", description),
+            CommonError::InvalidParam8(ref description) => write!(f, "Invalid param 8: {}", description),
+            CommonError::InvalidParam9(ref description) => write!(f, "Invalid param 9: {}", description),
             CommonError::InvalidState(ref description) => write!(f, "Invalid library state: {}", description),
             CommonError::InvalidStructure(ref description) => write!(f, "Invalid structure: {}", description)
         }
@@ -2,6 +2,7 @@ use std::error;
 use std::io;
 use std::fmt;
 
+use errors::common::CommonError;
 use errors::crypto::CryptoError;
 
 use api::ErrorCode;
@@ -10,7 +11,12 @@ use errors::ToErrorCode;
 #[derive(Debug)]
 pub enum LedgerError {
     NoConsensus(String),
+    RequestCancelled(String),
+    InvalidTransaction(String),
+    NotFound(String),
+    SecurityError(String),
     Io(io::Error),
+    CommonError(CommonError),
     CryptoError(CryptoError)
 }
 
@@ -18,7 +24,12 @@ impl fmt::Display for LedgerError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             LedgerError::NoConsensus(ref description) => write!(f, "No consensus: {}", description),
+            LedgerError::RequestCancelled(ref description) => write!(f, "Request cancelled: {}", description),
+            LedgerError::InvalidTransaction(ref description) => write!(f, "Invalid transaction: {}", description),
+            LedgerError::NotFound(ref description) => write!(f, "Not found: {}", description),
+            LedgerError::SecurityError(ref description) => write!(f, "Security error: {}", description),
             LedgerError::Io(ref err) => err.fmt(f),
+            LedgerError::CommonError(ref err) => err.fmt(f),
             LedgerError::CryptoError(ref err) => err.fmt(f)
         }
     }
@@ -28,7 +39,12 @@ impl error::Error for LedgerError {
     fn description(&self) -> &str {
         match *self {
             LedgerError::NoConsensus(ref description) => description,
+            LedgerError::RequestCancelled(ref description) => description,
+            LedgerError::InvalidTransaction(ref description) => description,
+            LedgerError::NotFound(ref description) => description,
+            LedgerError::SecurityError(ref description) => description,
             LedgerError::Io(ref err) => err.description(),
+            LedgerError::CommonError(ref err) => err.description(),
             LedgerError::CryptoError(ref err) => err.description()
         }
     }
@@ -36,12 +52,23 @@ impl error::Error for LedgerError {
     fn cause(&self) -> Option<&error::Error> {
         match *self {
             LedgerError::NoConsensus(ref description) => None,
+            LedgerError::RequestCancelled(ref description) => None,
+            LedgerError::InvalidTransaction(ref description) => None,
+            LedgerError::NotFound(ref description) => None,
+            LedgerError::SecurityError(ref description) => None,
             LedgerError::Io(ref err) => Some(err),
+            LedgerError::CommonError(ref err) => Some(err),
             LedgerError::CryptoError(ref err) => Some(err)
         }
     }
 }
 
+impl From<CommonError> for LedgerError {
+    fn from(err: CommonError) -> Self {
+        LedgerError::CommonError(err)
+    }
+}
+
 impl From<CryptoError> for LedgerError {
     fn from(err: CryptoError) -> Self {
         LedgerError::CryptoError(err)
@@ -52,7 +79,12 @@ impl ToErrorCode for LedgerError {
     fn to_error_code(&self) -> ErrorCode {
         match *self {
             LedgerError::NoConsensus(ref description) => ErrorCode::LedgerNoConsensusError,
+            LedgerError::RequestCancelled(ref description) => ErrorCode::LedgerRequestCancelled,
+            LedgerError::InvalidTransaction(ref description) => ErrorCode::LedgerInvalidTransaction,
+            LedgerError::NotFound(ref description) => ErrorCode::LedgerNotFound,
+            LedgerError::SecurityError(ref description) => ErrorCode::LedgerSecurityError,
             LedgerError::Io(ref err) => ErrorCode::PoolLedgerIOError,
+            LedgerError::CommonError(ref err) => err.to_error_code(),
             LedgerError::CryptoError(ref err) => err.to_error_code()
         }
     }
@@ -0,0 +1,38 @@
+pub const NODE: &'static str = "0";
+pub const NYM: &'static str = "1";
+pub const GET_TXN: &'static str = "3";
+pub const ATTRIB: &'static str = "100";
+pub const SCHEMA: &'static str = "101";
+pub const CLAIM_DEF: &'static str = "102";
+pub const GET_ATTR: &'static str = "104";
+pub const GET_NYM: &'static str = "105";
+pub const GET_SCHEMA: &'static str = "107";
+pub const GET_CLAIM_DEF: &'static str = "108";
+pub const POOL_UPGRADE: &'static str = "109";
+pub const GET_DDO: &'static str = "120";
+
+pub const TRUSTEE: &'static str = "0";
+pub const TGB: &'static str = "1";
+pub const STEWARD: &'static str = "2";
+pub const TRUST_ANCHOR: &'static str = "101";
+
+pub const SERVICES: &'static [&'static str] = &["VALIDATOR", "OBSERVER"];
+
+pub const POOL_LEDGER: &'static str = "POOL";
+pub const DOMAIN_LEDGER: &'static str = "DOMAIN";
+pub const POOL_LEDGER_ID: i32 = 0;
+pub const DOMAIN_LEDGER_ID: i32 = 1;
+
+pub const START: &'static str = "start";
+pub const CANCEL: &'static str = "cancel";
+
+pub const DID_METHOD_PREFIX: &'static str = "did:sov:";
+pub const ENDPOINT_ATTR: &'static str = "endpoint";
+pub const SERVICE_ATTR: &'static str = "service";
+pub const AGENT_SERVICE: &'static str = "agent";
+
+/// Version of request envelope sent by builders and required by pool
+pub const PROTOCOL_VERSION: u64 = 1;
+
+/// Fields nodes add to txn in write reply, which aren't part of txn stored on the ledger
+pub const REPLY_ONLY_FIELDS: &'static [&'static str] = &["seqNo", "auditPath", "rootHash"];
@@ -1,102 +1,90 @@
-use services::ledger::merkletree::tree::{ Tree, LeavesIterator, LeavesIntoIterator, TreeLeafData };
-use services::ledger::merkletree::proof::{ Proof, Lemma };
 use utils::crypto::hash::{Hash, HASH_OUTPUT_LEN};
 use errors::crypto::CryptoError;
 
-/// A Merkle tree is a binary tree, with values of type `T` at the leafs,
-/// and where every internal node holds the hash of the concatenation of the hashes of its children nodes.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Stored tree starts with magic and format version
+const STORAGE_MAGIC: &'static [u8] = b"SMT";
+const STORAGE_VERSION: u8 = 2;
+const STORAGE_HEADER_LEN: usize = 4;
+
+/// Append-only Merkle tree of RFC 6962 shape, which keeps hashes only.
+/// Leaf data is stored by the owner of the tree, e.g. in the txn file of the ledger.
+///
+/// Hashes of all full subtrees are kept by their height, so a leaf is appended in O(log n)
+/// and audit paths and consistency proofs are built without leaf data.
+#[derive(Clone, Debug, PartialEq)]
 pub struct MerkleTree {
+    /// Concatenated hashes of full subtrees of each height, leaf hashes first
+    levels: Vec<Vec<u8>>,
 
-    /// The root of the inner binary tree
-    pub root: Tree,
-
-    /// The height of the tree
-    pub height: usize,
+    /// The root hash, hash of empty string for empty tree
+    root_hash: Vec<u8>,
 
     /// The number of leaf nodes in the tree
-    pub count: usize,
-
-    /// The number of nodes in the tree
-    pub nodes_count: usize
+    count: usize
 }
 
 impl MerkleTree {
 
-    /// Constructs a Merkle Tree from a vector of data blocks.
-    /// Returns `None` if `values` is empty.
-    pub fn from_vec(values: Vec<TreeLeafData>) -> Result<Self, CryptoError> {
+    /// Constructs empty Merkle Tree
+    pub fn new() -> Result<Self, CryptoError> {
+        Ok(MerkleTree {
+            levels: Vec::new(),
+            root_hash: Hash::hash_empty()?.to_vec(),
+            count: 0
+        })
+    }
 
-        if values.is_empty() {
-            return Ok(MerkleTree {
-                root: Tree::empty(Hash::hash_empty()?),
-                height: 0,
-                count: 0,
-                nodes_count: 0
-            });
+    /// Constructs a Merkle Tree from a vector of data blocks.
+    pub fn from_vec(values: Vec<String>) -> Result<Self, CryptoError> {
+        let mut tree = MerkleTree::new()?;
+        for value in values {
+            tree.append(&value)?;
         }
+        Ok(tree)
+    }
 
-        let count = values.len();
-        let mut nodes_count = 0;
-        let mut height = 0;
-        let mut cur    = Vec::with_capacity(count);
+    /// Appends leaf with given data, the data itself isn't kept by the tree
+    pub fn append(&mut self, leaf: &str) -> Result<(), CryptoError> {
+        let leaf_hash = Hash::hash_leaf(&leaf)?.to_vec();
+        self.append_hash(leaf_hash)
+    }
 
-        for v in values {
-            let leaf = Tree::new_leaf(v)?;
-            cur.push(leaf);
+    /// Appends leaf by its hash together with hashes of the subtrees it completes
+    pub fn append_hash(&mut self, leaf_hash: Vec<u8>) -> Result<(), CryptoError> {
+        if leaf_hash.len() != HASH_OUTPUT_LEN {
+            return Err(CryptoError::InvalidStructure(format!("Leaf hash has {} bytes, {} expected",
+                                                             leaf_hash.len(), HASH_OUTPUT_LEN)));
         }
 
-        while cur.len() > 1 {
-            let mut next = Vec::new();
-            while !cur.is_empty() {
-                if cur.len() == 1 {
-                    next.push(cur.remove(0));
-                }
-                else {
-                    let left  = cur.remove(0);
-                    let right = cur.remove(0);
-
-                    let combined_hash = Hash::hash_nodes(
-                        left.hash(),
-                        right.hash()
-                    )?;
-
-                    let node = Tree::Node {
-                       hash: combined_hash.to_vec(),
-                       left: Box::new(left),
-                       right: Box::new(right)
-                    };
-
-                    next.push(node);
-                    nodes_count+=1;
-                }
+        let mut hash = leaf_hash;
+        let mut height = 0;
+        loop {
+            if self.levels.len() == height {
+                self.levels.push(Vec::new());
             }
-
+            self.levels[height].extend_from_slice(&hash);
+            let level_count = self.levels[height].len() / HASH_OUTPUT_LEN;
+            if level_count % 2 != 0 {
+                break;
+            }
+            let left = self.node_hash(height, level_count - 2).to_vec();
+            hash = Hash::hash_nodes(&left, &hash)?.to_vec();
             height += 1;
-
-            cur = next;
         }
 
-        debug_assert!(cur.len() == 1);
-
-        let root = cur.remove(0);
-
-        Ok(MerkleTree {
-            root: root,
-            height: height,
-            count: count,
-            nodes_count: nodes_count
-        })
+        self.count += 1;
+        self.root_hash = self.range_hash(0, self.count)?;
+        Ok(())
     }
 
     /// Returns the root hash of Merkle tree
     pub fn root_hash(&self) -> &Vec<u8> {
-        self.root.hash()
+        &self.root_hash
     }
 
     /// Returns the hex root hash of Merkle tree
     pub fn root_hash_hex(&self) -> String {
-        let rh = self.root.hash();
+        let rh = self.root_hash();
         let mut ret:String = String::with_capacity(HASH_OUTPUT_LEN*2);
         for i in rh {
             ret.push_str(&format!("{:02x}", i));
@@ -106,7 +94,11 @@ impl MerkleTree {
 
     /// Returns the height of Merkle tree
     pub fn height(&self) -> usize {
-        self.height
+        let mut height = 0;
+        while (1 << height) < self.count {
+            height += 1;
+        }
+        height
     }
 
     /// Returns the number of leaves in the Merkle tree
@@ -116,49 +108,130 @@ impl MerkleTree {
 
     /// Returns whether the Merkle tree is empty or not
     pub fn is_empty(&self) -> bool {
-        self.count() == 0
+        self.count == 0
     }
 
-    /// Generate an inclusion proof for the given value.
-    /// Returns `None` if the given value is not found in the tree.
-    pub fn gen_proof(&self, value: TreeLeafData) -> Result<Option<Proof>, CryptoError> {
-
-        let root_hash  = self.root_hash().clone();
-        let leaf_hash  = Hash::hash_leaf(&value)?;
-
-        Ok(Lemma::new(&self.root, leaf_hash.to_vec().as_slice()).map(|lemma|
-            Proof::new(root_hash, lemma, value)
-        ))
+    /// Returns hash of the leaf with 0-based index
+    pub fn leaf_hash(&self, index: usize) -> Option<&[u8]> {
+        if index < self.count {
+            Some(self.node_hash(0, index))
+        } else {
+            None
+        }
     }
 
-    /// Creates an `Iterator` over the values contained in this Merkle tree.
-    pub fn iter(&self) -> LeavesIterator {
-        self.root.iter()
+    /// Hash of full subtree of given height with leaves from index << height
+    fn node_hash(&self, height: usize, index: usize) -> &[u8] {
+        &self.levels[height][index * HASH_OUTPUT_LEN..(index + 1) * HASH_OUTPUT_LEN]
     }
 
-}
+    /// Hash of subtree with leaves start..end, where start is aligned to the subtree size
+    /// as in RFC 6962 decomposition. It is folded from the full subtrees covering the range.
+    pub fn range_hash(&self, start: usize, end: usize) -> Result<Vec<u8>, CryptoError> {
+        if start == end {
+            return Ok(Hash::hash_empty()?.to_vec());
+        }
+        if start > end || end > self.count {
+            return Err(CryptoError::InvalidStructure(format!("Range {}..{} is out of tree of size {}",
+                                                             start, end, self.count)));
+        }
 
-impl IntoIterator for MerkleTree {
+        let mut subtrees: Vec<&[u8]> = Vec::new();
+        let mut pos = start;
+        while pos < end {
+            let mut height = 0;
+            while pos % (2 << height) == 0 && pos + (2 << height) <= end {
+                height += 1;
+            }
+            subtrees.push(self.node_hash(height, pos >> height));
+            pos += 1 << height;
+        }
 
-    type Item     = TreeLeafData;
-    type IntoIter = LeavesIntoIterator;
+        let mut hash = subtrees.pop().unwrap().to_vec();
+        while let Some(left) = subtrees.pop() {
+            hash = Hash::hash_nodes(&left.to_vec(), &hash)?.to_vec();
+        }
+        Ok(hash)
+    }
 
-    /// Creates a consuming iterator, that is, one that moves each value out of the Merkle tree.
-    /// The tree cannot be used after calling this.
-    fn into_iter(self) -> Self::IntoIter {
-        self.root.into_iter()
+    /// Serializes the tree to stable binary format: magic and version followed by hashes
+    /// of all leaves as `appended_bytes` of the whole tree
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MerkleTree::stored_len(self.count));
+        bytes.extend_from_slice(STORAGE_MAGIC);
+        bytes.push(STORAGE_VERSION);
+        bytes.extend(self.appended_bytes(0));
+        bytes
     }
 
-}
+    /// Hashes created by appending leaves `start..count` in the order they were created:
+    /// each leaf hash is followed by hashes of the full subtrees it completes.
+    /// Stored tree is extended by writing these bytes to its end.
+    pub fn appended_bytes(&self, start: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for index in start..self.count {
+            bytes.extend_from_slice(self.node_hash(0, index));
+            let size = index + 1;
+            let mut height = 1;
+            while size % (1 << height) == 0 {
+                bytes.extend_from_slice(self.node_hash(height, (size >> height) - 1));
+                height += 1;
+            }
+        }
+        bytes
+    }
 
-impl <'a> IntoIterator for &'a MerkleTree {
+    /// Length of stored tree with `count` leaves, each leaf adds one hash per full subtree it completes
+    pub fn stored_len(count: usize) -> usize {
+        STORAGE_HEADER_LEN + (2 * count - count.count_ones() as usize) * HASH_OUTPUT_LEN
+    }
 
-    type Item     = &'a TreeLeafData;
-    type IntoIter = LeavesIterator<'a>;
+    /// Restores the tree from stable binary format. Hashes aren't recomputed, only hashes
+    /// of the subtrees completed by the last leaf are checked against their children.
+    pub fn from_bytes(bytes: &[u8]) -> Result<MerkleTree, CryptoError> {
+        if bytes.len() < STORAGE_HEADER_LEN || &bytes[..3] != STORAGE_MAGIC {
+            return Err(CryptoError::InvalidStructure("Stored tree has invalid header".to_string()));
+        }
+        if bytes[3] != STORAGE_VERSION {
+            return Err(CryptoError::InvalidStructure(format!("Stored tree has unsupported version {}", bytes[3])));
+        }
+        if (bytes.len() - STORAGE_HEADER_LEN) % HASH_OUTPUT_LEN != 0 {
+            return Err(CryptoError::InvalidStructure("Stored tree has partial hash".to_string()));
+        }
 
-    /// Creates a borrowing `Iterator` over the values contained in this Merkle tree.
-    fn into_iter(self) -> Self::IntoIter {
-        self.root.iter()
-    }
+        let mut tree = MerkleTree::new()?;
+        let mut hashes = bytes[STORAGE_HEADER_LEN..].chunks(HASH_OUTPUT_LEN);
+        while let Some(leaf_hash) = hashes.next() {
+            let size = tree.count + 1;
+            let mut hash = leaf_hash;
+            let mut height = 0;
+            loop {
+                if tree.levels.len() == height {
+                    tree.levels.push(Vec::new());
+                }
+                tree.levels[height].extend_from_slice(hash);
+                height += 1;
+                if size % (1 << height) != 0 {
+                    break;
+                }
+                hash = hashes.next().ok_or(CryptoError::InvalidStructure(
+                    format!("Stored tree of size {} is truncated", size)))?;
+            }
+            tree.count = size;
+        }
 
+        for height in 1..tree.levels.len() {
+            if tree.count % (1 << height) != 0 {
+                break;
+            }
+            let index = (tree.count >> height) - 1;
+            let hash = Hash::hash_nodes(&tree.node_hash(height - 1, 2 * index).to_vec(),
+                                        &tree.node_hash(height - 1, 2 * index + 1).to_vec())?.to_vec();
+            if tree.node_hash(height, index) != hash.as_slice() {
+                return Err(CryptoError::InvalidStructure("Stored tree hashes don't match".to_string()));
+            }
+        }
+        tree.root_hash = tree.range_hash(0, tree.count)?;
+        Ok(tree)
+    }
 }
@@ -1,211 +1,179 @@
-extern crate serde_json;
-
-pub mod tree;
-pub mod proof;
 pub mod merkletree;
 
-use self::tree::*;
 use self::merkletree::*;
 use errors::crypto::CryptoError;
 use utils::crypto::hash::Hash;
 
 impl MerkleTree {
-    fn count_bits(v: usize) -> usize {
-        let mut ret = 0;
-        let mut val = v;
-        while val != 0 {
-            val &= val - 1;
-            ret += 1;
+    /// Consistency proof between the first `old_size` leaves and the whole tree
+    /// as defined in RFC 6962 2.1.2, proof between trees of the same size is empty
+    pub fn consistency_proof(&self, old_size: usize) -> Result<Vec<Vec<u8>>, CryptoError> {
+        if old_size == 0 || old_size > self.count() {
+            return Err(CryptoError::InvalidStructure(format!("Can't prove consistency of tree of size {} with tree of size {}",
+                                                             old_size, self.count())));
         }
-        return ret;
+        let mut proof = Vec::new();
+        self._consistency_proof(old_size, 0, self.count(), true, &mut proof)?;
+        Ok(proof)
     }
 
-    pub fn find_hash<'a>(from: &'a Tree, required_hash: &Vec<u8>) -> Option<&'a Tree> {
-        match from {
-            &Tree::Empty{ ref hash, .. } => {
-                assert!(false);
-                return None;
-            },
-            &Tree::Node{ ref left, ref right, ref hash, .. } => {
-                if hash == required_hash {
-                    return Some(from);
-                } else {
-                    let right = MerkleTree::find_hash(right, required_hash);
-                    match right {
-                        Some(r) => {
-                            return Some(r);
-                        },
-                        None => {
-                            let left = MerkleTree::find_hash(left, required_hash);
-                            match left {
-                                Some(r) => {
-                                    return Some(r);
-                                },
-                                None => {
-                                    return None;
-                                }
-                            }
-                        }
-                    }
-                }
-            },
-            &Tree::Leaf{ ref hash, .. } => {
-                if hash == required_hash {
-                    return Some(from);
-                } else {
-                    return None;
-                }
+    fn _consistency_proof(&self, old_size: usize, start: usize, end: usize, complete: bool,
+                          proof: &mut Vec<Vec<u8>>) -> Result<(), CryptoError> {
+        if start + old_size == end {
+            if !complete {
+                proof.push(self.range_hash(start, end)?);
             }
+            return Ok(());
+        }
+        let split = MerkleTree::_split(end - start);
+        if old_size <= split {
+            self._consistency_proof(old_size, start, start + split, complete, proof)?;
+            proof.push(self.range_hash(start + split, end)?);
+        } else {
+            self._consistency_proof(old_size - split, start + split, end, false, proof)?;
+            proof.push(self.range_hash(start, start + split)?);
         }
+        Ok(())
     }
 
-    pub fn consistency_proof(&self,
-                             new_root_hash: &Vec<u8>, new_size: usize,
-                             proof: &Vec<Vec<u8>>) -> Result<bool, CryptoError> {
-        if self.nodes_count == 0 {
-            // empty old tree
-            return Ok(true);
+    /// Checks RFC 6962 consistency proof between tree of `old_size` leaves with `old_root`
+    /// and tree of `new_size` leaves with `new_root`. Proof that doesn't match the sizes,
+    /// including proof for empty or shrunk tree, is treated as invalid.
+    pub fn verify_consistency(old_size: usize, old_root: &Vec<u8>, new_size: usize, new_root: &Vec<u8>,
+                              proof: &Vec<Vec<u8>>) -> Result<bool, CryptoError> {
+        if old_size == 0 || old_size > new_size {
+            return Ok(false);
+        }
+        if old_size == new_size {
+            return Ok(proof.is_empty() && old_root == new_root);
         }
-        if self.nodes_count == new_size && self.root_hash() == new_root_hash {
-            // identical trees
-            return Ok(true);
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        if old_size.is_power_of_two() {
+            // old tree is the full left subtree, so its root starts the path
+            path.push(old_root.clone());
         }
-        if self.nodes_count > new_size {
-            // old tree is bigger!
-            assert!(false);
+        path.extend(proof.iter().cloned());
+        if path.is_empty() {
             return Ok(false);
         }
 
-        let mut old_node = self.nodes_count - 1;
+        let mut old_node = old_size - 1;
         let mut new_node = new_size - 1;
-
         while old_node % 2 != 0 {
             old_node = old_node / 2;
             new_node = new_node / 2;
         }
-
-        let mut proofs = proof.iter();
-        let mut old_hash: Vec<u8>;
-        let mut new_hash: Vec<u8>;
-
-        if old_node != 0 {
-            new_hash = proofs.next().unwrap().to_vec();
-            old_hash = new_hash.clone();
-        } else {
-            new_hash = self.root_hash().to_vec();
-            old_hash = new_hash.clone();
-        }
-
-        while old_node != 0 {
-            if old_node % 2 != 0 {
-                let next_proof = proofs.next().unwrap();
-                old_hash = Hash::hash_nodes(next_proof, &old_hash)?.to_vec();
-                new_hash = Hash::hash_nodes(next_proof, &new_hash)?.to_vec();
-            } else if old_node < new_node {
-                new_hash = Hash::hash_nodes(&new_hash,
-                                             proofs.next().unwrap())?.to_vec();
+        let mut old_hash = path[0].clone();
+        let mut new_hash = path[0].clone();
+        for hash in &path[1..] {
+            if new_node == 0 {
+                // proof is longer than the new tree is high
+                return Ok(false);
+            }
+            if old_node % 2 != 0 || old_node == new_node {
+                old_hash = Hash::hash_nodes(hash, &old_hash)?.to_vec();
+                new_hash = Hash::hash_nodes(hash, &new_hash)?.to_vec();
+                while old_node % 2 == 0 && old_node != 0 {
+                    old_node = old_node / 2;
+                    new_node = new_node / 2;
+                }
+            } else {
+                new_hash = Hash::hash_nodes(&new_hash, hash)?.to_vec();
             }
             old_node = old_node / 2;
             new_node = new_node / 2;
         }
 
-        while new_node != 0 {
-            let n = proofs.next().unwrap();
-            new_hash = Hash::hash_nodes(&new_hash, n)?.to_vec();
-            new_node = new_node / 2;
-        }
+        Ok(new_node == 0 && old_hash == *old_root && new_hash == *new_root)
+    }
 
-        if new_hash != *new_root_hash {
-            // new hash differs
-            return Ok(false);
+    /// Size of the left subtree, the largest power of 2 less than the size
+    fn _split(size: usize) -> usize {
+        let mut split = 1;
+        while split * 2 < size {
+            split *= 2;
         }
+        split
+    }
 
-        if old_hash != *self.root_hash() {
-            // old hash differs
-            return Ok(false);
+    /// Audit path of leaf in RFC 6962 order: sibling hashes from the leaf up to the root
+    pub fn audit_path(&self, leaf_index: usize) -> Option<Vec<Vec<u8>>> {
+        if leaf_index >= self.count() {
+            return None;
         }
+        let mut path = Vec::new();
+        match self._audit_path(leaf_index, 0, self.count(), &mut path) {
+            Ok(()) => Some(path),
+            Err(_) => None
+        }
+    }
 
-        return Ok(true);
+    fn _audit_path(&self, leaf_index: usize, start: usize, end: usize, path: &mut Vec<Vec<u8>>) -> Result<(), CryptoError> {
+        if end - start <= 1 {
+            return Ok(());
+        }
+        // left subtree is always full
+        let split = MerkleTree::_split(end - start);
+        if leaf_index < start + split {
+            self._audit_path(leaf_index, start, start + split, path)?;
+            path.push(self.range_hash(start + split, end)?);
+        } else {
+            self._audit_path(leaf_index, start + split, end, path)?;
+            path.push(self.range_hash(start, start + split)?);
+        }
+        Ok(())
     }
 
-    pub fn append(&mut self, node: TreeLeafData) -> Result<(), CryptoError> {
-        if self.count == 0 {
-            // empty tree
-            self.root = Tree::new_leaf(node)?;
-            self.count += 1;
+    /// Checks RFC 6962 audit path of leaf with given hash and 0-based index against root of tree
+    /// with tree_size leaves. Path that doesn't match the tree shape is treated as invalid.
+    pub fn verify_inclusion(leaf_hash: &Vec<u8>, leaf_index: usize, tree_size: usize,
+                            audit_path: &Vec<Vec<u8>>, root_hash: &Vec<u8>) -> Result<bool, CryptoError> {
+        if leaf_index >= tree_size {
+            return Ok(false);
         }
-        else if Self::count_bits(self.count) != 1 {
-            // add to right subtree
-            match self.root.clone() {
-                Tree::Node { ref left, ref right, .. } => {
-                    let mut iter = right.iter().map(|x| (*x).clone()).collect::<Vec<TreeLeafData>>();
-                    iter.push(node);
-                    let new_right = MerkleTree::from_vec(iter)?;
-                    let combined_hash = Hash::hash_nodes(
-                        left.hash(),
-                        new_right.root_hash() as &Vec<u8>
-                    )?;
-                    self.root = Tree::Node {
-                        left: (*left).clone(),
-                        right: Box::new(new_right.root),
-                        hash: combined_hash.to_vec()
-                    };
-                    self.count += 1;
-                    self.nodes_count += 1;
-                }
-                _ => {
-                    assert!(false);
-                }
+
+        let mut node = leaf_index;
+        let mut last_node = tree_size - 1;
+        let mut hash = leaf_hash.clone();
+
+        for sibling in audit_path {
+            if last_node == 0 {
+                // path is longer than the tree is high
+                return Ok(false);
             }
-        }
-        else
-        {
-            // add tree layer
-            let new_right = MerkleTree::from_vec(vec![ node ])?;
-            match self.root.clone() {
-                Tree::Node { ref left, ref right, ref hash } => {
-                    let combined_hash = Hash::hash_nodes(
-                        hash,
-                        new_right.root_hash()
-                    )?;
-                    self.root = Tree::Node {
-                        left: Box::new(self.root.clone()),
-                        right: Box::new(new_right.root),
-                        hash: combined_hash.to_vec()
-                    };
-                    self.count += 1;
-                    self.nodes_count += 1;
-                }
-                Tree::Leaf { ref hash, ref value } => {
-                    let combined_hash = Hash::hash_nodes(
-                        hash,
-                        new_right.root_hash()
-                    )?;
-                    self.root = Tree::Node {
-                        left: Box::new(Tree::new_leaf((*value).clone())?),
-                        right: Box::new(new_right.root),
-                        hash: combined_hash.to_vec()
-                    };
-                    self.count += 1;
-                    self.nodes_count += 1;
-                }
-                _ => {
-                    assert!(false);
+            if node % 2 != 0 || node == last_node {
+                hash = Hash::hash_nodes(sibling, &hash)?.to_vec();
+                if node % 2 == 0 {
+                    // node is the last one on its level, so it's promoted without hashing
+                    while node % 2 == 0 && node != 0 {
+                        node = node / 2;
+                        last_node = last_node / 2;
+                    }
                 }
+            } else {
+                hash = Hash::hash_nodes(&hash, sibling)?.to_vec();
             }
-            self.height += 1;
+            node = node / 2;
+            last_node = last_node / 2;
         }
-        Ok(())
+
+        Ok(last_node == 0 && hash == *root_hash)
     }
 }
 
 
 #[cfg(test)]
 mod tests {
+    extern crate rand;
+
     use super::*;
-    use self::serde_json;
+    use self::rand::Rng;
     use utils::crypto::hash::HASH_OUTPUT_LEN;
 
+    fn values_to_strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|x| String::from(*x)).collect()
+    }
+
     fn hash_hex(rh: &Vec<u8>) -> String {
         let mut ret:String = String::with_capacity(HASH_OUTPUT_LEN*2);
         for i in rh {
@@ -225,7 +193,7 @@ mod tests {
         println!("root(0)={}", mt.root_hash_hex());
         let mut r = 1;
         for i in values {
-            mt.append(String::from(i)).unwrap();
+            mt.append(i).unwrap();
             println!("root({})={}", r, mt.root_hash_hex());
             r+=1;
         }
@@ -233,19 +201,15 @@ mod tests {
     }
 
     #[test]
-    fn find_hash_works() {
+    fn append_works_for_subtree_hashes() {
         let values = vec![ "1", "2", "3", "4", "5", "6", "7", "8", "9" ];
-        let mut mt = MerkleTree::from_vec(vec![]).unwrap();
-        println!("root(0)={}", mt.root_hash_hex());
-        let mut r = 1;
-        for i in values {
-            mt.append(String::from(i)).unwrap();
-            println!("root({})={}", r, mt.root_hash_hex());
-            r+=1;
+        let mut mt = MerkleTree::new().unwrap();
+        for i in &values {
+            mt.append(i).unwrap();
         }
 
-        assert!(mt.count == 9);
-        assert!(mt.nodes_count == 8);
+        assert_eq!(mt.count(), 9);
+        assert_eq!(mt.height(), 4);
 
         let mut rh: Vec<u8>;
 
@@ -253,34 +217,28 @@ mod tests {
                    0xfe, 0xc0, 0x54, 0xfe, 0x21, 0x9a, 0xb3, 0x57,
                    0xb7, 0x5d, 0x3c, 0x1c, 0xd9, 0xf8, 0xbe, 0x17,
                    0x67, 0xf6, 0x09, 0x0f, 0x9c, 0x86, 0xf9, 0xfd ];
-        assert!(MerkleTree::find_hash(&mt.root, &rh) != None);
+        assert_eq!(mt.range_hash(0, 2).unwrap(), rh);
 
         rh = vec![ 0x22, 0x15, 0xe8, 0xac, 0x4e, 0x2b, 0x87, 0x1c,
                    0x2a, 0x48, 0x18, 0x9e, 0x79, 0x73, 0x8c, 0x95,
                    0x6c, 0x08, 0x1e, 0x23, 0xac, 0x2f, 0x24, 0x15,
                    0xbf, 0x77, 0xda, 0x19, 0x9d, 0xfd, 0x92, 0x0c ];
-        assert!(MerkleTree::find_hash(&mt.root, &rh) != None);
+        assert_eq!(mt.leaf_hash(0).unwrap(), rh.as_slice());
 
-        rh = vec![ 0x23, 0x15, 0xe8, 0xac, 0x4e, 0x2b, 0x87, 0x1c,
-                   0x2a, 0x48, 0x18, 0x9e, 0x79, 0x73, 0x8c, 0x95,
-                   0x6c, 0x08, 0x1e, 0x23, 0xac, 0x2f, 0x24, 0x15,
-                   0xbf, 0x77, 0xda, 0x19, 0x9d, 0xfd, 0x92, 0x0d ];
-        assert!(MerkleTree::find_hash(&mt.root, &rh) == None);
+        assert!(mt.leaf_hash(9).is_none());
+        assert!(mt.range_hash(0, 10).is_err());
+        assert_eq!(mt.root_hash(), MerkleTree::from_vec(values_to_strings(&values)).unwrap().root_hash());
     }
 
     #[test]
-    fn consistency_proof_works_for_valid_proof() {
+    fn consistency_proof_works() {
         let values = vec![
             "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}",
-            "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"192.168.1.35\",\"client_port\":9704,\"node_ip\":\"192.168.1.35\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}" ];
-        let mut mt = MerkleTree::from_vec(vec![]).unwrap();
-        println!("root(0)={}", mt.root_hash_hex());
-        let mut r = 1;
-        for i in values {
-            mt.append(String::from(i)).unwrap();
-            println!("root({})={}", r, mt.root_hash_hex());
-            r+=1;
-        }
+            "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"192.168.1.35\",\"client_port\":9704,\"node_ip\":\"192.168.1.35\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}",
+            "{\"data\":{\"alias\":\"Node3\",\"client_ip\":\"192.168.1.35\",\"client_port\":9706,\"node_ip\":\"192.168.1.35\",\"node_port\":9705,\"services\":[\"VALIDATOR\"]},\"dest\":\"DKVxG2fXXTU8yT5N7hGEbXB3dfdAnYv1JczDUHpmDxya\",\"identifier\":\"2yAeV5ftuasWNgQwVYzeHeTuM7LwwNtPR3Zg9N4JiDgF\",\"txnId\":\"7e9f355dffa78ed24668f0e0e369fd8c224076571c51e2ea8be5f26479edebe4\",\"type\":\"0\"}",
+            "{\"data\":{\"alias\":\"Node4\",\"client_ip\":\"192.168.1.35\",\"client_port\":9708,\"node_ip\":\"192.168.1.35\",\"node_port\":9707,\"services\":[\"VALIDATOR\"]},\"dest\":\"4PS3EDQ3dW1tci1Bp6543CfuuebjFrg36kLAUcskGfaA\",\"identifier\":\"FTE95CVthRtrBnK2PYCBbC9LghTcGwi9Zfi1Gz2dnyNx\",\"txnId\":\"aa5e817d7cc626170eca175822029339a444eb0ee8f0bd20d3b0b76e566fb008\",\"type\":\"0\"}" ];
+        let mt3 = MerkleTree::from_vec(values_to_strings(&values[..3])).unwrap();
+        let mt4 = MerkleTree::from_vec(values_to_strings(&values)).unwrap();
 
         let proofs: Vec<Vec<u8>> = vec![
             vec![ 0x26, 0x06, 0x53, 0x99, 0xf1, 0xe9, 0x0d, 0xba,
@@ -297,46 +255,233 @@ mod tests {
                   0x61, 0x4d, 0x7f, 0x5a, 0x68, 0x72, 0x60, 0xd6 ]
         ];
 
-        assert!(mt.consistency_proof(&vec![0x77 as u8, 0xf1, 0x5a, 0x58, 0x07, 0xfd, 0xaa, 0x56,
-                                           0x51, 0x28, 0xc5, 0x8f, 0x59, 0x1f, 0x4f, 0x03,
-                                           0x25, 0x81, 0xfe, 0xe7, 0xd8, 0x61, 0x99, 0xae,
-                                           0xf8, 0xae, 0xac, 0x7b, 0x05, 0x80, 0xbe, 0x0a ],
-                                     2,
-                                     &proofs).unwrap());
+        assert_eq!(mt3.root_hash(), &vec![0x77 as u8, 0xf1, 0x5a, 0x58, 0x07, 0xfd, 0xaa, 0x56,
+                                          0x51, 0x28, 0xc5, 0x8f, 0x59, 0x1f, 0x4f, 0x03,
+                                          0x25, 0x81, 0xfe, 0xe7, 0xd8, 0x61, 0x99, 0xae,
+                                          0xf8, 0xae, 0xac, 0x7b, 0x05, 0x80, 0xbe, 0x0a ]);
+        assert_eq!(mt4.consistency_proof(3).unwrap(), proofs);
+        assert!(MerkleTree::verify_consistency(3, mt3.root_hash(), 4, mt4.root_hash(), &proofs).unwrap());
+    }
+
+    #[test]
+    fn consistency_proof_works_for_invalid_sizes() {
+        let mt = MerkleTree::from_vec(values_to_strings(&["1", "2", "3"])).unwrap();
+
+        assert!(mt.consistency_proof(3).unwrap().is_empty());
+        assert!(mt.consistency_proof(0).is_err());
+        assert!(mt.consistency_proof(4).is_err());
+        assert!(MerkleTree::new().unwrap().consistency_proof(0).is_err());
     }
 
     #[test]
-    fn gen_proof_and_proof_validate_work() {
+    fn verify_consistency_works_for_invalid_proof() {
+        let txns: Vec<String> = (1..5).map(|i| i.to_string()).collect();
+        let mt2 = MerkleTree::from_vec(txns[..2].to_vec()).unwrap();
+        let mt3 = MerkleTree::from_vec(txns[..3].to_vec()).unwrap();
+        let mt4 = MerkleTree::from_vec(txns.clone()).unwrap();
+        let leaves: Vec<Vec<u8>> = txns.iter().map(|txn| Hash::hash_leaf(txn).unwrap().to_vec()).collect();
+        let proof: Vec<Vec<u8>> = vec![leaves[2].clone(), leaves[3].clone(), mt2.root_hash().clone()];
+
+        assert!(MerkleTree::verify_consistency(3, mt3.root_hash(), 4, mt4.root_hash(), &proof).unwrap());
+        assert!(MerkleTree::verify_consistency(2, mt2.root_hash(), 3, mt3.root_hash(), &vec![leaves[2].clone()]).unwrap());
+        assert!(MerkleTree::verify_consistency(4, mt4.root_hash(), 4, mt4.root_hash(), &Vec::new()).unwrap());
+        assert!(!MerkleTree::verify_consistency(3, mt3.root_hash(), 4, mt2.root_hash(), &proof).unwrap());
+        assert!(!MerkleTree::verify_consistency(3, mt3.root_hash(), 4, mt4.root_hash(), &proof[..2].to_vec()).unwrap());
+        assert!(!MerkleTree::verify_consistency(4, mt4.root_hash(), 3, mt3.root_hash(), &Vec::new()).unwrap());
+        assert!(!MerkleTree::verify_consistency(0, mt2.root_hash(), 4, mt4.root_hash(), &proof).unwrap());
+        assert!(!MerkleTree::verify_consistency(4, mt4.root_hash(), 4, mt4.root_hash(), &proof).unwrap());
+
+        let mut extended = proof.clone();
+        extended.push(mt4.root_hash().clone());
+        assert!(!MerkleTree::verify_consistency(3, mt3.root_hash(), 4, mt4.root_hash(), &extended).unwrap());
+    }
+
+    #[test]
+    fn consistency_proof_works_for_all_sizes() {
+        let values = (0..33).map(|x| x.to_string()).collect::<Vec<_>>();
+        let trees = (0..values.len() + 1)
+            .map(|size| MerkleTree::from_vec(values[..size].to_vec()).unwrap())
+            .collect::<Vec<_>>();
+
+        for new_size in 1..trees.len() {
+            for old_size in 1..new_size + 1 {
+                let proof = trees[new_size].consistency_proof(old_size).unwrap();
+
+                assert!(MerkleTree::verify_consistency(old_size, trees[old_size].root_hash(),
+                                                       new_size, trees[new_size].root_hash(), &proof).unwrap());
+                if old_size < new_size {
+                    assert!(!MerkleTree::verify_consistency(old_size, trees[old_size].root_hash(),
+                                                            new_size, trees[new_size - 1].root_hash(), &proof).unwrap());
+                    assert!(!MerkleTree::verify_consistency(old_size, trees[old_size - 1].root_hash(),
+                                                            new_size, trees[new_size].root_hash(), &proof).unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn consistency_proof_works_for_random_sizes() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..50 {
+            let new_size: usize = rng.gen_range(2, 300);
+            let old_size: usize = rng.gen_range(1, new_size);
+            let values = (0..new_size).map(|_| rng.gen::<u64>().to_string()).collect::<Vec<_>>();
+            let old_mt = MerkleTree::from_vec(values[..old_size].to_vec()).unwrap();
+            let new_mt = MerkleTree::from_vec(values.clone()).unwrap();
+
+            let proof = new_mt.consistency_proof(old_size).unwrap();
+            assert!(MerkleTree::verify_consistency(old_size, old_mt.root_hash(), new_size, new_mt.root_hash(), &proof).unwrap());
+
+            // any tampered hash breaks the proof
+            let mut tampered = proof.clone();
+            let index = rng.gen_range(0, tampered.len());
+            tampered[index][0] ^= 0x01;
+            assert!(!MerkleTree::verify_consistency(old_size, old_mt.root_hash(), new_size, new_mt.root_hash(), &tampered).unwrap());
+        }
+    }
+
+    #[test]
+    fn audit_path_and_verify_inclusion_work() {
         let strvals   = vec![ "1", "2", "3", "4", "5", "6", "7", "8", "9", "10" ];
-        let values    = strvals.iter().map(|x| String::from(*x)).collect::<Vec<_>>();
+        let values    = values_to_strings(&strvals);
         let tree      = MerkleTree::from_vec(values.clone()).unwrap();
         let root_hash = tree.root_hash();
 
-        for value in values {
-            let proof    = tree.gen_proof(value).unwrap();
-            let is_valid = proof.map(|p| p.validate(&root_hash).unwrap()).unwrap_or(false);
+        for (index, value) in values.iter().enumerate() {
+            let leaf_hash = Hash::hash_leaf(value).unwrap().to_vec();
+            let audit_path = tree.audit_path(index).unwrap();
+            let is_valid = MerkleTree::verify_inclusion(&leaf_hash, index, values.len(), &audit_path, &root_hash).unwrap();
 
             assert!(is_valid);
         }
     }
 
     #[test]
-    fn serialize_works() {
-        let strvals   = vec![ "1", "2", "3", "4", "5", "6", "7", "8", "9", "10" ];
+    fn audit_path_works() {
+        let strvals   = vec![ "1", "2", "3", "4", "5" ];
         let values    = strvals.iter().map(|x| String::from(*x)).collect::<Vec<_>>();
-        let mt = MerkleTree::from_vec(values.clone()).unwrap();
-        let serialized = serde_json::to_string(&mt).unwrap();
-        println!("serialize mt: h={}, c={}, rhash={}", mt.height, mt.count, serialized);
-        let newmt: MerkleTree = serde_json::from_str(serialized.as_str()).unwrap();
-        println!("serialize newmt: h={}, c={}", newmt.height, newmt.count);
-
-        let mut collected = Vec::new();
-        for value in &newmt {
-            collected.push(value);
+        let mt = MerkleTree::from_vec(values).unwrap();
+
+        let audit_path = mt.audit_path(2).unwrap();
+
+        assert_eq!(hash_hex(mt.root_hash()), "e106de6d331e826225bf269c4d7086760bcfbdf83ed58457457632d7071ea963");
+        assert_eq!(audit_path.iter().map(hash_hex).collect::<Vec<_>>(),
+                   vec![ "11e1f558223f4c71b6be1cecfd1f0de87146d2594877c27b29ec519f9040213c",
+                         "e8bcd97e349693dcfec054fe219ab357b75d3c1cd9f8be1767f6090f9c86f9fd",
+                         "53304f5e3fd4bcd20b39abdef2fe118031cc5ae8217bcea008dea7e27869348a" ]);
+        assert!(mt.audit_path(5).is_none());
+    }
+
+    #[test]
+    fn verify_inclusion_works() {
+        for size in 1..20 {
+            let values = (0..size).map(|x| x.to_string()).collect::<Vec<_>>();
+            let mt = MerkleTree::from_vec(values.clone()).unwrap();
+
+            for (index, value) in values.iter().enumerate() {
+                let leaf_hash = Hash::hash_leaf(value).unwrap().to_vec();
+                let audit_path = mt.audit_path(index).unwrap();
+
+                assert!(MerkleTree::verify_inclusion(&leaf_hash, index, size, &audit_path, mt.root_hash()).unwrap());
+            }
         }
-        let refs = values.iter().collect::<Vec<_>>();
-        assert_eq!(refs, collected);
+    }
+
+    #[test]
+    fn verify_inclusion_works_for_invalid_proof() {
+        let values = (0..7).map(|x| x.to_string()).collect::<Vec<_>>();
+        let mt = MerkleTree::from_vec(values.clone()).unwrap();
+        let leaf_hash = Hash::hash_leaf(&values[3]).unwrap().to_vec();
+        let audit_path = mt.audit_path(3).unwrap();
+
+        // other leaf
+        let other_hash = Hash::hash_leaf(&values[4]).unwrap().to_vec();
+        assert!(!MerkleTree::verify_inclusion(&other_hash, 3, 7, &audit_path, mt.root_hash()).unwrap());
+
+        // other index or tree size
+        assert!(!MerkleTree::verify_inclusion(&leaf_hash, 2, 7, &audit_path, mt.root_hash()).unwrap());
+        assert!(!MerkleTree::verify_inclusion(&leaf_hash, 3, 8, &audit_path, mt.root_hash()).unwrap());
+        assert!(!MerkleTree::verify_inclusion(&leaf_hash, 7, 7, &audit_path, mt.root_hash()).unwrap());
+
+        // tampered, truncated and extended path
+        let mut tampered = audit_path.clone();
+        tampered[1][0] ^= 0x01;
+        assert!(!MerkleTree::verify_inclusion(&leaf_hash, 3, 7, &tampered, mt.root_hash()).unwrap());
+        assert!(!MerkleTree::verify_inclusion(&leaf_hash, 3, 7, &audit_path[..2].to_vec(), mt.root_hash()).unwrap());
+        let mut extended = audit_path.clone();
+        extended.push(mt.root_hash().clone());
+        assert!(!MerkleTree::verify_inclusion(&leaf_hash, 3, 7, &extended, mt.root_hash()).unwrap());
+    }
+
+    #[test]
+    fn serialize_works() {
+        let strvals   = vec![ "1", "2", "3", "4", "5", "6", "7", "8", "9", "10" ];
+        let mt = MerkleTree::from_vec(values_to_strings(&strvals)).unwrap();
+        let serialized = mt.to_bytes();
+
+        assert_eq!(serialized.len(), 4 + (10 + 5 + 2 + 1) * 32);
+        assert_eq!(serialized.len(), MerkleTree::stored_len(10));
 
+        let mut newmt = MerkleTree::from_bytes(&serialized).unwrap();
+        assert_eq!(mt, newmt);
         assert_eq!(mt.root_hash(), newmt.root_hash());
+
+        newmt.append("11").unwrap();
+        let mut values = values_to_strings(&strvals);
+        values.push("11".to_string());
+        assert_eq!(newmt, MerkleTree::from_vec(values).unwrap());
+    }
+
+    #[test]
+    fn serialize_works_for_appended_bytes() {
+        let values = (0..13).map(|x| x.to_string()).collect::<Vec<_>>();
+        let mut serialized = MerkleTree::new().unwrap().to_bytes();
+
+        for size in 1..values.len() + 1 {
+            let old_size = (size - 1) / 3 * 3;
+            let mt = MerkleTree::from_vec(values[..size].to_vec()).unwrap();
+            if size % 3 == 0 || size == values.len() {
+                serialized.extend(mt.appended_bytes(old_size));
+                assert_eq!(serialized, mt.to_bytes());
+                assert_eq!(MerkleTree::from_bytes(&serialized).unwrap(), mt);
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_works_for_empty_tree() {
+        let mt = MerkleTree::new().unwrap();
+
+        let newmt = MerkleTree::from_bytes(&mt.to_bytes()).unwrap();
+
+        assert_eq!(mt, newmt);
+        assert!(newmt.is_empty());
+    }
+
+    #[test]
+    fn deserialize_works_for_corrupted_data() {
+        let mt = MerkleTree::from_vec(values_to_strings(&[ "1", "2", "3", "4" ])).unwrap();
+        let serialized = mt.to_bytes();
+
+        let mut corrupted = serialized.clone();
+        corrupted[0] = b'X';
+        assert!(MerkleTree::from_bytes(&corrupted).is_err());
+
+        let mut corrupted = serialized.clone();
+        corrupted[3] = 2;
+        assert!(MerkleTree::from_bytes(&corrupted).is_err());
+
+        // the last hash is the root of the subtree of all leaves, completed by the last leaf
+        let mut corrupted = serialized.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0x01;
+        assert!(MerkleTree::from_bytes(&corrupted).is_err());
+
+        assert!(MerkleTree::from_bytes(&serialized[..serialized.len() - 1]).is_err());
+        assert!(MerkleTree::from_bytes(&serialized[..serialized.len() - 32]).is_err());
+
+        let mut extended = serialized.clone();
+        extended.push(0);
+        assert!(MerkleTree::from_bytes(&extended).is_err());
     }
 }
@@ -1 +1,1743 @@
-pub mod merkletree;
\ No newline at end of file
+pub mod constants;
+pub mod merkletree;
+pub mod types;
+
+extern crate serde;
+extern crate serde_json;
+extern crate time;
+
+use self::serde::Serialize;
+use self::serde_json::Value;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+use errors::common::CommonError;
+use errors::ledger::LedgerError;
+use self::merkletree::merkletree::MerkleTree;
+use services::anoncreds::constants::SIGNATURE_TYPE;
+use services::anoncreds::types::{ClaimDefinition, PublicKey, RevocationPublicKey, Schema};
+use utils::crypto::base58::Base58;
+use utils::crypto::hash::{Hash, HASH_OUTPUT_LEN};
+use utils::crypto::xsalsa20::{XSalsa20, XSALSA20_KEY_LEN, XSALSA20_NONCE_LEN};
+use utils::json::{JsonDecodable, JsonEncodable};
+
+use self::constants::{
+    AGENT_SERVICE,
+    ATTRIB,
+    CANCEL,
+    CLAIM_DEF,
+    DOMAIN_LEDGER,
+    DOMAIN_LEDGER_ID,
+    ENDPOINT_ATTR,
+    GET_ATTR,
+    GET_CLAIM_DEF,
+    GET_NYM,
+    GET_SCHEMA,
+    GET_TXN,
+    NODE,
+    NYM,
+    POOL_LEDGER,
+    POOL_LEDGER_ID,
+    POOL_UPGRADE,
+    REPLY_ONLY_FIELDS,
+    SCHEMA,
+    SERVICE_ATTR,
+    SERVICES,
+    START,
+    STEWARD,
+    TGB,
+    TRUSTEE,
+    TRUST_ANCHOR
+};
+use self::types::{
+    AttribOperation,
+    ClaimDefOperation,
+    ClaimDefOperationData,
+    DidDocument,
+    DidDocumentService,
+    GetAttribOperation,
+    GetClaimDefOperation,
+    GetDdoOperation,
+    GetNymOperation,
+    GetSchemaOperation,
+    GetSchemaOperationData,
+    GetTxnOperation,
+    LedgerTxn,
+    NodeOperation,
+    NodeOperationData,
+    NymData,
+    NymOperation,
+    PoolUpgradeData,
+    PoolUpgradeOperation,
+    Request,
+    SchemaOperation,
+    SchemaOperationData
+};
+
+lazy_static! {
+    static ref LAST_REQ_ID: Mutex<u64> = Mutex::new(0);
+}
+
+pub struct LedgerService {}
+
+impl LedgerService {
+    pub fn new() -> LedgerService {
+        LedgerService {}
+    }
+
+    pub fn build_nym_request(&self, identifier: &str, dest: &str, verkey: Option<&str>,
+                             xref: Option<&str>, alias: Option<&str>, role: Option<&str>) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        LedgerService::_validate_did(dest).map_err(CommonError::InvalidParam3)?;
+        if let Some(verkey) = verkey {
+            LedgerService::_validate_verkey(verkey).map_err(CommonError::InvalidParam4)?;
+        }
+        let role = match role {
+            Some(role) => Some(LedgerService::_role_code(role).map_err(CommonError::InvalidParam7)?),
+            None => None
+        };
+        let operation = NymOperation::new(dest.to_string(),
+                                          verkey.map(String::from),
+                                          xref.map(String::from),
+                                          alias.map(String::from),
+                                          role);
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    pub fn build_get_nym_request(&self, identifier: &str, dest: &str) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        LedgerService::_validate_did(dest).map_err(CommonError::InvalidParam3)?;
+        let operation = GetNymOperation::new(dest.to_string());
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    pub fn build_get_ddo_request(&self, identifier: &str, dest: &str) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        LedgerService::_validate_did(dest).map_err(CommonError::InvalidParam3)?;
+        let operation = GetDdoOperation::new(dest.to_string());
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Exactly one of `hash`, `raw` and `enc` must be set
+    pub fn build_attrib_request(&self, identifier: &str, dest: &str, hash: Option<&str>,
+                                raw: Option<&str>, enc: Option<&str>) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        LedgerService::_validate_did(dest).map_err(CommonError::InvalidParam3)?;
+        match (hash, raw, enc) {
+            (Some(hash), None, None) => LedgerService::_validate_hash(hash).map_err(CommonError::InvalidParam4)?,
+            (None, Some(raw), None) => LedgerService::_validate_raw(raw).map_err(CommonError::InvalidParam5)?,
+            (None, None, Some(enc)) => {
+                LedgerService::_decode_enc(enc).map_err(CommonError::InvalidParam6)?;
+            }
+            (None, None, None) => {
+                return Err(CommonError::InvalidParam4("One of hash, raw or enc must be set".to_string()));
+            }
+            (Some(_), Some(_), _) => {
+                return Err(CommonError::InvalidParam5("raw can't be set together with hash".to_string()));
+            }
+            (_, _, Some(_)) => {
+                return Err(CommonError::InvalidParam6("enc can't be set together with hash or raw".to_string()));
+            }
+        }
+        let operation = AttribOperation::new(dest.to_string(),
+                                             hash.map(String::from),
+                                             raw.map(String::from),
+                                             enc.map(String::from));
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Exactly one of `hash`, `raw` and `enc` must be set: `raw` is attribute name,
+    /// `hash` is hash of hashed attribute and `enc` is `enc` of encrypted attribute
+    pub fn build_get_attrib_request(&self, identifier: &str, dest: &str, hash: Option<&str>,
+                                    raw: Option<&str>, enc: Option<&str>) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        LedgerService::_validate_did(dest).map_err(CommonError::InvalidParam3)?;
+        match (hash, raw, enc) {
+            (Some(hash), None, None) => LedgerService::_validate_hash(hash).map_err(CommonError::InvalidParam4)?,
+            (None, Some(raw), None) if raw.is_empty() => {
+                return Err(CommonError::InvalidParam5("Attribute name is empty".to_string()));
+            }
+            (None, Some(_), None) => {}
+            (None, None, Some(enc)) => {
+                LedgerService::_decode_enc(enc).map_err(CommonError::InvalidParam6)?;
+            }
+            (None, None, None) => {
+                return Err(CommonError::InvalidParam4("One of hash, raw or enc must be set".to_string()));
+            }
+            (Some(_), Some(_), _) => {
+                return Err(CommonError::InvalidParam5("raw can't be set together with hash".to_string()));
+            }
+            (_, _, Some(_)) => {
+                return Err(CommonError::InvalidParam6("enc can't be set together with hash or raw".to_string()));
+            }
+        }
+        let operation = GetAttribOperation::new(dest.to_string(),
+                                                hash.map(String::from),
+                                                raw.map(String::from),
+                                                enc.map(String::from));
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Encrypts raw attribute json with fresh key and builds ATTRIB request with it as `enc`,
+    /// which is base58 encoded nonce followed by ciphertext.
+    /// Returns the request, hex encoded sha256 of `enc` that identifies the attribute and base58 encoded key.
+    pub fn build_encrypted_attrib_request(&self, identifier: &str, dest: &str, raw: &str) -> Result<(String, String, String), LedgerError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam3)?;
+        LedgerService::_validate_did(dest).map_err(CommonError::InvalidParam4)?;
+        LedgerService::_validate_raw(raw).map_err(CommonError::InvalidParam5)?;
+
+        let xsalsa20 = XSalsa20::new();
+        let key = xsalsa20.create_key();
+        let nonce = xsalsa20.gen_nonce();
+        let mut enc = nonce.clone();
+        enc.extend(xsalsa20.encrypt(&key, &nonce, raw.as_bytes()));
+        let enc = Base58::encode(&enc);
+        let enc_hash = LedgerService::_enc_hash(&enc)?;
+
+        let operation = AttribOperation::new(dest.to_string(), None, None, Some(enc));
+        let request = LedgerService::_build_request(identifier, operation)?;
+        Ok((request, enc_hash, Base58::encode(&key)))
+    }
+
+    /// Returns `enc` from GET_ATTRIB reply of encrypted attribute and its hash to find the key by
+    pub fn parse_get_attrib_enc(&self, response: &str) -> Result<(String, String), LedgerError> {
+        let result = LedgerService::_parse_reply(response, GET_ATTR)?;
+        let enc = match result.get("data") {
+            Some(&Value::String(ref enc)) => enc.clone(),
+            Some(&Value::Null) | None => return Err(LedgerError::NotFound("Reply has no data".to_string())),
+            Some(data) => return Err(LedgerError::InvalidTransaction(format!("Reply data {} isn't encrypted attribute", data)))
+        };
+        LedgerService::_decode_enc(&enc).map_err(LedgerError::InvalidTransaction)?;
+        let enc_hash = LedgerService::_enc_hash(&enc)?;
+        Ok((enc, enc_hash))
+    }
+
+    /// Decrypts `enc` of encrypted attribute with base58 encoded key, returns raw attribute json
+    pub fn decrypt_attrib(&self, enc: &str, key: &str) -> Result<String, LedgerError> {
+        let enc = LedgerService::_decode_enc(enc).map_err(LedgerError::InvalidTransaction)?;
+        let key = match Base58::decode(key) {
+            Ok(ref key) if key.len() == XSALSA20_KEY_LEN => key.clone(),
+            _ => return Err(LedgerError::CommonError(CommonError::InvalidState("Attribute key isn't valid".to_string())))
+        };
+
+        let raw = XSalsa20::new().decrypt(&key, &enc[..XSALSA20_NONCE_LEN], &enc[XSALSA20_NONCE_LEN..])?;
+        String::from_utf8(raw)
+            .map_err(|err| LedgerService::_invalid_reply(format!("Decrypted attribute isn't valid utf8: {}", err)))
+    }
+
+    /// Ledger type is POOL or DOMAIN, domain ledger is used by default
+    pub fn build_get_txn_request(&self, identifier: &str, ledger_type: Option<&str>, seq_no: i32) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        let ledger_id = match ledger_type {
+            Some(POOL_LEDGER) => POOL_LEDGER_ID,
+            Some(DOMAIN_LEDGER) | None => DOMAIN_LEDGER_ID,
+            Some(ledger_type) => return Err(CommonError::InvalidParam3(
+                format!("Unknown ledger type {:?}, {} or {} expected", ledger_type, POOL_LEDGER, DOMAIN_LEDGER)))
+        };
+        if seq_no <= 0 {
+            return Err(CommonError::InvalidParam4(format!("Seq no {} isn't positive number", seq_no)));
+        }
+        let operation = GetTxnOperation::new(seq_no, ledger_id);
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Returns txn from GET_TXN reply as stored on the ledger, without fields nodes add to replies,
+    /// together with its seqNo, txnTime and name of its type
+    pub fn parse_get_txn_response(&self, response: &str) -> Result<String, LedgerError> {
+        let result = LedgerService::_parse_reply(response, GET_TXN)?;
+        let mut txn = match LedgerService::_reply_data(&result)? {
+            Value::Object(txn) => txn,
+            _ => return Err(LedgerService::_invalid_reply("Txn isn't json object".to_string()))
+        };
+        let seq_no = txn.get("seqNo").and_then(Value::as_u64).or(result["seqNo"].as_u64())
+            .ok_or(LedgerService::_invalid_reply("Txn seqNo is missing".to_string()))?;
+        let txn_time = match txn.remove("txnTime") {
+            Some(Value::Null) | None => None,
+            Some(txn_time) => Some(txn_time.as_u64()
+                .ok_or(LedgerService::_invalid_reply(format!("Txn time {} isn't number", txn_time)))?)
+        };
+        let txn_type = match txn.get("type").and_then(Value::as_str) {
+            Some(txn_type) => LedgerService::_txn_type_name(txn_type),
+            None => return Err(LedgerService::_invalid_reply("Txn type is missing".to_string()))
+        };
+        for field in REPLY_ONLY_FIELDS {
+            txn.remove(*field);
+        }
+
+        LedgerTxn::new(seq_no, txn_time, txn_type, Value::Object(txn))
+            .to_json()
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidState(format!("Can't serialize txn: {}", err))))
+    }
+
+    /// Data is schema json like anoncreds one: name, version and attribute_names (or attr_names).
+    /// seq_no is ignored as it is assigned by the ledger.
+    pub fn build_schema_request(&self, identifier: &str, data: &str) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        let data = LedgerService::_parse_schema_data(data).map_err(CommonError::InvalidParam3)?;
+        let data = data.to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize schema data: {}", err)))?;
+        let operation = SchemaOperation::new(data);
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Dest is DID of schema issuer, data is json with name and version of schema
+    pub fn build_get_schema_request(&self, identifier: &str, dest: &str, data: &str) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        LedgerService::_validate_did(dest).map_err(CommonError::InvalidParam3)?;
+        let data = LedgerService::_parse_get_schema_data(data).map_err(CommonError::InvalidParam4)?;
+        let operation = GetSchemaOperation::new(dest.to_string(), data);
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Data is claim definition json returned by sovrin_issuer_create_and_store_claim_def
+    /// for the schema with seq_no equal to xref
+    pub fn build_claim_def_request(&self, identifier: &str, xref: &str, data: &str) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        let xref = LedgerService::_parse_seq_no(xref).map_err(CommonError::InvalidParam3)?;
+        let claim_def = ClaimDefinition::from_json(data)
+            .map_err(|err| CommonError::InvalidParam4(format!("Invalid claim definition json: {}", err)))?;
+        if claim_def.schema_seq_no != xref {
+            return Err(CommonError::InvalidParam4(
+                format!("Claim definition is for schema {}, but ref is {}", claim_def.schema_seq_no, xref)));
+        }
+        let data = ClaimDefOperationData::new(claim_def.public_key, claim_def.public_key_revocation);
+        let operation = ClaimDefOperation::new(xref, claim_def.signature_type, data);
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Origin is DID of claim definition issuer
+    pub fn build_get_claim_def_request(&self, identifier: &str, xref: &str, signature_type: Option<&str>,
+                                       origin: &str) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        let xref = LedgerService::_parse_seq_no(xref).map_err(CommonError::InvalidParam3)?;
+        LedgerService::_validate_did(origin).map_err(CommonError::InvalidParam5)?;
+        let signature_type = signature_type.unwrap_or(SIGNATURE_TYPE).to_string();
+        let operation = GetClaimDefOperation::new(xref, signature_type, origin.to_string());
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Dest is node verkey, data is NODE txn data. Absent fields of data keep their current values.
+    pub fn build_node_request(&self, identifier: &str, dest: &str, data: &str) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        LedgerService::_validate_node_key(dest).map_err(CommonError::InvalidParam3)?;
+        let data = NodeOperationData::from_json(data)
+            .map_err(|err| CommonError::InvalidParam4(format!("Invalid node data json: {}", err)))?;
+        LedgerService::_validate_node_data(&data).map_err(CommonError::InvalidParam4)?;
+        let operation = NodeOperation::new(dest.to_string(), data);
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    pub fn build_pool_upgrade_request(&self, identifier: &str, data: &str) -> Result<String, CommonError> {
+        LedgerService::_validate_did(identifier).map_err(CommonError::InvalidParam2)?;
+        let data = PoolUpgradeData::from_json(data)
+            .map_err(|err| CommonError::InvalidParam3(format!("Invalid pool upgrade json: {}", err)))?;
+        LedgerService::_validate_pool_upgrade_data(&data).map_err(CommonError::InvalidParam3)?;
+        let operation = PoolUpgradeOperation::new(data);
+        LedgerService::_build_request(identifier, operation)
+    }
+
+    /// Converts GET_SCHEMA reply into schema json expected by anoncreds
+    pub fn parse_get_schema_response(&self, response: &str) -> Result<String, LedgerError> {
+        let result = LedgerService::_parse_reply(response, GET_SCHEMA)?;
+        let data = LedgerService::_reply_data(&result)?;
+        let (name, version) = LedgerService::_parse_name_version(&data)
+            .map_err(LedgerService::_invalid_reply)?;
+        let attr_names = match data.get("attr_names").or(data.get("attribute_names")) {
+            Some(&Value::Array(ref attr_names)) => attr_names.iter()
+                .map(|attr_name| attr_name.as_str().map(String::from))
+                .collect::<Option<HashSet<String>>>()
+                .ok_or(LedgerService::_invalid_reply("Schema attribute names must be strings".to_string()))?,
+            _ => return Err(LedgerService::_invalid_reply("Schema attr_names must be array".to_string()))
+        };
+        let seq_no = data["seqNo"].as_i64().or(result["seqNo"].as_i64())
+            .ok_or(LedgerService::_invalid_reply("Schema seqNo is missing".to_string()))?;
+        Schema::new(name, version, attr_names, seq_no as i32)
+            .to_json()
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidState(format!("Can't serialize schema: {}", err))))
+    }
+
+    /// Converts GET_CLAIM_DEF reply into claim definition json expected by anoncreds
+    pub fn parse_get_claim_def_response(&self, response: &str) -> Result<String, LedgerError> {
+        let result = LedgerService::_parse_reply(response, GET_CLAIM_DEF)?;
+        let data = LedgerService::_reply_data(&result)?;
+        let schema_seq_no = result["ref"].as_i64()
+            .ok_or(LedgerService::_invalid_reply("Claim definition ref is missing".to_string()))?;
+        let signature_type = result["signature_type"].as_str()
+            .ok_or(LedgerService::_invalid_reply("Claim definition signature_type is missing".to_string()))?;
+        let public_key: PublicKey = serde_json::from_value(data["primary"].clone())
+            .map_err(|err| LedgerService::_invalid_reply(format!("Invalid claim definition primary key: {}", err)))?;
+        let public_key_revocation: Option<RevocationPublicKey> = match data.get("revocation") {
+            Some(&Value::Null) | None => None,
+            Some(revocation) => Some(serde_json::from_value(revocation.clone())
+                .map_err(|err| LedgerService::_invalid_reply(format!("Invalid claim definition revocation key: {}", err)))?)
+        };
+        ClaimDefinition::new(public_key, public_key_revocation, schema_seq_no as i32, signature_type.to_string())
+            .to_json()
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidState(format!("Can't serialize claim definition: {}", err))))
+    }
+
+    /// Converts GET_NYM reply into json with did, identifier of its creator, verkey and role name
+    pub fn parse_get_nym_response(&self, response: &str) -> Result<String, LedgerError> {
+        LedgerService::_parse_nym(response)?
+            .to_json()
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidState(format!("Can't serialize nym: {}", err))))
+    }
+
+    /// Returns verkey of the DID from GET_NYM reply, abbreviated one is expanded
+    pub fn parse_get_nym_verkey(&self, did: &str, get_nym_response: &str) -> Result<Option<String>, LedgerError> {
+        let nym = LedgerService::_parse_nym(get_nym_response)?;
+        if nym.did != did {
+            return Err(LedgerService::_invalid_reply(format!("Nym reply is for {:?}, {:?} expected", nym.did, did)));
+        }
+        LedgerService::_full_verkey(&nym.did, nym.verkey.as_ref().map(String::as_str))
+            .map_err(LedgerService::_invalid_reply)
+    }
+
+    /// Returns nym of the DID from GET_NYM reply or None if the DID isn't on the ledger
+    pub fn parse_get_nym_data(&self, did: &str, get_nym_response: &str) -> Result<Option<NymData>, LedgerError> {
+        let nym = match LedgerService::_parse_nym(get_nym_response) {
+            Ok(nym) => nym,
+            Err(LedgerError::NotFound(_)) => return Ok(None),
+            Err(err) => return Err(err)
+        };
+        if nym.did != did {
+            return Err(LedgerService::_invalid_reply(format!("Nym reply is for {:?}, {:?} expected", nym.did, did)));
+        }
+        Ok(Some(nym))
+    }
+
+    /// Returns DIDs whose nyms are needed to check auth rules for the request.
+    /// Requests without auth rules need none.
+    pub fn auth_check_dids(&self, request_json: &str) -> Result<Vec<String>, LedgerError> {
+        let request = LedgerService::_parse_request(request_json)?;
+        let identifier = LedgerService::_request_field(&request, "identifier")?;
+        let operation = &request["operation"];
+        let mut dids = vec![identifier.to_string()];
+        match operation["type"].as_str() {
+            Some(NYM) | Some(ATTRIB) => {
+                let dest = LedgerService::_request_field(operation, "dest")?;
+                if dest != identifier {
+                    dids.push(dest.to_string());
+                }
+            }
+            Some(SCHEMA) | Some(CLAIM_DEF) | Some(NODE) => {}
+            _ => return Ok(Vec::new())
+        }
+        Ok(dids)
+    }
+
+    /// Checks auth rules of the pool for NYM, ATTRIB, SCHEMA, CLAIM_DEF and NODE requests
+    /// against nyms of DIDs returned by auth_check_dids, None is for DIDs that aren't on the ledger.
+    pub fn check_auth_rules(&self, request_json: &str, nyms: &HashMap<String, Option<NymData>>) -> Result<(), LedgerError> {
+        let request = LedgerService::_parse_request(request_json)?;
+        let identifier = LedgerService::_request_field(&request, "identifier")?;
+        let operation = &request["operation"];
+        let txn_type = match operation["type"].as_str() {
+            Some(txn_type) if [NYM, ATTRIB, SCHEMA, CLAIM_DEF, NODE].contains(&txn_type) => txn_type,
+            _ => return Ok(())
+        };
+        let submitter = LedgerService::_resolved_nym(nyms, identifier)?
+            .ok_or(LedgerError::SecurityError(format!("Submitter {:?} isn't on the ledger", identifier)))?;
+        let submitter_role = submitter.role.as_ref().map(String::as_str);
+
+        match txn_type {
+            NYM => {
+                let dest = LedgerService::_request_field(operation, "dest")?;
+                let dest_nym = if dest == identifier { Some(submitter) } else { LedgerService::_resolved_nym(nyms, dest)? };
+                let new_role = match operation.get("role") {
+                    None => None,
+                    Some(&Value::Null) => Some(None),
+                    Some(&Value::String(ref code)) if code.is_empty() => Some(None),
+                    Some(&Value::String(ref code)) => Some(Some(LedgerService::_role_name(code)
+                        .map_err(|err| LedgerError::CommonError(CommonError::InvalidStructure(err)))?)),
+                    Some(role) => return Err(LedgerError::CommonError(CommonError::InvalidStructure(
+                        format!("Invalid role {}", role))))
+                };
+                match dest_nym {
+                    None => {
+                        let role = new_role.unwrap_or(None);
+                        LedgerService::_check_role(submitter_role, LedgerService::_roles_to_add(role.as_ref().map(String::as_str)),
+                                                   &format!("add nym with role {:?}", role))?;
+                    }
+                    Some(dest_nym) => {
+                        match new_role {
+                            Some(ref role) if *role != dest_nym.role => {
+                                let granters: &[&str] = match *role {
+                                    Some(ref role) => LedgerService::_roles_to_add(Some(role)),
+                                    None => &["TRUSTEE"]
+                                };
+                                LedgerService::_check_role(submitter_role, granters,
+                                                           &format!("change role of {:?} to {:?}", dest, role))?;
+                            }
+                            _ => {}
+                        }
+                        if operation.get("verkey").is_some() {
+                            LedgerService::_check_owner(identifier, dest_nym, "change verkey")?;
+                        }
+                    }
+                }
+            }
+            ATTRIB => {
+                let dest = LedgerService::_request_field(operation, "dest")?;
+                let dest_nym = if dest == identifier { Some(submitter) } else { LedgerService::_resolved_nym(nyms, dest)? };
+                let dest_nym = dest_nym
+                    .ok_or(LedgerError::SecurityError(format!("Attribute target {:?} isn't on the ledger", dest)))?;
+                LedgerService::_check_owner(identifier, dest_nym, "add attribute")?;
+            }
+            SCHEMA | CLAIM_DEF => {
+                LedgerService::_check_role(submitter_role, &["TRUSTEE", "STEWARD", "TRUST_ANCHOR"],
+                                           if txn_type == SCHEMA { "add schema" } else { "add claim definition" })?;
+            }
+            _ => {
+                LedgerService::_check_role(submitter_role, &["STEWARD"], "add node")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Assembles DID document from GET_NYM reply and GET_ATTRIB replies of the same DID.
+    /// Attribute replies without data are skipped, later replies override earlier ones.
+    pub fn build_did_document(&self, get_nym_response: &str, get_attrib_responses: &[String]) -> Result<String, LedgerError> {
+        let nym = LedgerService::_parse_nym(get_nym_response)?;
+        let verkey = LedgerService::_full_verkey(&nym.did, nym.verkey.as_ref().map(String::as_str))
+            .map_err(LedgerService::_invalid_reply)?;
+
+        let mut attributes: BTreeMap<String, Value> = BTreeMap::new();
+        for response in get_attrib_responses {
+            let result = LedgerService::_parse_reply(response, GET_ATTR)?;
+            if result["dest"].as_str() != Some(nym.did.as_str()) {
+                return Err(LedgerService::_invalid_reply(
+                    format!("Attribute reply is for {}, {:?} expected", result["dest"], nym.did)));
+            }
+            let data = match LedgerService::_reply_data(&result) {
+                Ok(data) => data,
+                Err(LedgerError::NotFound(_)) => continue,
+                Err(err) => return Err(err)
+            };
+            if let Value::Object(data) = data {
+                attributes.extend(data.into_iter());
+            }
+        }
+
+        let endpoint = attributes.get(ENDPOINT_ATTR).cloned();
+        let mut service: Vec<DidDocumentService> = Vec::new();
+        if let Some(ha) = endpoint.as_ref().and_then(|endpoint| endpoint["ha"].as_str()) {
+            service.push(DidDocumentService::new(AGENT_SERVICE.to_string(), ha.to_string()));
+        }
+        if let Some(&Value::Array(ref entries)) = attributes.get(SERVICE_ATTR) {
+            for entry in entries {
+                match (entry["type"].as_str(), entry["serviceEndpoint"].as_str()) {
+                    (Some(service_type), Some(service_endpoint)) =>
+                        service.push(DidDocumentService::new(service_type.to_string(), service_endpoint.to_string())),
+                    _ => return Err(LedgerService::_invalid_reply(
+                        format!("Service entry {} must have type and serviceEndpoint", entry)))
+                }
+            }
+        }
+
+        DidDocument::new(nym.did, verkey, nym.role, endpoint, service, attributes)
+            .to_json()
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidState(format!("Can't serialize DID document: {}", err))))
+    }
+
+    /// Checks that txn from write reply is included in the ledger with given root and size.
+    /// Ledger leaf is txn json without reply only fields, as nodes store it, and its position is seqNo.
+    pub fn verify_txn_inclusion(&self, txn_json: &str, audit_path: &str, root_hash: &str, tree_size: i32) -> Result<bool, LedgerError> {
+        let mut txn = match serde_json::from_str::<Value>(txn_json) {
+            Ok(Value::Object(txn)) => txn,
+            Ok(_) => return Err(LedgerError::CommonError(CommonError::InvalidParam2("Txn isn't json object".to_string()))),
+            Err(err) => return Err(LedgerError::CommonError(CommonError::InvalidParam2(format!("Txn isn't valid json: {}", err))))
+        };
+        let seq_no = match txn.get("seqNo").and_then(Value::as_u64) {
+            Some(seq_no) if seq_no > 0 => seq_no as usize,
+            _ => return Err(LedgerError::CommonError(CommonError::InvalidParam2("Txn seqNo isn't positive number".to_string())))
+        };
+        for field in REPLY_ONLY_FIELDS {
+            txn.remove(*field);
+        }
+        let leaf = serde_json::to_string(&Value::Object(txn))
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidState(format!("Can't serialize txn: {}", err))))?;
+
+        let audit_path: Vec<String> = serde_json::from_str(audit_path)
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidParam3(format!("Audit path isn't json array of strings: {}", err))))?;
+        let audit_path = audit_path.iter()
+            .map(|hash| LedgerService::_decode_hash(hash))
+            .collect::<Result<Vec<Vec<u8>>, String>>()
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidParam3(err)))?;
+        let root_hash = LedgerService::_decode_hash(root_hash)
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidParam4(err)))?;
+        if tree_size <= 0 {
+            return Err(LedgerError::CommonError(CommonError::InvalidParam5(format!("Tree size {} isn't positive number", tree_size))));
+        }
+
+        let leaf_hash = Hash::hash_leaf(&leaf)?.to_vec();
+        Ok(MerkleTree::verify_inclusion(&leaf_hash, seq_no - 1, tree_size as usize, &audit_path, &root_hash)?)
+    }
+
+    fn _build_request<T: Serialize>(identifier: &str, operation: T) -> Result<String, CommonError> {
+        Request::new(LedgerService::_gen_req_id(), identifier.to_string(), operation)
+            .to_json()
+            .map_err(|err| CommonError::InvalidState(format!("Can't serialize request: {}", err)))
+    }
+
+    /// Time based in nanoseconds, but strictly increasing even if the clock is coarse or goes back,
+    /// so requests built in the same process never share reqId
+    fn _gen_req_id() -> u64 {
+        let now = time::get_time();
+        let now = now.sec as u64 * 1_000_000_000 + now.nsec as u64;
+        let mut last_req_id = LAST_REQ_ID.lock().unwrap();
+        *last_req_id = if now > *last_req_id { now } else { *last_req_id + 1 };
+        *last_req_id
+    }
+
+    /// DID is base58 encoded 16 bytes or full 32 bytes verkey
+    fn _validate_did(did: &str) -> Result<(), String> {
+        match Base58::decode(did) {
+            Ok(ref bytes) if bytes.len() == 16 || bytes.len() == 32 => Ok(()),
+            Ok(bytes) => Err(format!("DID {:?} has {} bytes, 16 or 32 expected", did, bytes.len())),
+            Err(_) => Err(format!("DID {:?} isn't valid base58", did))
+        }
+    }
+
+    /// Verkey is base58 encoded 32 bytes or abbreviated "~" prefixed 16 bytes
+    fn _validate_verkey(verkey: &str) -> Result<(), String> {
+        let (key, expected_len) = if verkey.starts_with('~') { (&verkey[1..], 16) } else { (verkey, 32) };
+        match Base58::decode(key) {
+            Ok(ref bytes) if bytes.len() == expected_len => Ok(()),
+            Ok(bytes) => Err(format!("Verkey {:?} has {} bytes, {} expected", verkey, bytes.len(), expected_len)),
+            Err(_) => Err(format!("Verkey {:?} isn't valid base58", verkey))
+        }
+    }
+
+    /// Node is identified by base58 encoded 32 bytes verkey
+    fn _validate_node_key(key: &str) -> Result<(), String> {
+        match Base58::decode(key) {
+            Ok(ref bytes) if bytes.len() == 32 => Ok(()),
+            Ok(bytes) => Err(format!("Node key {:?} has {} bytes, 32 expected", key, bytes.len())),
+            Err(_) => Err(format!("Node key {:?} isn't valid base58", key))
+        }
+    }
+
+    /// Merkle tree hashes are base58 encoded sha256
+    fn _decode_hash(hash: &str) -> Result<Vec<u8>, String> {
+        match Base58::decode(hash) {
+            Ok(bytes) if bytes.len() == HASH_OUTPUT_LEN => Ok(bytes),
+            Ok(bytes) => Err(format!("Hash {:?} has {} bytes, {} expected", hash, bytes.len(), HASH_OUTPUT_LEN)),
+            Err(_) => Err(format!("Hash {:?} isn't valid base58", hash))
+        }
+    }
+
+    /// Hash is hex encoded sha256 of attribute data
+    fn _validate_hash(hash: &str) -> Result<(), String> {
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_digit(16)) {
+            return Err(format!("Hash {:?} isn't hex encoded sha256", hash));
+        }
+        Ok(())
+    }
+
+    /// Encrypted attribute is identified by hex encoded sha256 of its `enc`
+    fn _enc_hash(enc: &str) -> Result<String, LedgerError> {
+        let hash = Hash::hash(enc.as_bytes())?.to_vec();
+        Ok(hash.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    /// Enc is base58 encoded nonce followed by ciphertext
+    fn _decode_enc(enc: &str) -> Result<Vec<u8>, String> {
+        match Base58::decode(enc) {
+            Ok(ref bytes) if bytes.len() > XSALSA20_NONCE_LEN => Ok(bytes.clone()),
+            Ok(_) => Err(format!("Encrypted attribute {:?} is too short", enc)),
+            Err(_) => Err(format!("Encrypted attribute {:?} isn't valid base58", enc))
+        }
+    }
+
+    /// Raw is json object where key is attribute name and value is its value
+    fn _validate_raw(raw: &str) -> Result<(), String> {
+        match serde_json::from_str::<Value>(raw) {
+            Ok(Value::Object(ref attrs)) if !attrs.is_empty() => Ok(()),
+            Ok(_) => Err(format!("Raw {:?} isn't json object with attributes", raw)),
+            Err(err) => Err(format!("Raw {:?} isn't valid json: {}", raw, err))
+        }
+    }
+
+    fn _parse_schema_data(data: &str) -> Result<SchemaOperationData, String> {
+        let data: Value = serde_json::from_str(data)
+            .map_err(|err| format!("Schema {:?} isn't valid json: {}", data, err))?;
+        let (name, version) = LedgerService::_parse_name_version(&data)?;
+        let attr_names = match data.get("attribute_names").or(data.get("attr_names")) {
+            Some(&Value::Array(ref attr_names)) => attr_names,
+            _ => return Err("Schema attribute_names must be array".to_string())
+        };
+        let mut result: Vec<String> = Vec::new();
+        for attr_name in attr_names {
+            match attr_name.as_str() {
+                Some(attr_name) if attr_name.is_empty() => return Err("Schema attribute name can't be empty".to_string()),
+                Some(attr_name) if result.iter().any(|name| name == attr_name) =>
+                    return Err(format!("Schema attribute {:?} is duplicated", attr_name)),
+                Some(attr_name) => result.push(attr_name.to_string()),
+                None => return Err(format!("Schema attribute name {} isn't string", attr_name))
+            }
+        }
+        if result.is_empty() {
+            return Err("Schema must have at least one attribute".to_string());
+        }
+        Ok(SchemaOperationData::new(name, version, result))
+    }
+
+    fn _parse_get_schema_data(data: &str) -> Result<GetSchemaOperationData, String> {
+        let data: Value = serde_json::from_str(data)
+            .map_err(|err| format!("Data {:?} isn't valid json: {}", data, err))?;
+        let (name, version) = LedgerService::_parse_name_version(&data)?;
+        Ok(GetSchemaOperationData::new(name, version))
+    }
+
+    fn _parse_name_version(data: &Value) -> Result<(String, String), String> {
+        match (data["name"].as_str(), data["version"].as_str()) {
+            (Some(name), Some(version)) if !name.is_empty() && !version.is_empty() =>
+                Ok((name.to_string(), version.to_string())),
+            _ => Err("Schema name and version must be non empty strings".to_string())
+        }
+    }
+
+    /// Seq no of txn on the ledger is positive number
+    fn _parse_seq_no(seq_no: &str) -> Result<i32, String> {
+        match seq_no.parse::<i32>() {
+            Ok(seq_no) if seq_no > 0 => Ok(seq_no),
+            _ => Err(format!("Seq no {:?} isn't positive number", seq_no))
+        }
+    }
+
+    fn _validate_node_data(data: &NodeOperationData) -> Result<(), String> {
+        if data.alias.is_empty() {
+            return Err("Node alias can't be empty".to_string());
+        }
+        LedgerService::_validate_address(&data.client_ip, &data.client_port)
+            .map_err(|err| format!("Invalid client address: {}", err))?;
+        LedgerService::_validate_address(&data.node_ip, &data.node_port)
+            .map_err(|err| format!("Invalid node address: {}", err))?;
+        if let Some(ref services) = data.services {
+            if let Some(service) = services.iter().find(|service| !SERVICES.iter().any(|known| *known == service.as_str())) {
+                return Err(format!("Unknown service {:?}, one of {:?} expected", service, SERVICES));
+            }
+        }
+        if let Some(ref blskey) = data.blskey {
+            match Base58::decode(blskey) {
+                Ok(ref bytes) if !bytes.is_empty() => {}
+                _ => return Err(format!("BLS key {:?} isn't valid base58", blskey))
+            }
+        }
+        Ok(())
+    }
+
+    /// IP and port are set together, as the node can't be reached by only one of them
+    fn _validate_address(ip: &Option<String>, port: &Option<u32>) -> Result<(), String> {
+        match (ip, port) {
+            (&Some(ref ip), &Some(port)) => {
+                ip.parse::<IpAddr>().map_err(|_| format!("{:?} isn't valid IP", ip))?;
+                if port == 0 || port > 65535 {
+                    return Err(format!("{} isn't valid port", port));
+                }
+                Ok(())
+            }
+            (&None, &None) => Ok(()),
+            _ => Err("IP and port must be set together".to_string())
+        }
+    }
+
+    fn _validate_pool_upgrade_data(data: &PoolUpgradeData) -> Result<(), String> {
+        if data.name.is_empty() || data.version.is_empty() {
+            return Err("Upgrade name and version can't be empty".to_string());
+        }
+        LedgerService::_validate_hash(&data.sha256)?;
+        if data.timeout == Some(0) {
+            return Err("Upgrade timeout must be positive".to_string());
+        }
+        match data.action.as_str() {
+            START => {
+                match data.schedule {
+                    Some(ref schedule) if !schedule.is_empty() => {}
+                    _ => return Err("Upgrade start requires non empty schedule".to_string())
+                }
+            }
+            CANCEL => {}
+            action => return Err(format!("Unknown upgrade action {:?}, {:?} or {:?} expected", action, START, CANCEL))
+        }
+        if let Some(ref schedule) = data.schedule {
+            for (dest, time) in schedule {
+                LedgerService::_validate_node_key(dest)?;
+                if time.is_empty() {
+                    return Err(format!("Upgrade time of node {:?} can't be empty", dest));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn _parse_nym(response: &str) -> Result<NymData, LedgerError> {
+        let result = LedgerService::_parse_reply(response, GET_NYM)?;
+        let data = LedgerService::_reply_data(&result)?;
+        let did = data["dest"].as_str()
+            .ok_or(LedgerService::_invalid_reply("Nym dest is missing".to_string()))?;
+        let role = match data["role"].as_str() {
+            Some(role) if !role.is_empty() => Some(LedgerService::_role_name(role).map_err(LedgerService::_invalid_reply)?),
+            _ => None
+        };
+        Ok(NymData::new(did.to_string(),
+                        data["identifier"].as_str().map(String::from),
+                        data["verkey"].as_str().map(String::from),
+                        role))
+    }
+
+    /// Expands abbreviated "~" verkey with DID bytes. DID of 32 bytes without verkey is the verkey itself.
+    fn _full_verkey(did: &str, verkey: Option<&str>) -> Result<Option<String>, String> {
+        let did_bytes = Base58::decode(did)
+            .map_err(|_| format!("DID {:?} isn't valid base58", did))?;
+        match verkey {
+            Some(verkey) if verkey.starts_with('~') => {
+                let mut key = did_bytes;
+                key.extend(Base58::decode(&verkey[1..])
+                    .map_err(|_| format!("Verkey {:?} isn't valid base58", verkey))?);
+                if key.len() != 32 {
+                    return Err(format!("Abbreviated verkey {:?} doesn't match DID {:?}", verkey, did));
+                }
+                Ok(Some(Base58::encode(&key)))
+            }
+            Some(verkey) => Ok(Some(verkey.to_string())),
+            None if did_bytes.len() == 32 => Ok(Some(did.to_string())),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns result of REPLY for the txn type, REQNACK and REJECT are reported as invalid transaction
+    fn _parse_reply(response: &str, txn_type: &str) -> Result<Value, LedgerError> {
+        let response: Value = serde_json::from_str(response)
+            .map_err(|err| LedgerService::_invalid_reply(format!("Response isn't valid json: {}", err)))?;
+        match response["op"].as_str() {
+            Some("REPLY") => {}
+            Some("REQNACK") | Some("REJECT") => {
+                return Err(LedgerError::InvalidTransaction(
+                    response["reason"].as_str().unwrap_or("Request was rejected by the pool").to_string()));
+            }
+            _ => return Err(LedgerService::_invalid_reply(format!("Unexpected response op {}", response["op"])))
+        }
+        let result = response["result"].clone();
+        if result["type"].as_str() != Some(txn_type) {
+            return Err(LedgerService::_invalid_reply(
+                format!("Reply is for txn type {}, {:?} expected", result["type"], txn_type)));
+        }
+        Ok(result)
+    }
+
+    /// Reply data can be json object or json string with object, null means nothing was found
+    fn _reply_data(result: &Value) -> Result<Value, LedgerError> {
+        let data = match result.get("data") {
+            Some(&Value::String(ref data)) => serde_json::from_str(data)
+                .map_err(|err| LedgerService::_invalid_reply(format!("Reply data isn't valid json: {}", err)))?,
+            Some(data) => data.clone(),
+            None => Value::Null
+        };
+        match data {
+            Value::Null => Err(LedgerError::NotFound("Reply has no data".to_string())),
+            Value::Object(fields) => {
+                if fields.is_empty() {
+                    return Err(LedgerError::NotFound("Reply has no data".to_string()));
+                }
+                Ok(Value::Object(fields))
+            }
+            data => Err(LedgerService::_invalid_reply(format!("Reply data {} isn't json object", data)))
+        }
+    }
+
+    fn _invalid_reply(err: String) -> LedgerError {
+        LedgerError::CommonError(CommonError::InvalidStructure(err))
+    }
+
+    fn _parse_request(request_json: &str) -> Result<Value, LedgerError> {
+        serde_json::from_str(request_json)
+            .map_err(|err| LedgerError::CommonError(CommonError::InvalidStructure(format!("Invalid request json: {}", err))))
+    }
+
+    fn _request_field<'a>(value: &'a Value, field: &str) -> Result<&'a str, LedgerError> {
+        value[field].as_str()
+            .ok_or(LedgerError::CommonError(CommonError::InvalidStructure(format!("Request {} is missing", field))))
+    }
+
+    fn _resolved_nym<'a>(nyms: &'a HashMap<String, Option<NymData>>, did: &str) -> Result<Option<&'a NymData>, LedgerError> {
+        nyms.get(did)
+            .map(Option::as_ref)
+            .ok_or(LedgerError::CommonError(CommonError::InvalidState(format!("Nym of {:?} isn't resolved", did))))
+    }
+
+    /// Roles allowed to add nym with the role, identity owner without role can be added by any of them
+    fn _roles_to_add(role: Option<&str>) -> &'static [&'static str] {
+        match role {
+            Some("TRUST_ANCHOR") => &["TRUSTEE", "STEWARD"],
+            Some(_) => &["TRUSTEE"],
+            None => &["TRUSTEE", "STEWARD", "TRUST_ANCHOR"]
+        }
+    }
+
+    fn _check_role(role: Option<&str>, allowed: &[&str], action: &str) -> Result<(), LedgerError> {
+        match role {
+            Some(role) if allowed.iter().any(|allowed| *allowed == role) => Ok(()),
+            _ => Err(LedgerError::SecurityError(format!("Submitter with role {:?} can't {}, {:?} required", role, action, allowed)))
+        }
+    }
+
+    /// Nym without verkey is owned by its guardian (the identifier that added it)
+    fn _check_owner(identifier: &str, nym: &NymData, action: &str) -> Result<(), LedgerError> {
+        let owner = match nym.verkey {
+            Some(_) => Some(nym.did.as_str()),
+            None => nym.identifier.as_ref().map(String::as_str)
+        };
+        if owner != Some(identifier) {
+            return Err(LedgerError::SecurityError(format!("Only owner of {:?} can {}", nym.did, action)));
+        }
+        Ok(())
+    }
+
+    fn _role_code(role: &str) -> Result<String, String> {
+        match role {
+            "TRUSTEE" => Ok(TRUSTEE.to_string()),
+            "TGB" => Ok(TGB.to_string()),
+            "STEWARD" => Ok(STEWARD.to_string()),
+            "TRUST_ANCHOR" => Ok(TRUST_ANCHOR.to_string()),
+            _ => Err(format!("Unknown role {:?}, TRUSTEE, TGB, STEWARD or TRUST_ANCHOR expected", role))
+        }
+    }
+
+    /// Name of txn type as in requests, unknown types are named by their code
+    fn _txn_type_name(code: &str) -> String {
+        match code {
+            NODE => "NODE".to_string(),
+            NYM => "NYM".to_string(),
+            ATTRIB => "ATTRIB".to_string(),
+            SCHEMA => "SCHEMA".to_string(),
+            CLAIM_DEF => "CLAIM_DEF".to_string(),
+            POOL_UPGRADE => "POOL_UPGRADE".to_string(),
+            _ => code.to_string()
+        }
+    }
+
+    fn _role_name(code: &str) -> Result<String, String> {
+        match code {
+            TRUSTEE => Ok("TRUSTEE".to_string()),
+            TGB => Ok("TGB".to_string()),
+            STEWARD => Ok("STEWARD".to_string()),
+            TRUST_ANCHOR => Ok("TRUST_ANCHOR".to_string()),
+            _ => Err(format!("Unknown role code {:?}", code))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use services::anoncreds::issuer;
+
+    const IDENTIFIER: &'static str = "Th7MpTaRZVRYnPiabds81Y";
+    const DEST: &'static str = "FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4";
+    const VERKEY: &'static str = "~7TYfekw4GUagBnBVCqPjiC";
+    const GET_NYM_REPLY: &'static str = r#"{"op":"REPLY","result":{"identifier":"V4SGRU86Z58d6TV7PBUe6f","reqId":1,"seqNo":2,"type":"105","dest":"Th7MpTaRZVRYnPiabds81Y","data":"{\"dest\":\"Th7MpTaRZVRYnPiabds81Y\",\"identifier\":\"V4SGRU86Z58d6TV7PBUe6f\",\"role\":\"101\",\"verkey\":\"~7TYfekw4GUagBnBVCqPjiC\"}"}}"#;
+    const GET_ENDPOINT_REPLY: &'static str = r#"{"op":"REPLY","result":{"identifier":"V4SGRU86Z58d6TV7PBUe6f","reqId":2,"seqNo":3,"type":"104","dest":"Th7MpTaRZVRYnPiabds81Y","raw":"endpoint","data":"{\"endpoint\":{\"ha\":\"127.0.0.1:5555\"}}"}}"#;
+    const GET_SERVICE_REPLY: &'static str = r#"{"op":"REPLY","result":{"identifier":"V4SGRU86Z58d6TV7PBUe6f","reqId":3,"seqNo":4,"type":"104","dest":"Th7MpTaRZVRYnPiabds81Y","raw":"service","data":"{\"service\":[{\"type\":\"hub\",\"serviceEndpoint\":\"https://hub.example.com\"}]}"}}"#;
+    const GET_MISSING_ATTR_REPLY: &'static str = r#"{"op":"REPLY","result":{"identifier":"V4SGRU86Z58d6TV7PBUe6f","reqId":4,"type":"104","dest":"Th7MpTaRZVRYnPiabds81Y","raw":"email","data":null}}"#;
+
+    fn _operation(request: &str) -> Value {
+        let request: Value = serde_json::from_str(request).unwrap();
+        assert_eq!(request["identifier"], IDENTIFIER);
+        assert!(request["reqId"].is_u64());
+        assert_eq!(request["protocolVersion"], constants::PROTOCOL_VERSION);
+        request["operation"].clone()
+    }
+
+    #[test]
+    fn gen_req_id_works() {
+        let req_ids: Vec<u64> = (0..1000).map(|_| LedgerService::_gen_req_id()).collect();
+
+        assert!(req_ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn build_nym_request_works() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_nym_request(IDENTIFIER, DEST, Some(VERKEY), None, Some("alias"), Some("STEWARD")).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"type":"1","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4","verkey":"~7TYfekw4GUagBnBVCqPjiC","alias":"alias","role":"2"}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+    }
+
+    #[test]
+    fn build_nym_request_works_for_only_required_fields() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_nym_request(IDENTIFIER, DEST, None, None, None, None).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"type":"1","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4"}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+    }
+
+    #[test]
+    fn build_nym_request_works_for_invalid_params() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_nym_request("invalid_base58_0OIl", DEST, None, None, None, None);
+        assert_match!(Err(CommonError::InvalidParam2(_)), res);
+
+        let res = ledger_service.build_nym_request(IDENTIFIER, "Ldp", None, None, None, None);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_nym_request(IDENTIFIER, DEST, Some("~Ldp"), None, None, None);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_nym_request(IDENTIFIER, DEST, None, None, None, Some("ADMIN"));
+        assert_match!(Err(CommonError::InvalidParam7(_)), res);
+    }
+
+    #[test]
+    fn build_get_nym_request_works() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_get_nym_request(IDENTIFIER, DEST).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"type":"105","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4"}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+    }
+
+    #[test]
+    fn build_attrib_request_works_for_raw() {
+        let ledger_service = LedgerService::new();
+        let raw = r#"{"endpoint":{"ha":"127.0.0.1:5555"}}"#;
+
+        let request = ledger_service.build_attrib_request(IDENTIFIER, DEST, None, Some(raw), None).unwrap();
+
+        let operation = _operation(&request);
+        assert_eq!(operation["type"], "100");
+        assert_eq!(operation["dest"], DEST);
+        assert_eq!(operation["raw"], raw);
+        assert!(operation.get("hash").is_none());
+        assert!(operation.get("enc").is_none());
+    }
+
+    #[test]
+    fn build_attrib_request_works_for_hash() {
+        let ledger_service = LedgerService::new();
+        let hash = "83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3";
+
+        let request = ledger_service.build_attrib_request(IDENTIFIER, DEST, Some(hash), None, None).unwrap();
+
+        assert_eq!(_operation(&request)["hash"], hash);
+    }
+
+    #[test]
+    fn build_attrib_request_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_attrib_request(IDENTIFIER, DEST, None, None, None);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_attrib_request(IDENTIFIER, DEST, Some("not_a_hash"), None, None);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_attrib_request(IDENTIFIER, DEST, None, Some("[]"), None);
+        assert_match!(Err(CommonError::InvalidParam5(_)), res);
+
+        let res = ledger_service.build_attrib_request(IDENTIFIER, DEST, None, Some("{\"name\":\"Alex\"}"), Some("enc"));
+        assert_match!(Err(CommonError::InvalidParam6(_)), res);
+    }
+
+    #[test]
+    fn build_attrib_request_works_for_invalid_enc() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_attrib_request(IDENTIFIER, DEST, None, None, Some("not_base58_0OIl"));
+        assert_match!(Err(CommonError::InvalidParam6(_)), res);
+
+        let res = ledger_service.build_attrib_request(IDENTIFIER, DEST, None, None, Some("3yZe7d"));
+        assert_match!(Err(CommonError::InvalidParam6(_)), res);
+    }
+
+    #[test]
+    fn build_encrypted_attrib_request_works() {
+        let ledger_service = LedgerService::new();
+        let raw = r#"{"email":"alice@example.com"}"#;
+
+        let (request, enc_hash, key) = ledger_service.build_encrypted_attrib_request(IDENTIFIER, DEST, raw).unwrap();
+
+        let operation = _operation(&request);
+        assert_eq!(operation["type"], "100");
+        assert_eq!(operation["dest"], DEST);
+        assert!(operation.get("raw").is_none());
+        assert!(operation.get("hash").is_none());
+        let enc = operation["enc"].as_str().unwrap();
+        assert!(!enc.contains("alice"));
+
+        let response = format!(r#"{{"op":"REPLY","result":{{"identifier":"{}","reqId":5,"type":"104","dest":"{}","data":"{}"}}}}"#,
+                               IDENTIFIER, DEST, enc);
+        let (reply_enc, reply_enc_hash) = ledger_service.parse_get_attrib_enc(&response).unwrap();
+        assert_eq!(reply_enc, enc);
+        assert_eq!(reply_enc_hash, enc_hash);
+        assert_eq!(ledger_service.decrypt_attrib(&reply_enc, &key).unwrap(), raw);
+    }
+
+    #[test]
+    fn build_encrypted_attrib_request_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_encrypted_attrib_request("invalid", DEST, "{\"name\":\"Alex\"}");
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidParam3(_))), res);
+
+        let res = ledger_service.build_encrypted_attrib_request(IDENTIFIER, DEST, "[]");
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidParam5(_))), res);
+    }
+
+    #[test]
+    fn parse_get_attrib_enc_works_for_missing_attrib() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.parse_get_attrib_enc(GET_MISSING_ATTR_REPLY);
+        assert_match!(Err(LedgerError::NotFound(_)), res);
+    }
+
+    #[test]
+    fn parse_get_attrib_enc_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+
+        for data in &["\"not base58 0OIl\"", "\"3mJr7AoUXx2Wqd\"", r#"{"email":"alice@example.com"}"#] {
+            let response = format!(r#"{{"op":"REPLY","result":{{"identifier":"{}","reqId":5,"type":"104","dest":"{}","data":{}}}}}"#,
+                                   IDENTIFIER, DEST, data);
+            let res = ledger_service.parse_get_attrib_enc(&response);
+            assert_match!(Err(LedgerError::InvalidTransaction(_)), res);
+        }
+    }
+
+    #[test]
+    fn decrypt_attrib_works_for_other_key() {
+        let ledger_service = LedgerService::new();
+        let (request, _, _) = ledger_service.build_encrypted_attrib_request(IDENTIFIER, DEST, "{\"name\":\"Alex\"}").unwrap();
+        let (_, _, other_key) = ledger_service.build_encrypted_attrib_request(IDENTIFIER, DEST, "{\"name\":\"Alex\"}").unwrap();
+        let operation = _operation(&request);
+        let enc = operation["enc"].as_str().unwrap();
+
+        assert!(ledger_service.decrypt_attrib(enc, &other_key).is_err());
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidState(_))), ledger_service.decrypt_attrib(enc, "key"));
+    }
+
+    #[test]
+    fn build_get_txn_request_works() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_get_txn_request(IDENTIFIER, None, 5).unwrap();
+
+        let expected: Value = serde_json::from_str(r#"{"type":"3","data":5,"ledgerId":1}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+
+        let request = ledger_service.build_get_txn_request(IDENTIFIER, Some("DOMAIN"), 5).unwrap();
+        assert_eq!(_operation(&request)["ledgerId"], 1);
+
+        let request = ledger_service.build_get_txn_request(IDENTIFIER, Some("POOL"), 2).unwrap();
+        assert_eq!(_operation(&request)["ledgerId"], 0);
+    }
+
+    #[test]
+    fn build_get_txn_request_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_get_txn_request(IDENTIFIER, Some("CONFIG"), 5);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_get_txn_request(IDENTIFIER, None, 0);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+    }
+
+    #[test]
+    fn parse_get_txn_response_works() {
+        let ledger_service = LedgerService::new();
+        let response = r#"{"op":"REPLY","result":{"identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":6,"type":"3","seqNo":12,"data":{"data":{"primary":{"n":"1"}},"identifier":"Th7MpTaRZVRYnPiabds81Y","ref":10,"reqId":3,"seqNo":12,"signature_type":"CL","txnTime":1500000000,"type":"102"}}}"#;
+
+        let txn = ledger_service.parse_get_txn_response(response).unwrap();
+
+        let txn: Value = serde_json::from_str(&txn).unwrap();
+        let expected: Value = serde_json::from_str(r#"{"seqNo":12,"txnTime":1500000000,"txnType":"CLAIM_DEF","txn":{"data":{"primary":{"n":"1"}},"identifier":"Th7MpTaRZVRYnPiabds81Y","ref":10,"reqId":3,"signature_type":"CL","type":"102"}}"#).unwrap();
+        assert_eq!(txn, expected);
+    }
+
+    #[test]
+    fn parse_get_txn_response_works_for_pool_txn_as_string() {
+        let ledger_service = LedgerService::new();
+        let response = r#"{"op":"REPLY","result":{"identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":7,"type":"3","seqNo":1,"data":"{\"data\":{\"alias\":\"Node1\"},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"type\":\"0\"}"}}"#;
+
+        let txn = ledger_service.parse_get_txn_response(response).unwrap();
+
+        let txn: Value = serde_json::from_str(&txn).unwrap();
+        assert_eq!(txn["seqNo"], 1);
+        assert!(txn["txnTime"].is_null());
+        assert_eq!(txn["txnType"], "NODE");
+        assert_eq!(txn["txn"]["data"]["alias"], "Node1");
+    }
+
+    #[test]
+    fn parse_get_txn_response_works_for_missing_txn() {
+        let ledger_service = LedgerService::new();
+        let response = r#"{"op":"REPLY","result":{"identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":8,"type":"3","seqNo":100,"data":null}}"#;
+
+        let res = ledger_service.parse_get_txn_response(response);
+        assert_match!(Err(LedgerError::NotFound(_)), res);
+
+        let res = ledger_service.parse_get_txn_response(GET_MISSING_ATTR_REPLY);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn build_get_attrib_request_works() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_get_attrib_request(IDENTIFIER, DEST, None, Some("endpoint"), None).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"type":"104","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4","raw":"endpoint"}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+    }
+
+    #[test]
+    fn build_get_attrib_request_works_for_hash_and_enc() {
+        let ledger_service = LedgerService::new();
+        let hash = "83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3";
+
+        let request = ledger_service.build_get_attrib_request(IDENTIFIER, DEST, Some(hash), None, None).unwrap();
+        let operation = _operation(&request);
+        assert_eq!(operation["hash"], hash);
+        assert!(operation.get("raw").is_none());
+        assert!(operation.get("enc").is_none());
+
+        let (attrib_request, _, _) = ledger_service.build_encrypted_attrib_request(IDENTIFIER, DEST, r#"{"name":"Alex"}"#).unwrap();
+        let enc = _operation(&attrib_request)["enc"].as_str().unwrap().to_string();
+        let request = ledger_service.build_get_attrib_request(IDENTIFIER, DEST, None, None, Some(&enc)).unwrap();
+        let operation = _operation(&request);
+        assert_eq!(operation["type"], "104");
+        assert_eq!(operation["enc"], enc);
+        assert!(operation.get("raw").is_none());
+        assert!(operation.get("hash").is_none());
+    }
+
+    #[test]
+    fn build_get_attrib_request_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_get_attrib_request(IDENTIFIER, DEST, None, None, None);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_get_attrib_request(IDENTIFIER, DEST, Some("not_a_hash"), None, None);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_get_attrib_request(IDENTIFIER, DEST, None, Some(""), None);
+        assert_match!(Err(CommonError::InvalidParam5(_)), res);
+
+        let res = ledger_service.build_get_attrib_request(IDENTIFIER, DEST, None, None, Some("3mJr7AoUXx2Wqd"));
+        assert_match!(Err(CommonError::InvalidParam6(_)), res);
+
+        let res = ledger_service.build_get_attrib_request(IDENTIFIER, DEST, None, Some("endpoint"), Some("enc"));
+        assert_match!(Err(CommonError::InvalidParam6(_)), res);
+    }
+
+    #[test]
+    fn build_schema_request_works() {
+        let ledger_service = LedgerService::new();
+        let data = r#"{"name":"gvt","version":"1.0","attribute_names":["name","age"],"seq_no":1}"#;
+
+        let request = ledger_service.build_schema_request(IDENTIFIER, data).unwrap();
+
+        let operation = _operation(&request);
+        assert_eq!(operation["type"], "101");
+        let data: Value = serde_json::from_str(operation["data"].as_str().unwrap()).unwrap();
+        let expected: Value = serde_json::from_str(r#"{"name":"gvt","version":"1.0","attr_names":["name","age"]}"#).unwrap();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn build_schema_request_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_schema_request(IDENTIFIER, r#"{"name":"gvt","attr_names":["name"]}"#);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_schema_request(IDENTIFIER, r#"{"name":"gvt","version":"1.0","attr_names":[]}"#);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_schema_request(IDENTIFIER, r#"{"name":"gvt","version":"1.0","attr_names":["name","name"]}"#);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+    }
+
+    #[test]
+    fn build_get_schema_request_works() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_get_schema_request(IDENTIFIER, DEST, r#"{"name":"gvt","version":"1.0"}"#).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"type":"107","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4","data":{"name":"gvt","version":"1.0"}}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+
+        let res = ledger_service.build_get_schema_request(IDENTIFIER, DEST, r#"{"name":"gvt"}"#);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+    }
+
+    #[test]
+    fn build_claim_def_request_works() {
+        let ledger_service = LedgerService::new();
+        let claim_def = issuer::mocks::get_claim_definition();
+        let data = claim_def.to_json().unwrap();
+
+        let request = ledger_service.build_claim_def_request(IDENTIFIER, "1", &data).unwrap();
+
+        let operation = _operation(&request);
+        assert_eq!(operation["type"], "102");
+        assert_eq!(operation["ref"], 1);
+        assert_eq!(operation["signature_type"], "CL");
+        assert_eq!(operation["data"]["primary"], serde_json::to_value(&claim_def.public_key).unwrap());
+        assert!(operation["data"].get("revocation").is_none());
+    }
+
+    #[test]
+    fn build_claim_def_request_works_for_invalid_params() {
+        let ledger_service = LedgerService::new();
+        let data = issuer::mocks::get_claim_definition().to_json().unwrap();
+
+        let res = ledger_service.build_claim_def_request(IDENTIFIER, "schema", &data);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_claim_def_request(IDENTIFIER, "2", &data);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_claim_def_request(IDENTIFIER, "1", r#"{"n":"1"}"#);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+    }
+
+    #[test]
+    fn build_get_claim_def_request_works() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_get_claim_def_request(IDENTIFIER, "1", None, DEST).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"type":"108","ref":1,"signature_type":"CL","origin":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4"}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+
+        let res = ledger_service.build_get_claim_def_request(IDENTIFIER, "0", None, DEST);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+    }
+
+    #[test]
+    fn parse_get_schema_response_works() {
+        let ledger_service = LedgerService::new();
+        let response = r#"{"op":"REPLY","result":{"identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":1,"seqNo":2,"type":"107","dest":"Th7MpTaRZVRYnPiabds81Y","data":"{\"name\":\"gvt\",\"version\":\"1.0\",\"attr_names\":[\"name\",\"age\"]}"}}"#;
+
+        let schema = ledger_service.parse_get_schema_response(response).unwrap();
+
+        let schema = Schema::from_json(&schema).unwrap();
+        assert_eq!(schema.name, "gvt");
+        assert_eq!(schema.version, "1.0");
+        assert_eq!(schema.attribute_names, ["name".to_string(), "age".to_string()].iter().cloned().collect());
+        assert_eq!(schema.seq_no, 2);
+    }
+
+    #[test]
+    fn parse_get_schema_response_works_for_not_found() {
+        let ledger_service = LedgerService::new();
+        let response = r#"{"op":"REPLY","result":{"identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":1,"type":"107","dest":"Th7MpTaRZVRYnPiabds81Y","data":null}}"#;
+
+        let res = ledger_service.parse_get_schema_response(response);
+        assert_match!(Err(LedgerError::NotFound(_)), res);
+    }
+
+    #[test]
+    fn parse_get_schema_response_works_for_invalid_response() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.parse_get_schema_response(r#"{"op":"REQNACK","reqId":1,"reason":"client request invalid"}"#);
+        assert_match!(Err(LedgerError::InvalidTransaction(_)), res);
+
+        let res = ledger_service.parse_get_schema_response(r#"{"op":"REPLY","result":{"type":"105","data":"{}"}}"#);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidStructure(_))), res);
+
+        let res = ledger_service.parse_get_schema_response(r#"{"op":"REPLY","result":{"type":"107","seqNo":2,"data":{"name":"gvt"}}}"#);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn parse_get_claim_def_response_works() {
+        let ledger_service = LedgerService::new();
+        let claim_def = issuer::mocks::get_claim_definition();
+        let response = format!(
+            r#"{{"op":"REPLY","result":{{"identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":1,"seqNo":3,"type":"108","ref":1,"signature_type":"CL","origin":"Th7MpTaRZVRYnPiabds81Y","data":{{"primary":{}}}}}}}"#,
+            serde_json::to_string(&claim_def.public_key).unwrap());
+
+        let result = ledger_service.parse_get_claim_def_response(&response).unwrap();
+
+        assert_eq!(ClaimDefinition::from_json(&result).unwrap(), claim_def);
+    }
+
+    #[test]
+    fn parse_get_claim_def_response_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+        let response = r#"{"op":"REPLY","result":{"type":"108","ref":1,"signature_type":"CL","origin":"Th7MpTaRZVRYnPiabds81Y","data":{"primary":{"n":"1"}}}}"#;
+
+        let res = ledger_service.parse_get_claim_def_response(response);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn parse_get_nym_response_works() {
+        let ledger_service = LedgerService::new();
+        let response = r#"{"op":"REPLY","result":{"identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":1,"seqNo":2,"type":"105","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4","data":"{\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"role\":\"2\",\"verkey\":\"~7TYfekw4GUagBnBVCqPjiC\"}"}}"#;
+
+        let nym = ledger_service.parse_get_nym_response(response).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"did":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4","identifier":"Th7MpTaRZVRYnPiabds81Y","verkey":"~7TYfekw4GUagBnBVCqPjiC","role":"STEWARD"}"#).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&nym).unwrap(), expected);
+    }
+
+    #[test]
+    fn build_node_request_works() {
+        let ledger_service = LedgerService::new();
+        let data = r#"{"alias":"Node5","client_ip":"10.0.0.2","client_port":9710,"node_ip":"10.0.0.2","node_port":9709,"services":["VALIDATOR"],"blskey":"4N8aUNHSgjQVgkpm8nhNEfDf6txHznoYREg9kirmJrkivgL4oSEimFF6nsQ6M41QvhM2Z33nves5vfSn9n1UwNFJBYtWVnHYMATn76vLuL3zU88KyeAYcHfsih3He6UHcXDxcaecHVz6jhCYz1P2UZn2bDVruL5wXpehgBfBaLKm3Ba"}"#;
+
+        let request = ledger_service.build_node_request(IDENTIFIER, DEST, data).unwrap();
+
+        let operation = _operation(&request);
+        assert_eq!(operation["type"], "0");
+        assert_eq!(operation["dest"], DEST);
+        assert_eq!(operation["data"], serde_json::from_str::<Value>(data).unwrap());
+    }
+
+    #[test]
+    fn build_node_request_works_for_partial_update() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_node_request(IDENTIFIER, DEST, r#"{"alias":"Node5","services":[]}"#).unwrap();
+
+        let expected: Value = serde_json::from_str(r#"{"alias":"Node5","services":[]}"#).unwrap();
+        assert_eq!(_operation(&request)["data"], expected);
+    }
+
+    #[test]
+    fn build_node_request_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_node_request(IDENTIFIER, VERKEY, r#"{"alias":"Node5"}"#);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_node_request(IDENTIFIER, DEST, r#"{"alias":"Node5","client_ip":"10.0.0.256","client_port":9710}"#);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_node_request(IDENTIFIER, DEST, r#"{"alias":"Node5","node_ip":"10.0.0.2","node_port":70000}"#);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_node_request(IDENTIFIER, DEST, r#"{"alias":"Node5","client_ip":"10.0.0.2"}"#);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_node_request(IDENTIFIER, DEST, r#"{"alias":"Node5","services":["MINER"]}"#);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+
+        let res = ledger_service.build_node_request(IDENTIFIER, DEST, r#"{"alias":"Node5","port":9710}"#);
+        assert_match!(Err(CommonError::InvalidParam4(_)), res);
+    }
+
+    #[test]
+    fn build_pool_upgrade_request_works() {
+        let ledger_service = LedgerService::new();
+        let data = r#"{"name":"upgrade-13","version":"1.3","action":"start","sha256":"83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3","schedule":{"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4":"2017-12-25T10:25:58.271857+00:00"},"timeout":10}"#;
+
+        let request = ledger_service.build_pool_upgrade_request(IDENTIFIER, data).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"type":"109","name":"upgrade-13","version":"1.3","action":"start","sha256":"83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3","schedule":{"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4":"2017-12-25T10:25:58.271857+00:00"},"timeout":10,"reinstall":false,"force":false}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+    }
+
+    #[test]
+    fn build_pool_upgrade_request_works_for_cancel() {
+        let ledger_service = LedgerService::new();
+        let data = r#"{"name":"upgrade-13","version":"1.3","action":"cancel","sha256":"83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3","justification":"bug found"}"#;
+
+        let request = ledger_service.build_pool_upgrade_request(IDENTIFIER, data).unwrap();
+
+        let operation = _operation(&request);
+        assert_eq!(operation["action"], "cancel");
+        assert_eq!(operation["justification"], "bug found");
+        assert!(operation.get("schedule").is_none());
+    }
+
+    #[test]
+    fn build_pool_upgrade_request_works_for_invalid_data() {
+        let ledger_service = LedgerService::new();
+
+        let res = ledger_service.build_pool_upgrade_request(IDENTIFIER,
+            r#"{"name":"upgrade-13","version":"1.3","action":"start","sha256":"83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3"}"#);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_pool_upgrade_request(IDENTIFIER,
+            r#"{"name":"upgrade-13","version":"1.3","action":"restart","sha256":"83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3"}"#);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_pool_upgrade_request(IDENTIFIER,
+            r#"{"name":"upgrade-13","version":"1.3","action":"cancel","sha256":"abc"}"#);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+
+        let res = ledger_service.build_pool_upgrade_request(IDENTIFIER,
+            r#"{"name":"upgrade-13","version":"1.3","action":"start","sha256":"83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3","schedule":{"Node1":"2017-12-25T10:25:58+00:00"}}"#);
+        assert_match!(Err(CommonError::InvalidParam3(_)), res);
+    }
+
+    #[test]
+    fn build_get_ddo_request_works() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_get_ddo_request(IDENTIFIER, DEST).unwrap();
+
+        let expected: Value = serde_json::from_str(
+            r#"{"type":"120","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4"}"#).unwrap();
+        assert_eq!(_operation(&request), expected);
+    }
+
+    #[test]
+    fn build_did_document_works() {
+        let ledger_service = LedgerService::new();
+        let attribs = vec![GET_ENDPOINT_REPLY.to_string(), GET_SERVICE_REPLY.to_string(), GET_MISSING_ATTR_REPLY.to_string()];
+
+        let did_doc = ledger_service.build_did_document(GET_NYM_REPLY, &attribs).unwrap();
+
+        let expected: Value = serde_json::from_str(r#"{
+            "id":"did:sov:Th7MpTaRZVRYnPiabds81Y",
+            "did":"Th7MpTaRZVRYnPiabds81Y",
+            "verkey":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4",
+            "role":"TRUST_ANCHOR",
+            "endpoint":{"ha":"127.0.0.1:5555"},
+            "service":[{"type":"agent","serviceEndpoint":"127.0.0.1:5555"},{"type":"hub","serviceEndpoint":"https://hub.example.com"}],
+            "attributes":{"endpoint":{"ha":"127.0.0.1:5555"},"service":[{"type":"hub","serviceEndpoint":"https://hub.example.com"}]}
+        }"#).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&did_doc).unwrap(), expected);
+    }
+
+    #[test]
+    fn build_did_document_works_without_attribs() {
+        let ledger_service = LedgerService::new();
+
+        let did_doc = ledger_service.build_did_document(GET_NYM_REPLY, &[]).unwrap();
+
+        let did_doc: Value = serde_json::from_str(&did_doc).unwrap();
+        assert_eq!(did_doc["endpoint"], Value::Null);
+        assert_eq!(did_doc["service"], Value::Array(Vec::new()));
+    }
+
+    #[test]
+    fn build_did_document_works_for_attrib_of_other_did() {
+        let ledger_service = LedgerService::new();
+        let attribs = vec![GET_ENDPOINT_REPLY.replace("Th7MpTaRZVRYnPiabds81Y", "V4SGRU86Z58d6TV7PBUe6f")];
+
+        let res = ledger_service.build_did_document(GET_NYM_REPLY, &attribs);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn parse_get_nym_verkey_works() {
+        let ledger_service = LedgerService::new();
+
+        let verkey = ledger_service.parse_get_nym_verkey(IDENTIFIER, GET_NYM_REPLY).unwrap();
+        assert_eq!(verkey, Some(DEST.to_string()));
+
+        let res = ledger_service.parse_get_nym_verkey("V4SGRU86Z58d6TV7PBUe6f", GET_NYM_REPLY);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidStructure(_))), res);
+    }
+
+    #[test]
+    fn parse_get_nym_data_works() {
+        let ledger_service = LedgerService::new();
+
+        let nym = ledger_service.parse_get_nym_data(IDENTIFIER, GET_NYM_REPLY).unwrap().unwrap();
+        assert_eq!(nym.role, Some("TRUST_ANCHOR".to_string()));
+
+        let response = r#"{"op":"REPLY","result":{"identifier":"V4SGRU86Z58d6TV7PBUe6f","reqId":1,"type":"105","dest":"Th7MpTaRZVRYnPiabds81Y","data":null}}"#;
+        assert_eq!(ledger_service.parse_get_nym_data(IDENTIFIER, response).unwrap(), None);
+    }
+
+    #[test]
+    fn auth_check_dids_works() {
+        let ledger_service = LedgerService::new();
+
+        let request = ledger_service.build_nym_request(IDENTIFIER, DEST, None, None, None, None).unwrap();
+        assert_eq!(ledger_service.auth_check_dids(&request).unwrap(), vec![IDENTIFIER.to_string(), DEST.to_string()]);
+
+        let request = ledger_service.build_attrib_request(IDENTIFIER, IDENTIFIER, None, Some(r#"{"endpoint":{"ha":"127.0.0.1:5555"}}"#), None).unwrap();
+        assert_eq!(ledger_service.auth_check_dids(&request).unwrap(), vec![IDENTIFIER.to_string()]);
+
+        let request = ledger_service.build_get_nym_request(IDENTIFIER, DEST).unwrap();
+        assert!(ledger_service.auth_check_dids(&request).unwrap().is_empty());
+    }
+
+    fn _nyms(submitter_role: Option<&str>, dest: Option<NymData>) -> HashMap<String, Option<NymData>> {
+        let mut nyms: HashMap<String, Option<NymData>> = HashMap::new();
+        nyms.insert(IDENTIFIER.to_string(),
+                    Some(NymData::new(IDENTIFIER.to_string(), None, Some(VERKEY.to_string()), submitter_role.map(String::from))));
+        nyms.insert(DEST.to_string(), dest);
+        nyms
+    }
+
+    #[test]
+    fn check_auth_rules_works_for_nym_role() {
+        let ledger_service = LedgerService::new();
+        let request = ledger_service.build_nym_request(IDENTIFIER, DEST, None, None, None, Some("TRUST_ANCHOR")).unwrap();
+
+        assert!(ledger_service.check_auth_rules(&request, &_nyms(Some("STEWARD"), None)).is_ok());
+
+        let res = ledger_service.check_auth_rules(&request, &_nyms(Some("TRUST_ANCHOR"), None));
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+
+        let request = ledger_service.build_nym_request(IDENTIFIER, DEST, None, None, None, Some("TRUSTEE")).unwrap();
+
+        let res = ledger_service.check_auth_rules(&request, &_nyms(Some("STEWARD"), None));
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+
+        assert!(ledger_service.check_auth_rules(&request, &_nyms(Some("TRUSTEE"), None)).is_ok());
+    }
+
+    #[test]
+    fn check_auth_rules_works_for_nym_role_removal() {
+        let ledger_service = LedgerService::new();
+        let request = format!(r#"{{"reqId":1,"identifier":"{}","operation":{{"type":"1","dest":"{}","role":null}},"protocolVersion":1}}"#, IDENTIFIER, DEST);
+        let dest = NymData::new(DEST.to_string(), Some(IDENTIFIER.to_string()), None, Some("TRUST_ANCHOR".to_string()));
+
+        let res = ledger_service.check_auth_rules(&request, &_nyms(Some("STEWARD"), Some(dest.clone())));
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+
+        assert!(ledger_service.check_auth_rules(&request, &_nyms(Some("TRUSTEE"), Some(dest))).is_ok());
+    }
+
+    #[test]
+    fn check_auth_rules_works_for_nym_verkey() {
+        let ledger_service = LedgerService::new();
+        let request = ledger_service.build_nym_request(IDENTIFIER, DEST, Some(VERKEY), None, None, None).unwrap();
+
+        let guarded = NymData::new(DEST.to_string(), Some(IDENTIFIER.to_string()), None, None);
+        assert!(ledger_service.check_auth_rules(&request, &_nyms(None, Some(guarded))).is_ok());
+
+        let owned = NymData::new(DEST.to_string(), Some(IDENTIFIER.to_string()), Some(DEST.to_string()), None);
+        let res = ledger_service.check_auth_rules(&request, &_nyms(Some("TRUSTEE"), Some(owned)));
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+    }
+
+    #[test]
+    fn check_auth_rules_works_for_attrib() {
+        let ledger_service = LedgerService::new();
+        let request = ledger_service.build_attrib_request(IDENTIFIER, DEST, None, Some(r#"{"endpoint":{"ha":"127.0.0.1:5555"}}"#), None).unwrap();
+
+        let res = ledger_service.check_auth_rules(&request, &_nyms(Some("TRUSTEE"), None));
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+
+        let owned = NymData::new(DEST.to_string(), Some(IDENTIFIER.to_string()), Some(DEST.to_string()), None);
+        let res = ledger_service.check_auth_rules(&request, &_nyms(Some("TRUSTEE"), Some(owned)));
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+
+        let guarded = NymData::new(DEST.to_string(), Some(IDENTIFIER.to_string()), None, None);
+        assert!(ledger_service.check_auth_rules(&request, &_nyms(None, Some(guarded))).is_ok());
+    }
+
+    #[test]
+    fn check_auth_rules_works_for_schema_and_node() {
+        let ledger_service = LedgerService::new();
+        let schema_request = ledger_service.build_schema_request(IDENTIFIER, r#"{"name":"gvt","version":"1.0","attr_names":["name"]}"#).unwrap();
+        let node_request = format!(r#"{{"reqId":1,"identifier":"{}","operation":{{"type":"0","dest":"{}","data":{{"alias":"Node5"}}}},"protocolVersion":1}}"#, IDENTIFIER, DEST);
+
+        let res = ledger_service.check_auth_rules(&schema_request, &_nyms(None, None));
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+        assert!(ledger_service.check_auth_rules(&schema_request, &_nyms(Some("TRUST_ANCHOR"), None)).is_ok());
+
+        let res = ledger_service.check_auth_rules(&node_request, &_nyms(Some("TRUSTEE"), None));
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+        assert!(ledger_service.check_auth_rules(&node_request, &_nyms(Some("STEWARD"), None)).is_ok());
+    }
+
+    #[test]
+    fn check_auth_rules_works_for_submitter_not_on_ledger() {
+        let ledger_service = LedgerService::new();
+        let request = ledger_service.build_schema_request(IDENTIFIER, r#"{"name":"gvt","version":"1.0","attr_names":["name"]}"#).unwrap();
+        let mut nyms: HashMap<String, Option<NymData>> = HashMap::new();
+        nyms.insert(IDENTIFIER.to_string(), None);
+
+        let res = ledger_service.check_auth_rules(&request, &nyms);
+        assert_match!(Err(LedgerError::SecurityError(_)), res);
+    }
+
+    fn _ledger_txns(count: usize) -> (Vec<String>, MerkleTree) {
+        let txns = (1..count + 1)
+            .map(|i| format!(r#"{{"dest":"{}","identifier":"{}","reqId":{},"type":"1"}}"#, DEST, IDENTIFIER, i))
+            .collect::<Vec<String>>();
+        let tree = MerkleTree::from_vec(txns.clone()).unwrap();
+        (txns, tree)
+    }
+
+    fn _reply_txn(txn: &str, seq_no: usize, tree: &MerkleTree) -> String {
+        let mut txn: Value = serde_json::from_str(txn).unwrap();
+        let audit_path = tree.audit_path(seq_no - 1).unwrap().iter().map(|hash| Base58::encode(hash)).collect::<Vec<_>>();
+        txn["seqNo"] = Value::from(seq_no as u64);
+        txn["auditPath"] = Value::from(audit_path);
+        txn["rootHash"] = Value::from(Base58::encode(tree.root_hash()));
+        txn.to_string()
+    }
+
+    #[test]
+    fn verify_txn_inclusion_works() {
+        let ledger_service = LedgerService::new();
+        let (txns, tree) = _ledger_txns(5);
+        let root_hash = Base58::encode(tree.root_hash());
+
+        for (i, txn) in txns.iter().enumerate() {
+            let reply_txn = _reply_txn(txn, i + 1, &tree);
+            let audit_path = serde_json::to_string(&tree.audit_path(i).unwrap().iter().map(|hash| Base58::encode(hash)).collect::<Vec<_>>()).unwrap();
+
+            assert!(ledger_service.verify_txn_inclusion(&reply_txn, &audit_path, &root_hash, 5).unwrap());
+        }
+    }
+
+    #[test]
+    fn verify_txn_inclusion_works_for_other_ledger() {
+        let ledger_service = LedgerService::new();
+        let (txns, tree) = _ledger_txns(5);
+        let (_, other_tree) = _ledger_txns(6);
+        let reply_txn = _reply_txn(&txns[2], 3, &tree);
+        let audit_path = serde_json::to_string(&tree.audit_path(2).unwrap().iter().map(|hash| Base58::encode(hash)).collect::<Vec<_>>()).unwrap();
+
+        assert!(!ledger_service.verify_txn_inclusion(&reply_txn, &audit_path, &Base58::encode(other_tree.root_hash()), 5).unwrap());
+        assert!(!ledger_service.verify_txn_inclusion(&reply_txn, &audit_path, &Base58::encode(tree.root_hash()), 6).unwrap());
+
+        let tampered_txn = reply_txn.replace(r#""type":"1""#, r#""type":"101""#);
+        assert!(!ledger_service.verify_txn_inclusion(&tampered_txn, &audit_path, &Base58::encode(tree.root_hash()), 5).unwrap());
+    }
+
+    #[test]
+    fn verify_txn_inclusion_works_for_invalid_params() {
+        let ledger_service = LedgerService::new();
+        let (txns, tree) = _ledger_txns(2);
+        let root_hash = Base58::encode(tree.root_hash());
+        let audit_path = format!(r#"["{}"]"#, Base58::encode(&tree.audit_path(0).unwrap()[0]));
+        let reply_txn = _reply_txn(&txns[0], 1, &tree);
+
+        let res = ledger_service.verify_txn_inclusion(&txns[0], &audit_path, &root_hash, 2);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidParam2(_))), res);
+
+        let res = ledger_service.verify_txn_inclusion(&reply_txn, r#"["abc"]"#, &root_hash, 2);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidParam3(_))), res);
+
+        let res = ledger_service.verify_txn_inclusion(&reply_txn, &audit_path, "0OIl", 2);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidParam4(_))), res);
+
+        let res = ledger_service.verify_txn_inclusion(&reply_txn, &audit_path, &root_hash, 0);
+        assert_match!(Err(LedgerError::CommonError(CommonError::InvalidParam5(_))), res);
+    }
+}
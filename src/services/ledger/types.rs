@@ -0,0 +1,500 @@
+extern crate serde;
+extern crate serde_json;
+
+use std::collections::BTreeMap;
+
+use self::serde::Serialize;
+use self::serde_json::Value;
+use services::anoncreds::types::{PublicKey, RevocationPublicKey};
+use utils::json::{JsonDecodable, JsonEncodable};
+
+use super::constants::{ATTRIB, CLAIM_DEF, DID_METHOD_PREFIX, GET_ATTR, GET_CLAIM_DEF, GET_DDO, GET_NYM, GET_SCHEMA, GET_TXN, NODE, NYM, POOL_UPGRADE, PROTOCOL_VERSION, SCHEMA};
+
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Request<T: Serialize> {
+    pub req_id: u64,
+    pub identifier: String,
+    pub operation: T,
+    pub protocol_version: u64,
+}
+
+impl<T: Serialize> Request<T> {
+    pub fn new(req_id: u64, identifier: String, operation: T) -> Request<T> {
+        Request {
+            req_id: req_id,
+            identifier: identifier,
+            operation: operation,
+            protocol_version: PROTOCOL_VERSION,
+        }
+    }
+}
+
+impl<T: Serialize> JsonEncodable for Request<T> {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct NymOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verkey: Option<String>,
+    #[serde(rename = "ref")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+}
+
+impl NymOperation {
+    pub fn new(dest: String, verkey: Option<String>, xref: Option<String>,
+               alias: Option<String>, role: Option<String>) -> NymOperation {
+        NymOperation {
+            _type: NYM.to_string(),
+            dest: dest,
+            verkey: verkey,
+            xref: xref,
+            alias: alias,
+            role: role,
+        }
+    }
+}
+
+impl JsonEncodable for NymOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetNymOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: String,
+}
+
+impl GetNymOperation {
+    pub fn new(dest: String) -> GetNymOperation {
+        GetNymOperation {
+            _type: GET_NYM.to_string(),
+            dest: dest,
+        }
+    }
+}
+
+impl JsonEncodable for GetNymOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct AttribOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enc: Option<String>,
+}
+
+impl AttribOperation {
+    pub fn new(dest: String, hash: Option<String>, raw: Option<String>, enc: Option<String>) -> AttribOperation {
+        AttribOperation {
+            _type: ATTRIB.to_string(),
+            dest: dest,
+            hash: hash,
+            raw: raw,
+            enc: enc,
+        }
+    }
+}
+
+impl JsonEncodable for AttribOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetAttribOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enc: Option<String>,
+}
+
+impl GetAttribOperation {
+    pub fn new(dest: String, hash: Option<String>, raw: Option<String>, enc: Option<String>) -> GetAttribOperation {
+        GetAttribOperation {
+            _type: GET_ATTR.to_string(),
+            dest: dest,
+            hash: hash,
+            raw: raw,
+            enc: enc,
+        }
+    }
+}
+
+impl JsonEncodable for GetAttribOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct SchemaOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub data: String,
+}
+
+impl SchemaOperation {
+    pub fn new(data: String) -> SchemaOperation {
+        SchemaOperation {
+            _type: SCHEMA.to_string(),
+            data: data,
+        }
+    }
+}
+
+impl JsonEncodable for SchemaOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct SchemaOperationData {
+    pub name: String,
+    pub version: String,
+    pub attr_names: Vec<String>,
+}
+
+impl SchemaOperationData {
+    pub fn new(name: String, version: String, attr_names: Vec<String>) -> SchemaOperationData {
+        SchemaOperationData {
+            name: name,
+            version: version,
+            attr_names: attr_names,
+        }
+    }
+}
+
+impl JsonEncodable for SchemaOperationData {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetSchemaOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: String,
+    pub data: GetSchemaOperationData,
+}
+
+impl GetSchemaOperation {
+    pub fn new(dest: String, data: GetSchemaOperationData) -> GetSchemaOperation {
+        GetSchemaOperation {
+            _type: GET_SCHEMA.to_string(),
+            dest: dest,
+            data: data,
+        }
+    }
+}
+
+impl JsonEncodable for GetSchemaOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetSchemaOperationData {
+    pub name: String,
+    pub version: String,
+}
+
+impl GetSchemaOperationData {
+    pub fn new(name: String, version: String) -> GetSchemaOperationData {
+        GetSchemaOperationData {
+            name: name,
+            version: version,
+        }
+    }
+}
+
+impl JsonEncodable for GetSchemaOperationData {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct ClaimDefOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(rename = "ref")]
+    pub xref: i32,
+    pub signature_type: String,
+    pub data: ClaimDefOperationData,
+}
+
+impl ClaimDefOperation {
+    pub fn new(xref: i32, signature_type: String, data: ClaimDefOperationData) -> ClaimDefOperation {
+        ClaimDefOperation {
+            _type: CLAIM_DEF.to_string(),
+            xref: xref,
+            signature_type: signature_type,
+            data: data,
+        }
+    }
+}
+
+impl JsonEncodable for ClaimDefOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct ClaimDefOperationData {
+    pub primary: PublicKey,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation: Option<RevocationPublicKey>,
+}
+
+impl ClaimDefOperationData {
+    pub fn new(primary: PublicKey, revocation: Option<RevocationPublicKey>) -> ClaimDefOperationData {
+        ClaimDefOperationData {
+            primary: primary,
+            revocation: revocation,
+        }
+    }
+}
+
+impl JsonEncodable for ClaimDefOperationData {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetClaimDefOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    #[serde(rename = "ref")]
+    pub xref: i32,
+    pub signature_type: String,
+    pub origin: String,
+}
+
+impl GetClaimDefOperation {
+    pub fn new(xref: i32, signature_type: String, origin: String) -> GetClaimDefOperation {
+        GetClaimDefOperation {
+            _type: GET_CLAIM_DEF.to_string(),
+            xref: xref,
+            signature_type: signature_type,
+            origin: origin,
+        }
+    }
+}
+
+impl JsonEncodable for GetClaimDefOperation {}
+
+#[derive(Serialize, PartialEq, Debug, Clone)]
+pub struct NymData {
+    pub did: String,
+    pub identifier: Option<String>,
+    pub verkey: Option<String>,
+    pub role: Option<String>,
+}
+
+impl NymData {
+    pub fn new(did: String, identifier: Option<String>, verkey: Option<String>, role: Option<String>) -> NymData {
+        NymData {
+            did: did,
+            identifier: identifier,
+            verkey: verkey,
+            role: role,
+        }
+    }
+}
+
+impl JsonEncodable for NymData {}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct NodeOperationData {
+    pub alias: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_port: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_port: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blskey: Option<String>,
+}
+
+impl JsonEncodable for NodeOperationData {}
+
+impl<'a> JsonDecodable<'a> for NodeOperationData {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct NodeOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: String,
+    pub data: NodeOperationData,
+}
+
+impl NodeOperation {
+    pub fn new(dest: String, data: NodeOperationData) -> NodeOperation {
+        NodeOperation {
+            _type: NODE.to_string(),
+            dest: dest,
+            data: data,
+        }
+    }
+}
+
+impl JsonEncodable for NodeOperation {}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PoolUpgradeData {
+    pub name: String,
+    pub version: String,
+    pub action: String,
+    pub sha256: String,
+    pub timeout: Option<u32>,
+    pub schedule: Option<BTreeMap<String, String>>,
+    pub justification: Option<String>,
+    pub reinstall: Option<bool>,
+    pub force: Option<bool>,
+}
+
+impl<'a> JsonDecodable<'a> for PoolUpgradeData {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct PoolUpgradeOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub name: String,
+    pub version: String,
+    pub action: String,
+    pub sha256: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub justification: Option<String>,
+    pub reinstall: bool,
+    pub force: bool,
+}
+
+impl PoolUpgradeOperation {
+    pub fn new(data: PoolUpgradeData) -> PoolUpgradeOperation {
+        PoolUpgradeOperation {
+            _type: POOL_UPGRADE.to_string(),
+            name: data.name,
+            version: data.version,
+            action: data.action,
+            sha256: data.sha256,
+            timeout: data.timeout,
+            schedule: data.schedule,
+            justification: data.justification,
+            reinstall: data.reinstall.unwrap_or(false),
+            force: data.force.unwrap_or(false),
+        }
+    }
+}
+
+impl JsonEncodable for PoolUpgradeOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetDdoOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: String,
+}
+
+impl GetDdoOperation {
+    pub fn new(dest: String) -> GetDdoOperation {
+        GetDdoOperation {
+            _type: GET_DDO.to_string(),
+            dest: dest,
+        }
+    }
+}
+
+impl JsonEncodable for GetDdoOperation {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct GetTxnOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub data: i32,
+    #[serde(rename = "ledgerId")]
+    pub ledger_id: i32,
+}
+
+impl GetTxnOperation {
+    pub fn new(seq_no: i32, ledger_id: i32) -> GetTxnOperation {
+        GetTxnOperation {
+            _type: GET_TXN.to_string(),
+            data: seq_no,
+            ledger_id: ledger_id,
+        }
+    }
+}
+
+impl JsonEncodable for GetTxnOperation {}
+
+/// Transaction got by GET_TXN with its position on the ledger and the time it was ordered
+#[derive(Serialize, PartialEq, Debug)]
+pub struct LedgerTxn {
+    #[serde(rename = "seqNo")]
+    pub seq_no: u64,
+    #[serde(rename = "txnTime")]
+    pub txn_time: Option<u64>,
+    #[serde(rename = "txnType")]
+    pub txn_type: String,
+    pub txn: Value,
+}
+
+impl LedgerTxn {
+    pub fn new(seq_no: u64, txn_time: Option<u64>, txn_type: String, txn: Value) -> LedgerTxn {
+        LedgerTxn {
+            seq_no: seq_no,
+            txn_time: txn_time,
+            txn_type: txn_type,
+            txn: txn,
+        }
+    }
+}
+
+impl JsonEncodable for LedgerTxn {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct DidDocument {
+    pub id: String,
+    pub did: String,
+    pub verkey: Option<String>,
+    pub role: Option<String>,
+    pub endpoint: Option<Value>,
+    pub service: Vec<DidDocumentService>,
+    pub attributes: BTreeMap<String, Value>,
+}
+
+impl DidDocument {
+    pub fn new(did: String, verkey: Option<String>, role: Option<String>, endpoint: Option<Value>,
+               service: Vec<DidDocumentService>, attributes: BTreeMap<String, Value>) -> DidDocument {
+        DidDocument {
+            id: format!("{}{}", DID_METHOD_PREFIX, did),
+            did: did,
+            verkey: verkey,
+            role: role,
+            endpoint: endpoint,
+            service: service,
+            attributes: attributes,
+        }
+    }
+}
+
+impl JsonEncodable for DidDocument {}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct DidDocumentService {
+    #[serde(rename = "type")]
+    pub service_type: String,
+    #[serde(rename = "serviceEndpoint")]
+    pub service_endpoint: String,
+}
+
+impl DidDocumentService {
+    pub fn new(service_type: String, service_endpoint: String) -> DidDocumentService {
+        DidDocumentService {
+            service_type: service_type,
+            service_endpoint: service_endpoint,
+        }
+    }
+}
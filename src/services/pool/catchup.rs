@@ -1,62 +1,70 @@
+extern crate serde_json;
+
 use std::cmp;
-use std::collections::{BinaryHeap};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
 use errors::pool::PoolError;
 use super::{
     MerkleTree,
     RemoteNode,
 };
-use super::rust_base58::ToBase58;
+use super::transport::Transport;
+use super::rust_base58::{FromBase58, ToBase58};
 use super::types::*;
-use utils::json::JsonEncodable;
 
+/// Time to wait for CATCHUP_REP before the slice is re-requested from another node
+pub const CATCHUP_REP_TIMEOUT_MS: u64 = 3000;
+
+/// Catches up the ledger with `ledger_id` from the nodes
 pub struct CatchupHandler {
     pub f: usize,
-    pub ledger_status_same: usize,
+    pub ledger_id: usize,
+    pub ledger_status_same: HashSet<usize /* node idx */>,
     pub merkle_tree: MerkleTree,
     pub new_mt_size: usize,
-    pub new_mt_vote: usize,
+    pub new_mt_root: Vec<u8>,
+    pub new_mt_votes: HashMap<(usize /* size */, String /* root */), HashSet<usize /* node idx */>>,
     pub nodes: Vec<RemoteNode>,
     pub pending_catchup: Option<CatchUpProcess>,
 }
 
 impl CatchupHandler {
-    pub fn process_msg(&mut self, msg: Message, raw_msg: &String, src_ind: usize) -> Result<Option<MerkleTree>, PoolError> {
+    pub fn process_msg(&mut self, transport: &Transport, msg: Message, raw_msg: &String, src_ind: usize) -> Result<Option<LedgerUpdate>, PoolError> {
         match msg {
             Message::Pong => {
                 //sending ledger status
                 //TODO not send ledger status directly as response on ping, wait pongs from all nodes?
-                let ls: LedgerStatus = LedgerStatus {
-                    txnSeqNo: self.nodes.len(),
-                    merkleRoot: self.merkle_tree.root_hash().as_slice().to_base58(),
-                    ledgerId: 0,
-                };
-                let resp_msg: Message = Message::LedgerStatus(ls);
-                self.nodes[src_ind].send_msg(&resp_msg);
+                self.nodes[src_ind].send_msg(transport, &self._ledger_status()).ok();
+            }
+            Message::LedgerStatus(ref ledger_status) if ledger_status.ledgerId != self.ledger_id => {
+                debug!("Node {} sent status of other ledger {}", self.nodes[src_ind].name, ledger_status.ledgerId);
+            }
+            Message::ConsistencyProof(ref cons_proof) if cons_proof.ledgerId != self.ledger_id => {
+                debug!("Node {} sent consistency proof of other ledger {}", self.nodes[src_ind].name, cons_proof.ledgerId);
+            }
+            Message::CatchupRep(ref catchup) if catchup.ledgerId != self.ledger_id => {
+                debug!("Node {} sent catchup reply of other ledger {}", self.nodes[src_ind].name, catchup.ledgerId);
             }
             Message::LedgerStatus(ledger_status) => {
-                assert_eq!(ledger_status.merkleRoot, self.merkle_tree.root_hash().as_slice().to_base58());
-                self.ledger_status_same += 1;
-                if self.ledger_status_same == self.f + 1 {
-                    return Ok(Some(self.merkle_tree.clone()));
+                if ledger_status.merkleRoot != self.merkle_tree.root_hash().as_slice().to_base58() {
+                    warn!("Node {} sent ledger status with unexpected root {}", self.nodes[src_ind].name, ledger_status.merkleRoot);
+                    return Ok(None);
+                }
+                self.ledger_status_same.insert(src_ind);
+                if self.ledger_status_same.len() == self.f + 1 {
+                    return Ok(Some(LedgerUpdate {
+                        merkle_tree: self.merkle_tree.clone(),
+                        txns: Vec::new(),
+                    }));
                 }
             }
             Message::ConsistencyProof(cons_proof) => {
                 trace!("{:?}", cons_proof);
-                if cons_proof.seqNoStart == self.merkle_tree.count()
-                    && cons_proof.seqNoEnd > self.merkle_tree.count() {
-                    self.new_mt_size = cmp::max(cons_proof.seqNoEnd, self.new_mt_size);
-                    self.new_mt_vote += 1;
-                    debug!("merkle tree expected size now {}", self.new_mt_size);
-                }
-                if self.new_mt_vote == self.f + 1 {
-                    self.start_catchup();
-                }
+                self.process_consistency_proof(transport, cons_proof, src_ind)?;
             }
             Message::CatchupRep(catchup) => {
-                if let Some(new_mt) = self.process_catchup_rep(catchup)? {
-                    return Ok(Some(new_mt));
-                }
+                return self.process_catchup_rep(transport, catchup, src_ind);
             }
             _ => {
                 warn!("unhandled msg {:?}", msg);
@@ -65,57 +73,157 @@ impl CatchupHandler {
         Ok(None)
     }
 
-    pub fn start_catchup(&mut self) {
+    /// Asks all nodes for the status of the ledger. Nodes with the same ledger reply
+    /// with LEDGER_STATUS, nodes with bigger ledger reply with CONSISTENCY_PROOF.
+    pub fn request_ledger_status(&self, transport: &Transport) {
+        let msg = self._ledger_status();
+        for node in &self.nodes {
+            node.send_msg(transport, &msg).ok();
+        }
+    }
+
+    fn _ledger_status(&self) -> Message {
+        Message::LedgerStatus(LedgerStatus {
+            txnSeqNo: self.merkle_tree.count(),
+            merkleRoot: self.merkle_tree.root_hash().as_slice().to_base58(),
+            ledgerId: self.ledger_id,
+        })
+    }
+
+    /// Counts votes of nodes for the new ledger size and root. Catchup starts
+    /// once f + 1 nodes sent valid proofs for the same ledger.
+    fn process_consistency_proof(&mut self, transport: &Transport, cons_proof: ConsistencyProof, src_ind: usize) -> Result<(), PoolError> {
+        if self.pending_catchup.is_some() {
+            return Ok(());
+        }
+        let mt_size = self.merkle_tree.count();
+        if cons_proof.seqNoStart != mt_size || cons_proof.seqNoEnd <= mt_size {
+            debug!("Node {} sent consistency proof {}..{} not applicable to ledger of size {}",
+                   self.nodes[src_ind].name, cons_proof.seqNoStart, cons_proof.seqNoEnd, mt_size);
+            return Ok(());
+        }
+        let new_root = match CatchupHandler::_check_consistency_proof(&self.merkle_tree, &cons_proof) {
+            Ok(new_root) => new_root,
+            Err(err) => {
+                warn!("Node {} sent invalid consistency proof: {}", self.nodes[src_ind].name, err);
+                return Ok(());
+            }
+        };
+        let votes_cnt = {
+            let votes = self.new_mt_votes.entry((cons_proof.seqNoEnd, cons_proof.newMerkleRoot))
+                .or_insert(HashSet::new());
+            votes.insert(src_ind);
+            votes.len()
+        };
+        if votes_cnt == self.f + 1 {
+            self.new_mt_size = cons_proof.seqNoEnd;
+            self.new_mt_root = new_root;
+            debug!("merkle tree expected size now {}", self.new_mt_size);
+            self.start_catchup(transport)?;
+        }
+        Ok(())
+    }
+
+    /// Splits missing txns into slices, one slice per node.
+    pub fn start_catchup(&mut self, transport: &Transport) -> Result<(), PoolError> {
         trace!("start_catchup");
-        assert!(self.pending_catchup.is_none());
-        self.pending_catchup = Some(CatchUpProcess {
-            merkle_tree: self.merkle_tree.clone(),
-            pending_reps: BinaryHeap::new(),
-        });
+        if self.pending_catchup.is_some() {
+            return Err(PoolError::InvalidState("Catchup is already in progress".to_string()));
+        }
+        if self.nodes.is_empty() {
+            return Err(PoolError::InvalidState("No nodes to catchup from".to_string()));
+        }
+        let mt_size = self.merkle_tree.count();
+        if self.new_mt_size <= mt_size {
+            return Err(PoolError::InvalidState(format!("Nothing to catchup: local size {}, target size {}",
+                                                       mt_size, self.new_mt_size)));
+        }
         let node_cnt = self.nodes.len();
-        assert!(self.merkle_tree.count() == node_cnt);
 
-        let cnt_to_catchup = self.new_mt_size - self.merkle_tree.count();
-        assert!(cnt_to_catchup > 0);
+        let cnt_to_catchup = self.new_mt_size - mt_size;
         let portion = (cnt_to_catchup + node_cnt - 1) / node_cnt; //TODO check standard round up div
-        let mut catchup_req = CatchupReq {
-            ledgerId: 0,
-            seqNoStart: node_cnt + 1,
-            seqNoEnd: node_cnt + 1 + portion - 1,
-            catchupTill: self.new_mt_size,
-        };
-        for node in &self.nodes {
-            node.send_msg(&Message::CatchupReq(catchup_req.clone()));
-            catchup_req.seqNoStart += portion;
-            catchup_req.seqNoEnd = cmp::min(catchup_req.seqNoStart + portion - 1,
-                                            catchup_req.catchupTill);
+        let mut slices: Vec<CatchupSlice> = Vec::new();
+        let mut seq_no_start = mt_size + 1;
+        for (node_idx, node) in self.nodes.iter().enumerate() {
+            if seq_no_start > self.new_mt_size {
+                break;
+            }
+            let catchup_req = CatchupReq {
+                ledgerId: self.ledger_id,
+                seqNoStart: seq_no_start,
+                seqNoEnd: cmp::min(seq_no_start + portion - 1, self.new_mt_size),
+                catchupTill: self.new_mt_size,
+            };
+            seq_no_start = catchup_req.seqNoEnd + 1;
+            node.send_msg(transport, &Message::CatchupReq(catchup_req.clone())).ok();
+            slices.push(CatchupSlice {
+                req: catchup_req,
+                node_idx: node_idx,
+                sent_at: Instant::now(),
+                failed_nodes: Vec::new(),
+                rep: None,
+            });
         }
+        self.pending_catchup = Some(CatchUpProcess {
+            merkle_tree: self.merkle_tree.clone(),
+            txns: Vec::new(),
+            slices: slices,
+        });
+        Ok(())
     }
 
-    pub fn process_catchup_rep(&mut self, catchup: CatchupRep) -> Result<Option<MerkleTree>, PoolError> {
+    /// Accepts reply for the slice requested from the node and appends all
+    /// consecutive received slices that are consistent with the agreed ledger.
+    pub fn process_catchup_rep(&mut self, transport: &Transport, catchup: CatchupRep, src_ind: usize) -> Result<Option<LedgerUpdate>, PoolError> {
         trace!("append {:?}", catchup);
         let catchup_finished = {
-            let mut process = self.pending_catchup.as_mut().unwrap();
-            process.pending_reps.push(catchup);
-            while !process.pending_reps.is_empty()
-                && process.pending_reps.peek().unwrap().min_tx() - 1 == process.merkle_tree.count() {
-                let mut first_resp = process.pending_reps.pop().unwrap();
-                while !first_resp.txns.is_empty() {
-                    let key = first_resp.min_tx().to_string();
-                    let new_gen_tx = first_resp.txns.remove(&key).unwrap().to_json().unwrap();
-                    trace!("append to tree {}", new_gen_tx);
-                    process.merkle_tree.append(
-                        new_gen_tx
-                    )?;
+            let process = match self.pending_catchup.as_mut() {
+                Some(process) => process,
+                None => {
+                    warn!("Unexpected catchup reply, catchup isn't started");
+                    return Ok(None);
+                }
+            };
+            let seq_no_start = match catchup.min_tx() {
+                Ok(seq_no_start) => seq_no_start,
+                Err(err) => {
+                    warn!("Node {} sent invalid catchup reply: {}", self.nodes[src_ind].name, err);
+                    return Ok(None);
                 }
+            };
+            let slice_idx = match process.slices.iter().position(|slice| slice.req.seqNoStart == seq_no_start
+                && slice.node_idx == src_ind && slice.rep.is_none()) {
+                Some(slice_idx) => slice_idx,
+                None => {
+                    warn!("Node {} sent unexpected catchup reply starting at {}", self.nodes[src_ind].name, seq_no_start);
+                    return Ok(None);
+                }
+            };
+            if let Err(err) = CatchupHandler::_sorted_txns(&process.slices[slice_idx].req, &catchup) {
+                warn!("Node {} sent invalid catchup reply: {}", self.nodes[src_ind].name, err);
+                CatchupHandler::_resend_slice(&self.nodes, transport, &mut process.slices[slice_idx])?;
+                return Ok(None);
             }
-            trace!("updated mt hash {}, tree {:?}", process.merkle_tree.root_hash().as_slice().to_base58(), process.merkle_tree);
-            if &process.merkle_tree.count() == &self.new_mt_size {
-                //TODO check also root hash?
-                true
-            } else {
-                false
+            process.slices[slice_idx].rep = Some(catchup);
+
+            while process.slices.first().map_or(false, |slice| slice.rep.is_some()) {
+                match CatchupHandler::_apply_slice(&process.merkle_tree, &process.slices[0],
+                                                   self.new_mt_size, &self.new_mt_root) {
+                    Ok((merkle_tree, txns)) => {
+                        process.merkle_tree = merkle_tree;
+                        process.txns.extend(txns);
+                        process.slices.remove(0);
+                    }
+                    Err(err) => {
+                        warn!("Node {} sent catchup reply that doesn't match agreed ledger: {}",
+                              self.nodes[process.slices[0].node_idx].name, err);
+                        CatchupHandler::_resend_slice(&self.nodes, transport, &mut process.slices[0])?;
+                        break;
+                    }
+                }
             }
+            trace!("updated mt hash {}, size {}", process.merkle_tree.root_hash().as_slice().to_base58(), process.merkle_tree.count());
+            process.slices.is_empty()
         };
         if catchup_finished {
             return Ok(Some(self.finish_catchup()?));
@@ -123,7 +231,125 @@ impl CatchupHandler {
         Ok(None)
     }
 
-    pub fn finish_catchup(&mut self) -> Result<MerkleTree, PoolError> {
-        Ok(self.pending_catchup.take().unwrap().merkle_tree)
+    pub fn finish_catchup(&mut self) -> Result<LedgerUpdate, PoolError> {
+        let process = self.pending_catchup.take()
+            .ok_or(PoolError::InvalidState("Catchup isn't started".to_string()))?;
+        Ok(LedgerUpdate {
+            merkle_tree: process.merkle_tree,
+            txns: process.txns,
+        })
+    }
+
+    /// Returns time in ms until the nearest slice request expires, -1 if nothing is awaited
+    pub fn get_timeout(&self) -> i64 {
+        let process = match self.pending_catchup {
+            Some(ref process) => process,
+            None => return -1,
+        };
+        process.slices.iter()
+            .filter(|slice| slice.rep.is_none())
+            .map(|slice| {
+                let elapsed = slice.sent_at.elapsed();
+                let elapsed_ms = elapsed.as_secs() * 1000 + (elapsed.subsec_nanos() / 1_000_000) as u64;
+                CATCHUP_REP_TIMEOUT_MS.saturating_sub(elapsed_ms) as i64
+            })
+            .min()
+            .unwrap_or(-1)
+    }
+
+    /// Re-requests expired slices from other nodes
+    pub fn process_timeout(&mut self, transport: &Transport) -> Result<(), PoolError> {
+        if let Some(ref mut process) = self.pending_catchup {
+            for slice in process.slices.iter_mut() {
+                if slice.rep.is_none() && slice.sent_at.elapsed() >= Duration::from_millis(CATCHUP_REP_TIMEOUT_MS) {
+                    warn!("Node {} didn't answer catchup request {}..{} in time",
+                          self.nodes[slice.node_idx].name, slice.req.seqNoStart, slice.req.seqNoEnd);
+                    CatchupHandler::_resend_slice(&self.nodes, transport, slice)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn _resend_slice(nodes: &Vec<RemoteNode>, transport: &Transport, slice: &mut CatchupSlice) -> Result<(), PoolError> {
+        slice.failed_nodes.push(slice.node_idx);
+        let node_cnt = nodes.len();
+        let node_idx = (1..node_cnt + 1)
+            .map(|i| (slice.node_idx + i) % node_cnt)
+            .find(|node_idx| !slice.failed_nodes.contains(node_idx))
+            .ok_or(PoolError::InvalidState(format!("Catchup failed: no node sent valid txns {}..{}",
+                                                   slice.req.seqNoStart, slice.req.seqNoEnd)))?;
+        debug!("Re-request txns {}..{} from node {}", slice.req.seqNoStart, slice.req.seqNoEnd, nodes[node_idx].name);
+        slice.node_idx = node_idx;
+        slice.sent_at = Instant::now();
+        slice.rep = None;
+        nodes[node_idx].send_msg(transport, &Message::CatchupReq(slice.req.clone())).ok();
+        Ok(())
+    }
+
+    /// Returns txns of the reply ordered by seq_no, if they cover requested range exactly
+    fn _sorted_txns(req: &CatchupReq, rep: &CatchupRep) -> Result<Vec<String>, PoolError> {
+        let mut txns: Vec<(usize, &serde_json::Value)> = Vec::new();
+        for (key, txn) in &rep.txns {
+            let seq_no = key.parse::<usize>()
+                .map_err(|_| PoolError::InvalidData(format!("Invalid txn seq_no {:?} in catchup reply", key)))?;
+            txns.push((seq_no, txn));
+        }
+        txns.sort_by_key(|&(seq_no, _)| seq_no);
+        let seq_nos: Vec<usize> = txns.iter().map(|&(seq_no, _)| seq_no).collect();
+        let expected: Vec<usize> = (req.seqNoStart..req.seqNoEnd + 1).collect();
+        if seq_nos != expected {
+            return Err(PoolError::InvalidData(format!("Catchup reply contains txns {:?}, expected {}..{}",
+                                                      seq_nos, req.seqNoStart, req.seqNoEnd)));
+        }
+        txns.iter()
+            .map(|&(_, txn)| serde_json::to_string(txn)
+                .map_err(|err| PoolError::InvalidData(format!("Can't serialize txn: {}", err))))
+            .collect()
+    }
+
+    /// Appends txns of the slice to the copy of the tree and checks consProof
+    /// of the reply against the agreed ledger size and root
+    fn _apply_slice(merkle_tree: &MerkleTree, slice: &CatchupSlice,
+                    target_size: usize, target_root: &Vec<u8>) -> Result<(MerkleTree, Vec<String>), PoolError> {
+        let rep = slice.rep.as_ref()
+            .ok_or(PoolError::InvalidState("Catchup reply isn't received yet".to_string()))?;
+        let txns = CatchupHandler::_sorted_txns(&slice.req, rep)?;
+        let proof: Vec<Vec<u8>> = rep.consProof.iter()
+            .map(|hash| CatchupHandler::_decode_hash(hash))
+            .collect::<Result<Vec<Vec<u8>>, PoolError>>()?;
+        let mut merkle_tree = merkle_tree.clone();
+        for txn in &txns {
+            trace!("append to tree {}", txn);
+            merkle_tree.append(txn)?;
+        }
+        if !MerkleTree::verify_consistency(merkle_tree.count(), merkle_tree.root_hash(),
+                                           target_size, target_root, &proof)? {
+            return Err(PoolError::InvalidData(format!("Txns {}..{} aren't consistent with ledger of size {}",
+                                                      slice.req.seqNoStart, slice.req.seqNoEnd, target_size)));
+        }
+        Ok((merkle_tree, txns))
+    }
+
+    /// Returns new root of the proof if it is consistent with local ledger
+    fn _check_consistency_proof(merkle_tree: &MerkleTree, cons_proof: &ConsistencyProof) -> Result<Vec<u8>, PoolError> {
+        let new_root = CatchupHandler::_decode_hash(&cons_proof.newMerkleRoot)?;
+        if merkle_tree.count() == 0 {
+            // Empty ledger is consistent with any ledger, so the root is trusted by votes only
+            return Ok(new_root);
+        }
+        let proof: Vec<Vec<u8>> = cons_proof.hashes.iter()
+            .map(|hash| CatchupHandler::_decode_hash(hash))
+            .collect::<Result<Vec<Vec<u8>>, PoolError>>()?;
+        if !MerkleTree::verify_consistency(merkle_tree.count(), merkle_tree.root_hash(),
+                                           cons_proof.seqNoEnd, &new_root, &proof)? {
+            return Err(PoolError::InvalidData(format!("Ledger {} isn't consistent with local ledger", cons_proof.newMerkleRoot)));
+        }
+        Ok(new_root)
+    }
+
+    fn _decode_hash(hash: &str) -> Result<Vec<u8>, PoolError> {
+        hash.from_base58()
+            .map_err(|_| PoolError::InvalidData(format!("Hash {:?} isn't valid base58", hash)))
     }
 }
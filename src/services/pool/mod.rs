@@ -1,17 +1,25 @@
 mod types;
 mod catchup;
+mod replica;
+mod storage;
+mod transport;
 
 extern crate byteorder;
 extern crate rust_base58;
+extern crate serde_json;
 extern crate zmq;
 
 use self::byteorder::{ByteOrder, LittleEndian};
-use self::rust_base58::FromBase58;
+use self::rust_base58::{FromBase58, ToBase58};
 use std::cell::RefCell;
-use std::collections::{HashMap};
-use std::{fmt, fs, io, thread};
+use std::collections::{HashMap, HashSet};
+use std::{fmt, fs, io, mem, thread};
 use std::fmt::Debug;
-use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::io::{BufRead, Read, Write};
+use std::net::IpAddr;
+use std::path::Path;
 use std::error::Error;
 
 use commands::{Command, CommandExecutor};
@@ -19,14 +27,36 @@ use commands::ledger::LedgerCommand;
 use commands::pool::PoolCommand;
 use errors::pool::PoolError;
 use errors::crypto::CryptoError;
+use errors::sovrin::SovrinError;
 use self::catchup::CatchupHandler;
+use self::replica::DomainReplica;
+use self::storage::LedgerStorage;
+use self::transport::{CommandSender, ConnectionId, Transport, TransportEvent, ZMQTransport};
 use self::types::*;
+use services::ledger::constants::PROTOCOL_VERSION;
 use services::ledger::merkletree::merkletree::MerkleTree;
 use utils::crypto::ed25519::ED25519;
 use utils::environment::EnvironmentUtils;
 use utils::json::{JsonDecodable, JsonEncodable};
 use utils::sequence::SequenceUtils;
 
+const NODE_TXN_TYPE: &'static str = "0";
+/// BFT needs n >= 3f + 1 validators to tolerate f >= 1 faulty nodes.
+const MIN_ACTIVE_NODES_CNT: usize = 4;
+const DEFAULT_CLIENT_IDENTITY: &'static str = "default";
+const DEFAULT_REPLICA_MAX_AGE_SECS: u64 = 300;
+/// Time given to domain ledger catchup of the stale replica before it's started again
+const REPLICA_REFRESH_TIMEOUT_MS: u64 = 10000;
+/// Silent node is pinged and reported unreachable if it doesn't answer in time
+const PING_INTERVAL_MS: u64 = 5000;
+const PONG_TIMEOUT_MS: u64 = 3000;
+/// Kinds of commands sent to the pool worker
+const CMD_REQUEST: &'static str = "request";
+const CMD_CANCEL: &'static str = "cancel";
+const CMD_EXIT: &'static str = "exit";
+const POOL_LEDGER_ID: usize = 0;
+const DOMAIN_LEDGER_ID: usize = 1;
+
 pub struct PoolService {
     pools: RefCell<HashMap<i32, Pool>>,
 }
@@ -34,16 +64,32 @@ pub struct PoolService {
 struct Pool {
     name: String,
     id: i32,
-    cmd_sock: zmq::Socket,
+    cmd_sender: Box<CommandSender>,
+    status: Arc<Mutex<PoolStatus>>,
     worker: Option<thread::JoinHandle<Result<(), PoolError>>>,
+    auth_checks: bool,
 }
 
 struct PoolWorker {
-    cmd_sock: zmq::Socket,
+    transport: Box<Transport>,
+    client_keys: ClientKeyPair,
     open_cmd_id: i32,
     pool_id: i32,
     name: String,
+    /// Stored pool ledger, opened by catchup initialization
+    pool_ledger: Option<LedgerStorage>,
+    /// Nodes merged from pool ledger txns
+    node_state: Vec<GenTransaction>,
     handler: PoolWorkerHandler,
+    status: Arc<Mutex<PoolStatus>>,
+    replica: Option<DomainReplica>,
+    /// Domain ledger catchup of the stale replica, requests are sent to nodes meanwhile
+    replica_refresh: Option<ReplicaRefresh>,
+}
+
+struct ReplicaRefresh {
+    started_at: Instant,
+    handler: CatchupHandler,
 }
 
 enum PoolWorkerHandler {
@@ -54,14 +100,20 @@ enum PoolWorkerHandler {
 struct TransactionHandler {
     f: usize,
     nodes: Vec<RemoteNode>,
-    pending_commands: HashMap<u64 /* requestId */, CommandProcess>,
+    pending_commands: HashMap<(String, u64) /* identifier, requestId */, CommandProcess>,
 }
 
 impl PoolWorkerHandler {
-    fn process_msg(&mut self, raw_msg: &String, src_ind: usize) -> Result<Option<MerkleTree>, PoolError> {
-        let msg = Message::from_raw_str(raw_msg).unwrap();
+    fn process_msg(&mut self, transport: &Transport, raw_msg: &String, src_ind: usize) -> Result<Option<LedgerUpdate>, PoolError> {
+        let msg = match Message::from_raw_str(raw_msg) {
+            Ok(msg) => msg,
+            Err(err) => {
+                warn!("Node {} sent invalid message {:?}: {}", self.node_name(src_ind), raw_msg, err.description());
+                return Ok(None);
+            }
+        };
         match self {
-            &mut PoolWorkerHandler::CatchupHandler(ref mut ch) => ch.process_msg(msg, raw_msg, src_ind),
+            &mut PoolWorkerHandler::CatchupHandler(ref mut ch) => ch.process_msg(transport, msg, raw_msg, src_ind),
             &mut PoolWorkerHandler::TransactionHandler(ref mut ch) => ch.process_msg(msg, raw_msg, src_ind),
         }
     }
@@ -80,6 +132,24 @@ impl PoolWorkerHandler {
         }
     }
 
+    fn node_name(&self, idx: usize) -> String {
+        self.nodes().get(idx).map(|node| node.name.clone()).unwrap_or(format!("#{}", idx))
+    }
+
+    fn get_timeout(&self) -> i64 {
+        match self {
+            &PoolWorkerHandler::CatchupHandler(ref ch) => ch.get_timeout(),
+            &PoolWorkerHandler::TransactionHandler(_) => -1,
+        }
+    }
+
+    fn process_timeout(&mut self, transport: &Transport) -> Result<(), PoolError> {
+        match self {
+            &mut PoolWorkerHandler::CatchupHandler(ref mut ch) => ch.process_timeout(transport),
+            &mut PoolWorkerHandler::TransactionHandler(_) => Ok(()),
+        }
+    }
+
     fn set_f(&mut self, f: usize) {
         match self {
             &mut PoolWorkerHandler::CatchupHandler(ref mut ch) => ch.f = f,
@@ -89,10 +159,13 @@ impl PoolWorkerHandler {
 }
 
 impl TransactionHandler {
-    fn process_msg(&mut self, msg: Message, raw_msg: &String, src_ind: usize) -> Result<Option<MerkleTree>, PoolError> {
+    fn process_msg(&mut self, msg: Message, raw_msg: &String, src_ind: usize) -> Result<Option<LedgerUpdate>, PoolError> {
         match msg {
             Message::Reply(reply) => {
-                self.process_reply(&reply, raw_msg);
+                self.process_reply(&reply, raw_msg, src_ind)?;
+            }
+            Message::ReqNACK(response) | Message::Reject(response) => {
+                self.process_nack(&response, raw_msg, src_ind)?;
             }
             _ => {
                 warn!("unhandled msg {:?}", msg);
@@ -101,91 +174,301 @@ impl TransactionHandler {
         Ok(None)
     }
 
-    fn process_reply(&mut self, reply: &Reply, raw_msg: &String) {
-        let req_id = reply.result.req_id;
+    /// Reply is accepted once f + 1 distinct nodes sent the same result,
+    /// so a faulty node can neither vote twice nor get its own result accepted
+    fn process_reply(&mut self, reply: &Reply, raw_msg: &String, src_ind: usize) -> Result<(), PoolError> {
+        let req_key = (reply.result.identifier.clone(), reply.result.req_id);
         let mut remove = false;
-        if let Some(pend_cmd) = self.pending_commands.get_mut(&req_id) {
-            pend_cmd.reply_cnt += 1;
-            if pend_cmd.reply_cnt == self.f + 1 {
-                for &cmd_id in &pend_cmd.cmd_ids {
-                    CommandExecutor::instance().send(
-                        Command::Ledger(LedgerCommand::SubmitAck(cmd_id, Ok(raw_msg.clone())))).unwrap();
-                }
+        if let Some(pend_cmd) = self.pending_commands.get_mut(&req_key) {
+            let result = TransactionHandler::_reply_result(raw_msg);
+            let agreed = {
+                let nodes = pend_cmd.replies.entry(result).or_insert_with(HashSet::new);
+                nodes.insert(src_ind);
+                nodes.len() == self.f + 1
+            };
+            if agreed {
+                TransactionHandler::_send_submit_ack(&pend_cmd.cmd_ids, raw_msg)?;
+                remove = true;
+            }
+        }
+        if remove {
+            self.pending_commands.remove(&req_key);
+        }
+        Ok(())
+    }
+
+    /// Request refused by f + 1 distinct nodes (REQNACK or REJECT) isn't going to be
+    /// accepted by the pool, so the refusal is passed to the caller as the answer
+    fn process_nack(&mut self, response: &Response, raw_msg: &String, src_ind: usize) -> Result<(), PoolError> {
+        let req_key = (response.identifier.clone(), response.req_id);
+        let mut remove = false;
+        if let Some(pend_cmd) = self.pending_commands.get_mut(&req_key) {
+            pend_cmd.nacks.insert(src_ind);
+            if pend_cmd.nacks.len() == self.f + 1 {
+                TransactionHandler::_send_submit_ack(&pend_cmd.cmd_ids, raw_msg)?;
                 remove = true;
             }
         }
         if remove {
-            self.pending_commands.remove(&req_id);
+            self.pending_commands.remove(&req_key);
+        }
+        Ok(())
+    }
+
+    fn _send_submit_ack(cmd_ids: &[i32], raw_msg: &String) -> Result<(), PoolError> {
+        for &cmd_id in cmd_ids {
+            CommandExecutor::instance().send(
+                Command::Ledger(LedgerCommand::SubmitAck(cmd_id, Ok(raw_msg.clone()))))
+                .map_err(|err| PoolError::InvalidState(format!("Can't send SubmitAck: {}", err.description())))?;
+        }
+        Ok(())
+    }
+
+    fn _reply_result(raw_msg: &str) -> String {
+        serde_json::from_str::<serde_json::Value>(raw_msg)
+            .map(|reply| reply["result"].to_string())
+            .unwrap_or_default()
+    }
+
+    fn cancel_request(&mut self, cmd_id: i32) {
+        let mut req_key_to_remove = None;
+        for (req_key, pend_cmd) in self.pending_commands.iter_mut() {
+            if let Some(idx) = pend_cmd.cmd_ids.iter().position(|&id| id == cmd_id) {
+                pend_cmd.cmd_ids.remove(idx);
+                if pend_cmd.cmd_ids.is_empty() {
+                    req_key_to_remove = Some(req_key.clone());
+                }
+                break;
+            }
+        }
+        if let Some(req_key) = req_key_to_remove {
+            // Nobody waits for this request anymore, so replies from nodes will be ignored
+            self.pending_commands.remove(&req_key);
         }
     }
 
-    fn try_send_request(&mut self, cmd: &String, cmd_id: i32) {
+    /// Requests are identified by identifier and reqId together as nodes do, so only
+    /// the same request submitted several times is merged
+    fn try_send_request(&mut self, transport: &Transport, cmd: &String, cmd_id: i32) -> Result<(), PoolError> {
         info!("cmd {:?}", cmd);
-        let tmp = SimpleRequest::from_json(cmd).unwrap();
-        if self.pending_commands.contains_key(&tmp.req_id) {
-            self.pending_commands.get_mut(&tmp.req_id).unwrap().cmd_ids.push(cmd_id);
-        } else {
-            let pc = CommandProcess {
-                cmd_ids: vec!(cmd_id),
-                nack_cnt: 0,
-                reply_cnt: 0,
-            };
-            self.pending_commands.insert(tmp.req_id, pc);
-            for node in &self.nodes {
-                let node: &RemoteNode = node;
-                node.send_str(cmd);
+        let tmp = match TransactionHandler::parse_request(cmd) {
+            Ok(tmp) => tmp,
+            Err(err) => {
+                warn!("Can't send request {:?}: {}", cmd, err);
+                return PoolWorker::send_submit_ack_err(cmd_id, err);
             }
+        };
+        let req_key = (tmp.identifier, tmp.req_id);
+        if let Some(pend_cmd) = self.pending_commands.get_mut(&req_key) {
+            pend_cmd.cmd_ids.push(cmd_id);
+            return Ok(());
+        }
+        let pc = CommandProcess {
+            cmd_ids: vec!(cmd_id),
+            nacks: HashSet::new(),
+            replies: HashMap::new(),
+        };
+        self.pending_commands.insert(req_key, pc);
+        for node in &self.nodes {
+            let node: &RemoteNode = node;
+            node.send_str(transport, cmd).ok();
+        }
+        Ok(())
+    }
+
+    fn parse_request(cmd: &str) -> Result<SimpleRequest, PoolError> {
+        let request = SimpleRequest::from_json(cmd)
+            .map_err(|err| PoolError::InvalidData(format!("Invalid request json: {}", err.description())))?;
+        if request.protocol_version != PROTOCOL_VERSION {
+            return Err(PoolError::InvalidData(format!("Unsupported protocol version {}, {} expected",
+                                                      request.protocol_version, PROTOCOL_VERSION)));
         }
+        Ok(request)
     }
 }
 
 impl PoolWorker {
-    fn connect_to_known_nodes(&mut self, merkle_tree: Option<&MerkleTree>) -> Result<(), PoolError> {
-        let merkle_tree: MerkleTree = match merkle_tree {
-            Some(mt) => {
-                let mt: MerkleTree = mt.clone();
-                mt
-            }
-            None => {
-                match self.handler {
-                    PoolWorkerHandler::CatchupHandler(ref ch) => ch.merkle_tree.clone(),
-                    PoolWorkerHandler::TransactionHandler(_) => return Err(PoolError::InvalidState("Expect catchup state".to_string())),
+    fn connect_to_known_nodes(&mut self, merkle_tree: &MerkleTree) -> Result<(), PoolError> {
+        let node_state = self.node_state.clone();
+        let mut old_nodes: Vec<RemoteNode> = mem::replace(self.handler.nodes_mut(), Vec::new());
+        for gen_txn in node_state {
+            if !gen_txn.is_active() {
+                debug!("skip inactive node {:?}", gen_txn);
+                continue;
+            }
+            let alias = gen_txn.data.alias.clone();
+            let mut rn: RemoteNode = match RemoteNode::new(gen_txn) {
+                Ok(rn) => rn,
+                Err(err) => {
+                    warn!("Node {} skipped: {}", alias, err);
+                    continue;
+                }
+            };
+            match old_nodes.iter().position(|old| old.is_same_endpoint(&rn)) {
+                Some(idx) => {
+                    // Node wasn't changed - keep existing connection
+                    rn = old_nodes.remove(idx);
+                }
+                None => {
+                    let res = rn.connect(self.transport.as_mut(), &self.client_keys)
+                        .and_then(|_| rn.send_str(self.transport.as_ref(), "pi"));
+                    if let Err(err) = res {
+                        warn!("Node {} skipped, can't connect: {}", alias, err);
+                        continue;
+                    }
                 }
             }
-        };
-        let ctx: zmq::Context = zmq::Context::new();
-        for gen_txn in &merkle_tree {
-            let mut rn: RemoteNode = RemoteNode::new(gen_txn.as_str());
-            rn.connect(&ctx);
-            rn.zsock.as_ref().unwrap().send("pi".as_bytes(), 0).expect("send ping");
             self.handler.nodes_mut().push(rn);
         }
-        self.handler.set_f(PoolWorker::get_f(merkle_tree.count())); //TODO set cnt to connect
+        // Close connections of changed and demoted nodes
+        for node in old_nodes {
+            if let Some(conn_id) = node.conn_id {
+                self.transport.disconnect(conn_id);
+            }
+        }
+        // Nodes that can't be connected are still counted as faulty ones of the validator set
+        let cnt = self.node_state.iter().filter(|gen_txn| gen_txn.is_active()).count();
+        let f = PoolWorker::get_f(cnt);
+        self.handler.set_f(f);
+
+        let aliases: Vec<String> = self.handler.nodes().iter().map(|node| node.name.clone()).collect();
+        self.update_status(|status| {
+            let nodes = aliases.into_iter().map(|alias| {
+                let state = status.nodes.iter()
+                    .find(|node| node.alias == alias)
+                    .map(|node| node.state)
+                    .unwrap_or(NodeConnectionState::Connecting);
+                NodeStatus { alias: alias, state: state }
+            }).collect();
+            status.nodes = nodes;
+            status.f = f;
+            status.ledger_size = merkle_tree.count();
+            status.root_hash = merkle_tree.root_hash().as_slice().to_base58();
+        });
+        Ok(())
+    }
+
+    fn update_status<F>(&self, update: F) -> PoolStatus where F: FnOnce(&mut PoolStatus) {
+        let mut status = match self.status.lock() {
+            Ok(status) => status,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        update(&mut status);
+        status.clone()
+    }
+
+    fn notify(&self, event: PoolEventType, node: Option<String>) {
+        let event = PoolEvent {
+            event: event,
+            node: node,
+            status: self.update_status(|_| {}),
+        };
+        let res = event.to_json()
+            .map_err(|err| err.to_string())
+            .and_then(|event_json| CommandExecutor::instance()
+                .send(Command::Pool(PoolCommand::Event(self.pool_id, event_json)))
+                .map_err(|err| err.to_string()));
+        if let Err(err) = res {
+            warn!("Can't send pool event {:?}: {}", event.event, err);
+        }
+    }
+
+    fn set_phase(&self, phase: PoolPhase) {
+        let mut prev_phase = phase;
+        self.update_status(|status| {
+            prev_phase = status.phase;
+            status.phase = phase;
+        });
+        if prev_phase != phase {
+            self.notify(PoolEventType::PhaseChanged, None);
+        }
+    }
+
+    fn set_node_state(&self, node_idx: usize, state: NodeConnectionState) {
+        let mut changed = false;
+        let alias = self.handler.node_name(node_idx);
+        self.update_status(|status| {
+            if let Some(node) = status.nodes.iter_mut().find(|node| node.alias == alias) {
+                changed = node.state != state;
+                node.state = state;
+            }
+        });
+        if changed {
+            match state {
+                NodeConnectionState::Connected => self.notify(PoolEventType::NodeConnected, Some(alias)),
+                NodeConnectionState::Unreachable => self.notify(PoolEventType::NodeDisconnected, Some(alias)),
+                NodeConnectionState::Connecting => {}
+            }
+        }
+    }
+
+    fn check_connected_nodes(&self) -> Result<(), PoolError> {
+        if self.handler.nodes().is_empty() {
+            return Err(PoolError::InvalidData("No active nodes to connect".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Folds pool ledger txns by dest into current state of each node.
+    /// Nodes are ordered by first appearance in the ledger.
+    fn _merge_node_txn(nodes: &mut Vec<GenTransaction>, gen_txn: &str) -> Result<(), PoolError> {
+        let gen_txn: GenTransaction = GenTransaction::from_json(gen_txn)
+            .map_err(|err| PoolError::InvalidData(format!("Invalid pool ledger txn: {}", err.description())))?;
+        match nodes.iter().position(|node| node.dest == gen_txn.dest) {
+            Some(idx) => nodes[idx].merge(gen_txn),
+            None => nodes.push(gen_txn),
+        }
         Ok(())
     }
 
     fn init_catchup(&mut self) -> Result<(), PoolError> {
+        let storage = PoolWorker::_pool_ledger_storage(self.name.as_str())?;
+        let merkle_tree = storage.restore_tree()?;
+        self.node_state = Vec::new();
+        for gen_txn in storage.txns()? {
+            PoolWorker::_merge_node_txn(&mut self.node_state, &gen_txn?)?;
+        }
+        self.pool_ledger = Some(storage);
         let catchup_handler = CatchupHandler {
             f: 0,
-            ledger_status_same: 0,
+            ledger_id: POOL_LEDGER_ID,
+            ledger_status_same: HashSet::new(),
             nodes: Vec::new(),
             new_mt_size: 0,
-            new_mt_vote: 0,
-            merkle_tree: PoolWorker::_restore_merkle_tree(self.name.as_str())?,
+            new_mt_root: Vec::new(),
+            new_mt_votes: HashMap::new(),
+            merkle_tree: merkle_tree.clone(),
             pending_catchup: None,
         };
         self.handler = PoolWorkerHandler::CatchupHandler(catchup_handler);
-        self.connect_to_known_nodes(None)?;
-        Ok(())
+        self.connect_to_known_nodes(&merkle_tree)?;
+        self.check_connected_nodes()
     }
 
     pub fn run(&mut self) -> Result<(), PoolError> {
-        self.init_catchup()?; //TODO consider error as PoolOpen error
+        let res = self._run();
+        match res {
+            Ok(()) => self.set_phase(PoolPhase::Terminated),
+            Err(ref err) => {
+                error!("Pool {} worker failed: {}", self.name, err);
+                self.fail_pending_commands(err);
+                self.set_phase(PoolPhase::Failed);
+            }
+        }
+        res
+    }
+
+    fn _run(&mut self) -> Result<(), PoolError> {
+        self.init_catchup()?;
 
         'zmq_poll_loop: loop {
             trace!("zmq poll loop >>");
 
-            let actions = self.poll_zmq();
+            let actions = self.poll_transport()?;
+            self.handler.process_timeout(self.transport.as_ref())?;
+            self.process_refresh_timeout();
+            self.check_replica_refresh();
+            self.check_nodes_liveness();
 
             for action in &actions {
                 match action {
@@ -194,21 +477,33 @@ impl PoolWorker {
                         break 'zmq_poll_loop;
                     }
                     &ZMQLoopAction::MessageToProcess(ref msg) => {
-                        if let Some(new_mt) = self.handler.process_msg(&msg.message, msg.node_idx)? {
-                            self.handler = PoolWorkerHandler::TransactionHandler(TransactionHandler {
-                                nodes: Vec::new(),
-                                pending_commands: HashMap::new(),
-                                f: 0,
-                            });
-                            self.connect_to_known_nodes(Some(&new_mt))?;
-                            CommandExecutor::instance().send(Command::Pool(
-                                PoolCommand::OpenAck(self.open_cmd_id, Ok(self.pool_id)))).expect("send ack cmd"); //TODO send only once?
-                        }
+                        self.process_msg(&msg.message, msg.node_idx)?;
                     }
                     &ZMQLoopAction::RequestToSend(ref req) => {
                         match self.handler {
-                            PoolWorkerHandler::CatchupHandler(_) => panic!("incorrect state"), //FIXME
-                            PoolWorkerHandler::TransactionHandler(ref mut handler) => handler.try_send_request(&req.request, req.id),
+                            PoolWorkerHandler::CatchupHandler(_) => {
+                                PoolWorker::send_submit_ack_err(
+                                    req.id, PoolError::InvalidState("Pool is not opened yet".to_string()))?;
+                            }
+                            PoolWorkerHandler::TransactionHandler(ref mut handler) => {
+                                let replica = match TransactionHandler::parse_request(&req.request) {
+                                    Ok(_) => self.replica.as_ref(),
+                                    Err(_) => None
+                                };
+                                match replica.and_then(|replica| replica.try_answer(&req.request)) {
+                                    Some(reply) => {
+                                        debug!("Request {} is answered from domain ledger replica", req.id);
+                                        CommandExecutor::instance().send(Command::Ledger(LedgerCommand::SubmitAck(req.id, Ok(reply))))
+                                            .map_err(|err| PoolError::InvalidState(format!("Can't send SubmitAck: {}", err.description())))?
+                                    }
+                                    None => handler.try_send_request(self.transport.as_ref(), &req.request, req.id)?
+                                }
+                            }
+                        }
+                    }
+                    &ZMQLoopAction::CancelRequest(cmd_id) => {
+                        if let PoolWorkerHandler::TransactionHandler(ref mut handler) = self.handler {
+                            handler.cancel_request(cmd_id);
                         }
                     }
                 }
@@ -220,63 +515,350 @@ impl PoolWorker {
         Ok(())
     }
 
-    fn poll_zmq(&mut self) -> Vec<ZMQLoopAction> {
-        let mut actions: Vec<ZMQLoopAction> = Vec::new();
+    /// Catchup messages go to the replica refresh while it runs. Replies of the pool
+    /// invalidate data they write in the replica before they are passed to the caller.
+    fn process_msg(&mut self, raw_msg: &String, node_idx: usize) -> Result<(), PoolError> {
+        if self.replica_refresh.is_some() && PoolWorker::_is_catchup_msg(raw_msg) {
+            self.process_refresh_msg(raw_msg, node_idx);
+            return Ok(());
+        }
+        if let PoolWorkerHandler::TransactionHandler(_) = self.handler {
+            if let Some(ref mut replica) = self.replica {
+                replica.invalidate(raw_msg);
+            }
+        }
+        if let Some(update) = self.handler.process_msg(self.transport.as_ref(), raw_msg, node_idx)? {
+            self.finish_catchup(update)?;
+        }
+        Ok(())
+    }
+
+    fn _is_catchup_msg(raw_msg: &str) -> bool {
+        match Message::from_raw_str(raw_msg) {
+            Ok(Message::LedgerStatus(_)) | Ok(Message::ConsistencyProof(_)) | Ok(Message::CatchupRep(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Failed refresh is dropped, the pool stays usable without the replica until it's started again
+    fn process_refresh_msg(&mut self, raw_msg: &String, node_idx: usize) {
+        let msg = match Message::from_raw_str(raw_msg) {
+            Ok(msg) => msg,
+            Err(_) => return,
+        };
+        let res = match self.replica_refresh {
+            Some(ref mut refresh) => refresh.handler.process_msg(self.transport.as_ref(), msg, raw_msg, node_idx),
+            None => return,
+        };
+        let update = match res {
+            Ok(Some(update)) => update,
+            Ok(None) => return,
+            Err(err) => {
+                warn!("Domain ledger replica refresh failed: {}", err);
+                self.stop_replica_refresh();
+                return;
+            }
+        };
+        self.stop_replica_refresh();
+        if let Some(ref mut replica) = self.replica {
+            match replica.update(update.merkle_tree, update.txns) {
+                Ok(()) => debug!("Domain ledger replica is refreshed"),
+                Err(err) => warn!("Can't update domain ledger replica: {}", err),
+            }
+        }
+    }
+
+    fn process_refresh_timeout(&mut self) {
+        let res = match self.replica_refresh {
+            Some(ref mut refresh) => refresh.handler.process_timeout(self.transport.as_ref()),
+            None => return,
+        };
+        if let Err(err) = res {
+            warn!("Domain ledger replica refresh failed: {}", err);
+            self.stop_replica_refresh();
+        }
+    }
+
+    fn stop_replica_refresh(&mut self) {
+        self.replica_refresh = None;
+        self.set_phase(PoolPhase::Ready);
+    }
+
+    /// Starts domain ledger catchup once the replica is stale. Refresh that isn't finished
+    /// in REPLICA_REFRESH_TIMEOUT_MS is started again.
+    fn check_replica_refresh(&mut self) {
+        let running = match self.replica_refresh {
+            Some(ref refresh) => refresh.started_at.elapsed() < Duration::from_millis(REPLICA_REFRESH_TIMEOUT_MS),
+            None => false,
+        };
+        if running {
+            return;
+        }
+        if self.replica_refresh.is_some() {
+            warn!("Domain ledger replica refresh isn't finished in time");
+            self.stop_replica_refresh();
+        }
+        let f = match self.handler {
+            PoolWorkerHandler::TransactionHandler(ref handler) => handler.f,
+            PoolWorkerHandler::CatchupHandler(_) => return,
+        };
+        let merkle_tree = match self.replica {
+            Some(ref replica) if replica.expires_in() == Some(Duration::from_secs(0)) => replica.merkle_tree().clone(),
+            _ => return,
+        };
+        debug!("Domain ledger replica is stale, catching it up");
+        let handler = CatchupHandler {
+            f: f,
+            ledger_id: DOMAIN_LEDGER_ID,
+            ledger_status_same: HashSet::new(),
+            nodes: self.handler.nodes().clone(),
+            new_mt_size: 0,
+            new_mt_root: Vec::new(),
+            new_mt_votes: HashMap::new(),
+            merkle_tree: merkle_tree,
+            pending_catchup: None,
+        };
+        handler.request_ledger_status(self.transport.as_ref());
+        self.replica_refresh = Some(ReplicaRefresh {
+            started_at: Instant::now(),
+            handler: handler,
+        });
+        self.set_phase(PoolPhase::Refreshing);
+    }
 
-        let mut poll_items = self.get_zmq_poll_items();
-        let r = zmq::poll(poll_items.as_mut_slice(), -1).expect("poll");
-        trace!("zmq poll {:?}", r);
-
-        for i in 0..self.handler.nodes().len() {
-            if poll_items[1 + i].is_readable() {
-                if let Some(msg) = self.handler.nodes()[i].recv_msg().expect("recv msg") {
-                    actions.push(ZMQLoopAction::MessageToProcess(MessageToProcess {
-                        node_idx: i,
-                        message: msg,
-                    }));
+    /// Pings nodes that were silent for PING_INTERVAL_MS. Node that doesn't answer
+    /// in PONG_TIMEOUT_MS is reported unreachable and pinged again.
+    fn check_nodes_liveness(&mut self) {
+        let mut unreachable: Vec<usize> = Vec::new();
+        {
+            let transport = self.transport.as_ref();
+            for (node_idx, node) in self.handler.nodes_mut().iter_mut().enumerate() {
+                let ping = match node.pinged_at {
+                    Some(pinged_at) => {
+                        let timed_out = pinged_at.elapsed() >= Duration::from_millis(PONG_TIMEOUT_MS);
+                        if timed_out {
+                            unreachable.push(node_idx);
+                        }
+                        timed_out
+                    }
+                    None => node.last_seen.elapsed() >= Duration::from_millis(PING_INTERVAL_MS),
+                };
+                if ping {
+                    node.pinged_at = Some(Instant::now());
+                    node.send_str(transport, "pi").ok();
                 }
             }
         }
-        if poll_items[0].is_readable() {
-            let cmd = self.cmd_sock.recv_multipart(zmq::DONTWAIT).unwrap();
-            trace!("cmd {:?}", cmd);
-            let cmd_s = String::from_utf8(cmd[0].clone()).expect("non-string command");
-            if "exit".eq(cmd_s.as_str()) {
-                actions.push(ZMQLoopAction::Terminate);
-            } else {
-                actions.push(ZMQLoopAction::RequestToSend(RequestToSend {
-                    id: LittleEndian::read_i32(cmd[1].as_slice()),
-                    request: cmd_s,
-                }));
+        for node_idx in unreachable {
+            warn!("Node {} doesn't answer ping", self.handler.node_name(node_idx));
+            self.set_node_state(node_idx, NodeConnectionState::Unreachable);
+        }
+    }
+
+    /// Returns time in ms until the nearest catchup timeout, replica expiration or node ping, -1 if there is none
+    fn get_timeout(&self) -> i64 {
+        let mut timeouts = vec![self.handler.get_timeout()];
+        for node in self.handler.nodes() {
+            let left = match node.pinged_at {
+                Some(pinged_at) => Duration::from_millis(PONG_TIMEOUT_MS).checked_sub(pinged_at.elapsed()),
+                None => Duration::from_millis(PING_INTERVAL_MS).checked_sub(node.last_seen.elapsed()),
+            };
+            timeouts.push(PoolWorker::_duration_ms(left.unwrap_or(Duration::from_secs(0))));
+        }
+        if let PoolWorkerHandler::TransactionHandler(_) = self.handler {
+            match self.replica_refresh {
+                Some(ref refresh) => {
+                    timeouts.push(refresh.handler.get_timeout());
+                    let left = Duration::from_millis(REPLICA_REFRESH_TIMEOUT_MS)
+                        .checked_sub(refresh.started_at.elapsed())
+                        .unwrap_or(Duration::from_secs(0));
+                    timeouts.push(PoolWorker::_duration_ms(left));
+                }
+                None => {
+                    if let Some(expires_in) = self.replica.as_ref().and_then(DomainReplica::expires_in) {
+                        timeouts.push(PoolWorker::_duration_ms(expires_in));
+                    }
+                }
             }
         }
-        actions
+        timeouts.into_iter().filter(|timeout| *timeout >= 0).min().unwrap_or(-1)
+    }
+
+    /// Rounds up, so the poll doesn't wake up right before the deadline
+    fn _duration_ms(duration: Duration) -> i64 {
+        (duration.as_secs() * 1000 + ((duration.subsec_nanos() + 999_999) / 1_000_000) as u64) as i64
     }
 
-    fn get_zmq_poll_items(&self) -> Vec<zmq::PollItem> {
-        let mut poll_items: Vec<zmq::PollItem> = Vec::new();
-        poll_items.push(self.cmd_sock.as_poll_item(zmq::POLLIN));
-        for ref node in self.handler.nodes() {
-            let s: &zmq::Socket = node.zsock.as_ref().unwrap();
-            poll_items.push(s.as_poll_item(zmq::POLLIN));
+    /// Pool ledger catchup is followed by domain ledger catchup if the replica is enabled.
+    /// Pool is ready once all ledgers are caught up. Caught up txns are appended to stored ledgers.
+    fn finish_catchup(&mut self, update: LedgerUpdate) -> Result<(), PoolError> {
+        let (ledger_id, f) = match self.handler {
+            PoolWorkerHandler::CatchupHandler(ref ch) => (ch.ledger_id, ch.f),
+            PoolWorkerHandler::TransactionHandler(_) => return Err(PoolError::InvalidState("Expect catchup state".to_string())),
+        };
+        let nodes = mem::replace(self.handler.nodes_mut(), Vec::new());
+        if ledger_id == DOMAIN_LEDGER_ID {
+            if let Some(ref mut replica) = self.replica {
+                replica.update(update.merkle_tree, update.txns)?;
+            }
+            self.handler = PoolWorkerHandler::TransactionHandler(TransactionHandler {
+                nodes: nodes,
+                pending_commands: HashMap::new(),
+                f: f,
+            });
+        } else {
+            if !update.txns.is_empty() {
+                match self.pool_ledger {
+                    Some(ref mut storage) => storage.append(&update.txns, &update.merkle_tree)?,
+                    None => return Err(PoolError::InvalidState("Pool ledger isn't opened".to_string())),
+                }
+                for gen_txn in &update.txns {
+                    PoolWorker::_merge_node_txn(&mut self.node_state, gen_txn)?;
+                }
+            }
+            self.handler = match self.replica {
+                Some(ref replica) => PoolWorkerHandler::CatchupHandler(CatchupHandler {
+                    f: 0,
+                    ledger_id: DOMAIN_LEDGER_ID,
+                    ledger_status_same: HashSet::new(),
+                    nodes: nodes,
+                    new_mt_size: 0,
+                    new_mt_root: Vec::new(),
+                    new_mt_votes: HashMap::new(),
+                    merkle_tree: replica.merkle_tree().clone(),
+                    pending_catchup: None,
+                }),
+                None => PoolWorkerHandler::TransactionHandler(TransactionHandler {
+                    nodes: nodes,
+                    pending_commands: HashMap::new(),
+                    f: 0,
+                }),
+            };
+            self.connect_to_known_nodes(&update.merkle_tree)?;
+            self.check_connected_nodes()?;
+            if let PoolWorkerHandler::CatchupHandler(ref ch) = self.handler {
+                debug!("Pool ledger is caught up, catching up domain ledger");
+                ch.request_ledger_status(self.transport.as_ref());
+                return Ok(());
+            }
         }
-        poll_items
+        self.notify(PoolEventType::CatchupCompleted, None);
+        self.set_phase(PoolPhase::Ready);
+        CommandExecutor::instance().send(Command::Pool(
+            PoolCommand::OpenAck(self.open_cmd_id, Ok(self.pool_id))))
+            .map_err(|err| PoolError::InvalidState(format!("Can't send OpenAck: {}", err.description())))?; //TODO send only once?
+        Ok(())
     }
 
+    /// Reports local failure of the worker to all callers that wait for it
+    fn fail_pending_commands(&mut self, err: &PoolError) {
+        let err_msg = format!("Pool worker failed: {}", err);
+        match self.handler {
+            PoolWorkerHandler::CatchupHandler(_) => {
+                let res = CommandExecutor::instance().send(Command::Pool(
+                    PoolCommand::OpenAck(self.open_cmd_id, Err(SovrinError::PoolError(PoolError::InvalidState(err_msg))))));
+                if let Err(err) = res {
+                    error!("Can't send OpenAck: {}", err);
+                }
+            }
+            PoolWorkerHandler::TransactionHandler(ref mut handler) => {
+                for (_, pend_cmd) in handler.pending_commands.drain() {
+                    for cmd_id in pend_cmd.cmd_ids {
+                        if let Err(err) = PoolWorker::send_submit_ack_err(cmd_id, PoolError::InvalidState(err_msg.clone())) {
+                            error!("{}", err);
+                        }
+                    }
+                }
+            }
+        }
+    }
 
-    fn _restore_merkle_tree(pool_name: &str) -> Result<MerkleTree, PoolError> {
-        let mut p = EnvironmentUtils::pool_path(pool_name);
-        let mut mt = MerkleTree::from_vec(Vec::new())?;
-        //TODO firstly try to deserialize merkle tree
-        p.push(pool_name);
-        p.set_extension("txn");
-        let f = fs::File::open(p)?;
-        let reader = io::BufReader::new(&f);
-        for line in reader.lines() {
-            let line: String = line?;
-            mt.append(line)?;
+    fn send_submit_ack_err(cmd_id: i32, err: PoolError) -> Result<(), PoolError> {
+        CommandExecutor::instance().send(
+            Command::Ledger(LedgerCommand::SubmitAck(cmd_id, Err(SovrinError::PoolError(err)))))
+            .map_err(|err| PoolError::InvalidState(format!("Can't send SubmitAck: {}", err.description())))
+    }
+
+    fn poll_transport(&mut self) -> Result<Vec<ZMQLoopAction>, PoolError> {
+        let mut actions: Vec<ZMQLoopAction> = Vec::new();
+
+        let events = self.transport.poll(self.get_timeout())?;
+        trace!("transport poll {:?}", events);
+
+        for event in events {
+            match event {
+                TransportEvent::NodeMessage(conn_id, msg) => {
+                    let node_idx = match self.node_idx(conn_id) {
+                        Some(node_idx) => node_idx,
+                        None => {
+                            warn!("Message from unknown connection {}: {:?}", conn_id, msg);
+                            continue;
+                        }
+                    };
+                    info!(target: "RemoteNode_recv_msg", "{} {}", self.handler.node_name(node_idx), msg);
+                    self.set_node_state(node_idx, NodeConnectionState::Connected);
+                    let liveness_pong = {
+                        let node = &mut self.handler.nodes_mut()[node_idx];
+                        let liveness_pong = msg == "po" && node.pinged_at.is_some();
+                        node.last_seen = Instant::now();
+                        node.pinged_at = None;
+                        liveness_pong
+                    };
+                    if msg != "pi" && !liveness_pong { //TODO send pong
+                        actions.push(ZMQLoopAction::MessageToProcess(MessageToProcess {
+                            node_idx: node_idx,
+                            message: msg,
+                        }));
+                    }
+                }
+                TransportEvent::NodeError(conn_id, err) => {
+                    if let Some(node_idx) = self.node_idx(conn_id) {
+                        warn!("Connection to node {} failed: {}", self.handler.node_name(node_idx), err);
+                        self.set_node_state(node_idx, NodeConnectionState::Unreachable);
+                    }
+                }
+                TransportEvent::Command(cmd) => {
+                    trace!("cmd {:?}", cmd);
+                    actions.push(PoolWorker::parse_cmd(cmd)?);
+                }
+            }
+        }
+        Ok(actions)
+    }
+
+    /// Command kind is sent in a separate frame, so request body can't be taken for a command
+    fn parse_cmd(cmd: Vec<Vec<u8>>) -> Result<ZMQLoopAction, PoolError> {
+        let kind = cmd.get(0).ok_or(PoolError::InvalidState("Empty command".to_string()))?;
+        let kind = String::from_utf8(kind.clone())
+            .map_err(|err| PoolError::InvalidState(format!("Non-string command kind: {}", err.description())))?;
+        let id = match cmd.get(1) {
+            Some(id) if id.len() == 4 => Some(LittleEndian::read_i32(id.as_slice())),
+            _ => None
+        };
+        match (kind.as_str(), id, cmd.len()) {
+            (CMD_EXIT, _, 1) => Ok(ZMQLoopAction::Terminate),
+            (CMD_CANCEL, Some(id), 2) => Ok(ZMQLoopAction::CancelRequest(id)),
+            (CMD_REQUEST, Some(id), 3) => {
+                let request = String::from_utf8(cmd[2].clone())
+                    .map_err(|err| PoolError::InvalidState(format!("Non-string request: {}", err.description())))?;
+                Ok(ZMQLoopAction::RequestToSend(RequestToSend {
+                    id: id,
+                    request: request,
+                }))
+            }
+            _ => Err(PoolError::InvalidState(format!("Invalid format of {:?} command", kind)))
         }
-        Ok(mt)
+    }
+
+    fn node_idx(&self, conn_id: ConnectionId) -> Option<usize> {
+        self.handler.nodes().iter().position(|node| node.conn_id == Some(conn_id))
+    }
+
+    fn _pool_ledger_storage(pool_name: &str) -> Result<LedgerStorage, PoolError> {
+        let mut path = EnvironmentUtils::pool_path(pool_name);
+        path.push(pool_name);
+        path.set_extension("txn");
+        LedgerStorage::open(path)
     }
 
     fn get_f(cnt: usize) -> usize {
@@ -288,28 +870,35 @@ impl PoolWorker {
 }
 
 impl Pool {
-    pub fn new(name: &str, cmd_id: i32) -> Result<Pool, PoolError> {
-        let zmq_ctx = zmq::Context::new();
-        let recv_cmd_sock = zmq_ctx.socket(zmq::SocketType::PAIR)?;
-        let send_cmd_sock = zmq_ctx.socket(zmq::SocketType::PAIR)?;
-        let inproc_sock_name: String = format!("inproc://pool_{}", name);
-
-        recv_cmd_sock.bind(inproc_sock_name.as_str())?;
+    pub fn new(name: &str, cmd_id: i32, client_keys: ClientKeyPair, replica: Option<DomainReplica>) -> Result<Pool, PoolError> {
+        let (cmd_sender, transport) = ZMQTransport::new(name)?;
+        Pool::new_with_transport(name, cmd_id, client_keys, replica, Box::new(cmd_sender), Box::new(transport))
+    }
 
-        send_cmd_sock.connect(inproc_sock_name.as_str())?;
+    fn new_with_transport(name: &str, cmd_id: i32, client_keys: ClientKeyPair, replica: Option<DomainReplica>,
+                          cmd_sender: Box<CommandSender>, transport: Box<Transport>) -> Result<Pool, PoolError> {
+        let status = Arc::new(Mutex::new(PoolStatus::new()));
         let pool_id = SequenceUtils::get_next_id();
         let mut pool_worker: PoolWorker = PoolWorker {
-            cmd_sock: recv_cmd_sock,
+            transport: transport,
+            client_keys: client_keys,
+            status: status.clone(),
             open_cmd_id: cmd_id,
             pool_id: pool_id,
             name: name.to_string(),
+            pool_ledger: None,
+            node_state: Vec::new(),
+            replica: replica,
+            replica_refresh: None,
             handler: PoolWorkerHandler::CatchupHandler(CatchupHandler {
                 f: 0,
-                ledger_status_same: 0,
+                ledger_id: POOL_LEDGER_ID,
+                ledger_status_same: HashSet::new(),
                 nodes: Vec::new(),
                 new_mt_size: 0,
-                new_mt_vote: 0,
-                merkle_tree: MerkleTree::from_vec(Vec::new())?,
+                new_mt_root: Vec::new(),
+                new_mt_votes: HashMap::new(),
+                merkle_tree: MerkleTree::new()?,
                 pending_catchup: None,
             }),
         };
@@ -317,17 +906,32 @@ impl Pool {
         Ok(Pool {
             name: name.to_string(),
             id: pool_id,
-            cmd_sock: send_cmd_sock,
+            cmd_sender: cmd_sender,
+            status: status,
             worker: Some(thread::spawn(move || {
                 pool_worker.run()
             })),
+            auth_checks: false,
         })
     }
 
-    pub fn send_tx(&self, cmd_id: i32, json: &str) {
+    pub fn send_tx(&self, cmd_id: i32, json: &str) -> Result<(), PoolError> {
         let mut buf = [0u8; 4];
         LittleEndian::write_i32(&mut buf, cmd_id);
-        self.cmd_sock.send_multipart(&[json.as_bytes(), &buf], zmq::DONTWAIT).expect("send to cmd sock");
+        self.cmd_sender.send(&[CMD_REQUEST.as_bytes(), &buf, json.as_bytes()])
+    }
+
+    pub fn cancel_tx(&self, cmd_id: i32) -> Result<(), PoolError> {
+        let mut buf = [0u8; 4];
+        LittleEndian::write_i32(&mut buf, cmd_id);
+        self.cmd_sender.send(&[CMD_CANCEL.as_bytes(), &buf])
+    }
+
+    pub fn get_status(&self) -> PoolStatus {
+        match self.status.lock() {
+            Ok(status) => status.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
     }
 }
 
@@ -335,10 +939,18 @@ impl Drop for Pool {
     fn drop(&mut self) {
         let target = format!("pool{}", self.name);
         info!(target: target.as_str(), "Drop started");
-        self.cmd_sock.send("exit".as_bytes(), 0).expect("send exit command"); //TODO
+        if let Err(err) = self.cmd_sender.send(&[CMD_EXIT.as_bytes()]) {
+            warn!(target: target.as_str(), "Can't send exit command to worker: {}", err);
+        }
         info!(target: target.as_str(), "Drop wait worker");
         // Option worker type and this kludge is workaround for rust
-        self.worker.take().unwrap().join().unwrap().unwrap();
+        if let Some(worker) = self.worker.take() {
+            match worker.join() {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => warn!(target: target.as_str(), "Worker finished with error: {}", err),
+                Err(_) => error!(target: target.as_str(), "Worker panicked"),
+            }
+        }
         info!(target: target.as_str(), "Drop finished");
     }
 }
@@ -351,66 +963,57 @@ impl From<CryptoError> for PoolError {
 
 impl Debug for RemoteNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "RemoteNode: {{ public_key: {:?}, verify_key {:?}, zaddr {:?}, zsock is_some {} }}",
-               self.public_key, self.verify_key, self.zaddr, self.zsock.is_some())
+        write!(f, "RemoteNode: {{ public_key: {:?}, verify_key {:?}, zaddr {:?}, conn_id {:?} }}",
+               self.public_key, self.verify_key, self.zaddr, self.conn_id)
     }
 }
 
 impl RemoteNode {
-    fn new(txn: &str) -> RemoteNode {
-        let gen_tx = GenTransaction::from_json(txn).expect("RemoteNode parsing");
-        RemoteNode::from(gen_tx)
+    fn new(tx: GenTransaction) -> Result<RemoteNode, PoolError> {
+        let key_field = if tx.verkey.is_some() { "verkey" } else { "dest" };
+        let public_key = tx.verkey.as_ref().unwrap_or(&tx.dest).as_str().from_base58()
+            .map_err(|_| PoolError::InvalidData(format!("Node {}: {} field isn't valid base58", tx.data.alias, key_field)))?;
+        let verify_key = ED25519::try_pk_to_curve25519(&public_key)
+            .map_err(|err| PoolError::InvalidData(format!("Node {}: {} field isn't valid ed25519 key: {}", tx.data.alias, key_field, err)))?;
+        let client_ip = tx.data.client_ip
+            .ok_or(PoolError::InvalidData(format!("Node {}: client_ip field is absent", tx.data.alias)))?;
+        let client_port = tx.data.client_port
+            .ok_or(PoolError::InvalidData(format!("Node {}: client_port field is absent", tx.data.alias)))?;
+        Ok(RemoteNode {
+            verify_key: verify_key,
+            public_key: public_key,
+            zaddr: format!("tcp://{}:{}", client_ip, client_port),
+            conn_id: None,
+            name: tx.data.alias,
+            last_seen: Instant::now(),
+            pinged_at: None,
+        })
     }
 
-    fn connect(&mut self, ctx: &zmq::Context) {
-        let key_pair = zmq::CurveKeyPair::new().expect("create key pair");
-        let s = ctx.socket(zmq::SocketType::DEALER).expect("socket for Node");
-        s.set_identity(key_pair.public_key.as_bytes()).expect("set identity");
-        s.set_curve_secretkey(key_pair.secret_key.as_str()).expect("set secret key");
-        s.set_curve_publickey(key_pair.public_key.as_str()).expect("set public key");
-        s.set_curve_serverkey(zmq::z85_encode(self.verify_key.as_slice()).unwrap().as_str()).expect("set verify key");
-        s.set_linger(0).expect("set linger"); //TODO set correct timeout
-        s.connect(self.zaddr.as_str()).expect("connect to Node");
-        self.zsock = Some(s);
+    fn is_same_endpoint(&self, other: &RemoteNode) -> bool {
+        self.public_key == other.public_key
+            && self.verify_key == other.verify_key
+            && self.zaddr == other.zaddr
     }
 
-    fn recv_msg(&self) -> Result<Option<String>, PoolError> {
-        impl From<Vec<u8>> for PoolError {
-            fn from(_: Vec<u8>) -> Self {
-                PoolError::Io(io::Error::from(io::ErrorKind::InvalidData))
-            }
-        }
-        let msg: String = self.zsock.as_ref().unwrap().recv_string(zmq::DONTWAIT)??;
-        info!(target: "RemoteNode_recv_msg", "{} {}", self.name, msg);
-
-        match msg.as_ref() {
-            "pi" => Ok(None), //TODO send pong
-            _ => Ok(Some(msg))
-        }
+    fn connect(&mut self, transport: &mut Transport, key_pair: &ClientKeyPair) -> Result<(), PoolError> {
+        self.conn_id = Some(transport.connect(self.zaddr.as_str(), self.verify_key.as_slice(), key_pair)?);
+        Ok(())
     }
 
-    fn send_str(&self, str: &str) {
+    fn send_str(&self, transport: &Transport, str: &str) -> Result<(), PoolError> {
         info!("Sending {:?}", str);
-        self.zsock.as_ref().unwrap()
-            .send_str(str, zmq::DONTWAIT)
-            .unwrap();
-    }
-
-    fn send_msg(&self, msg: &Message) {
-        self.send_str(msg.to_json().unwrap().as_str());
+        let res = self.conn_id
+            .ok_or(PoolError::InvalidState(format!("Node {} isn't connected", self.name)))
+            .and_then(|conn_id| transport.send(conn_id, str));
+        if let Err(ref err) = res {
+            warn!("Can't send message to node {}: {}", self.name, err);
+        }
+        res
     }
-}
 
-impl From<GenTransaction> for RemoteNode {
-    fn from(tx: GenTransaction) -> RemoteNode {
-        let public_key = tx.dest.as_str().from_base58().expect("dest field in GenTransaction isn't valid");
-        RemoteNode {
-            verify_key: ED25519::pk_to_curve25519(&public_key),
-            public_key: public_key,
-            zaddr: format!("tcp://{}:{}", tx.data.client_ip, tx.data.client_port),
-            zsock: None,
-            name: tx.data.alias,
-        }
+    fn send_msg(&self, transport: &Transport, msg: &Message) -> Result<(), PoolError> {
+        self.send_str(transport, msg.to_json()?.as_str())
     }
 }
 
@@ -432,6 +1035,9 @@ impl PoolService {
             return Err(PoolError::NotCreated("Already created".to_string()));
         }
 
+        let genesis_txns = fs::File::open(&pool_config.genesis_txn)?;
+        PoolService::_validate_genesis_txns(io::BufReader::new(genesis_txns))?;
+
         fs::create_dir_all(path.as_path())?;
 
         path.push(name);
@@ -455,6 +1061,71 @@ impl PoolService {
         unimplemented!()
     }
 
+    /// Checks that genesis txns can be used to connect to the pool:
+    /// each line is NODE txn with valid keys and addresses, aliases are unique
+    /// and enough validators to tolerate one faulty node (n >= 3f + 1, f = 1)
+    /// remain active after applying all txns.
+    fn _validate_genesis_txns<R: BufRead>(reader: R) -> Result<(), PoolError> {
+        let mut nodes: Vec<GenTransaction> = Vec::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line: String = line?;
+            let line_no = idx + 1;
+            let invalid = |msg: String| PoolError::InvalidData(format!("Genesis txn at line {}: {}", line_no, msg));
+
+            let gen_txn: GenTransaction = GenTransaction::from_json(line.as_str())
+                .map_err(|err| invalid(format!("invalid json: {}", err)))?;
+            if gen_txn.txn_type != NODE_TXN_TYPE {
+                return Err(invalid(format!("unexpected txn type {:?}, only NODE txns are allowed", gen_txn.txn_type)));
+            }
+            let (field, value) = match gen_txn.verkey {
+                Some(ref verkey) => ("verkey", verkey.clone()),
+                None => ("dest", gen_txn.dest.clone())
+            };
+            let key = value.as_str().from_base58()
+                .map_err(|_| invalid(format!("{} {:?} isn't valid base58", field, value)))?;
+            ED25519::try_pk_to_curve25519(&key)
+                .map_err(|err| invalid(format!("{} {:?} isn't valid ed25519 key: {}", field, value, err)))?;
+
+            let idx = match nodes.iter().position(|node| node.dest == gen_txn.dest) {
+                Some(idx) => {
+                    nodes[idx].merge(gen_txn);
+                    idx
+                }
+                None => {
+                    nodes.push(gen_txn);
+                    nodes.len() - 1
+                }
+            };
+            let node = &nodes[idx];
+            if nodes.iter().any(|other| other.dest != node.dest && other.data.alias == node.data.alias) {
+                return Err(invalid(format!("duplicated node alias {:?}", node.data.alias)));
+            }
+            PoolService::_validate_address(&node.data.client_ip, &node.data.client_port)
+                .map_err(|msg| invalid(format!("node {:?} has invalid client address: {}", node.data.alias, msg)))?;
+            PoolService::_validate_address(&node.data.node_ip, &node.data.node_port)
+                .map_err(|msg| invalid(format!("node {:?} has invalid node address: {}", node.data.alias, msg)))?;
+        }
+
+        let active_cnt = nodes.iter().filter(|node| node.is_active()).count();
+        if active_cnt < MIN_ACTIVE_NODES_CNT {
+            return Err(PoolError::InvalidData(format!("Genesis txns contain {} active validator nodes, at least {} expected",
+                                                      active_cnt, MIN_ACTIVE_NODES_CNT)));
+        }
+        Ok(())
+    }
+
+    fn _validate_address(ip: &Option<String>, port: &Option<u32>) -> Result<(), String> {
+        if let &Some(ref ip) = ip {
+            ip.parse::<IpAddr>().map_err(|_| format!("{:?} isn't valid IP", ip))?;
+        }
+        if let &Some(port) = port {
+            if port == 0 || port > 65535 {
+                return Err(format!("{} isn't valid port", port));
+            }
+        }
+        Ok(())
+    }
+
     pub fn open(&self, name: &str, config: Option<&str>) -> Result<i32, PoolError> {
         for pool in self.pools.try_borrow()?.values() {
             if name.eq(pool.name.as_str()) {
@@ -463,28 +1134,112 @@ impl PoolService {
             }
         }
 
+        let open_config = match config {
+            Some(config) => PoolOpenConfig::from_json(config)?,
+            None => PoolOpenConfig::default()
+        };
+        let client_keys = PoolService::_load_or_create_client_keys(
+            name, open_config.client_identity.as_ref().map(String::as_str).unwrap_or(DEFAULT_CLIENT_IDENTITY))?;
+
+        let replica = match open_config.domain_replica {
+            Some(true) => {
+                let max_age = Duration::from_secs(open_config.replica_max_age.unwrap_or(DEFAULT_REPLICA_MAX_AGE_SECS));
+                Some(DomainReplica::restore(name, max_age)?)
+            }
+            _ => None
+        };
+
         let cmd_id: i32 = SequenceUtils::get_next_id();
-        let new_pool = Pool::new(name, cmd_id)?;
-        //FIXME process config: check None (use default), transfer to Pool instance
+        let mut new_pool = Pool::new(name, cmd_id, client_keys, replica)?;
+        new_pool.auth_checks = open_config.auth_checks.unwrap_or(false);
 
         self.pools.try_borrow_mut()?.insert(new_pool.id, new_pool);
         return Ok(cmd_id);
     }
 
+    /// Client key pair is generated once per pool and identity and reused
+    /// across connections so nodes see the same client on reconnect.
+    fn _load_or_create_client_keys(pool_name: &str, identity: &str) -> Result<ClientKeyPair, PoolError> {
+        let is_valid_char = |c: char| match c {
+            'a'...'z' | 'A'...'Z' | '0'...'9' | '_' | '-' => true,
+            _ => false
+        };
+        if identity.is_empty() || !identity.chars().all(is_valid_char) {
+            return Err(PoolError::InvalidConfiguration(format!("Invalid client identity {:?}", identity)));
+        }
+        let mut path = EnvironmentUtils::pool_path(pool_name);
+        if !path.as_path().exists() {
+            return Err(PoolError::NotCreated(format!("Pool {} isn't created", pool_name)));
+        }
+        path.push("client_keys");
+        path.push(identity);
+        path.set_extension("json");
+
+        if path.as_path().exists() {
+            let mut keys_json = String::new();
+            fs::File::open(path.as_path())?.read_to_string(&mut keys_json)?;
+            return ClientKeyPair::from_json(keys_json.as_str())
+                .map_err(|err| PoolError::InvalidData(format!("Invalid client keys for identity {}: {}", identity, err)));
+        }
+
+        let client_keys = ClientKeyPair::new()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Secret key must never be observable half-written or readable by others
+        let mut tmp_path = path.clone();
+        tmp_path.set_extension("json.tmp");
+        {
+            let mut f = PoolService::_create_private_file(tmp_path.as_path())?;
+            f.write_all(client_keys.to_json()?.as_bytes())?;
+            f.sync_all()?;
+        }
+        fs::rename(tmp_path, path)?;
+        Ok(client_keys)
+    }
+
+    #[cfg(unix)]
+    fn _create_private_file(path: &Path) -> Result<fs::File, io::Error> {
+        use std::os::unix::fs::OpenOptionsExt;
+        fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)
+    }
+
+    #[cfg(not(unix))]
+    fn _create_private_file(path: &Path) -> Result<fs::File, io::Error> {
+        fs::OpenOptions::new().write(true).create(true).truncate(true).open(path)
+    }
+
     pub fn send_tx(&self, handle: i32, json: &str) -> Result<i32, PoolError> {
         let cmd_id: i32 = SequenceUtils::get_next_id();
         self.pools.try_borrow()?
             .get(&handle).ok_or(PoolError::InvalidHandle("No pool with requested handle".to_string()))?
-            .send_tx(cmd_id, json);
+            .send_tx(cmd_id, json)?;
         Ok(cmd_id)
     }
 
-    pub fn close(&self, handle: i32) -> Result<(), PoolError> {
-        unimplemented!()
+    pub fn get_status(&self, handle: i32) -> Result<String, PoolError> {
+        let status = self.pools.try_borrow()?
+            .get(&handle).ok_or(PoolError::InvalidHandle("No pool with requested handle".to_string()))?
+            .get_status();
+        Ok(status.to_json()?)
     }
 
-    pub fn refresh(&self, handle: i32) -> Result<(), PoolError> {
-        unimplemented!()
+    pub fn cancel_tx(&self, handle: i32, cmd_id: i32) -> Result<(), PoolError> {
+        self.pools.try_borrow()?
+            .get(&handle).ok_or(PoolError::InvalidHandle("No pool with requested handle".to_string()))?
+            .cancel_tx(cmd_id)
+    }
+
+    /// Dropped pool stops its worker and closes connections to the nodes
+    pub fn close(&self, handle: i32) -> Result<(), PoolError> {
+        let pool = self.pools.try_borrow_mut()?
+            .remove(&handle).ok_or(PoolError::InvalidHandle("No pool with requested handle".to_string()))?;
+        mem::drop(pool);
+        Ok(())
+    }
+
+    pub fn refresh(&self, handle: i32) -> Result<(), PoolError> {
+        unimplemented!()
     }
 
     pub fn get_pool_name(&self, handle: i32) -> Result<String, PoolError> {
@@ -492,6 +1247,12 @@ impl PoolService {
             Err(PoolError::InvalidHandle("Doesn't exists".to_string())),
             |pool: &Pool| Ok(pool.name.clone()))
     }
+
+    pub fn auth_checks(&self, handle: i32) -> Result<bool, PoolError> {
+        self.pools.try_borrow()?.get(&handle).map_or(
+            Err(PoolError::InvalidHandle("Doesn't exists".to_string())),
+            |pool: &Pool| Ok(pool.auth_checks))
+    }
 }
 
 #[cfg(test)]
@@ -549,6 +1310,10 @@ mod mocks {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::catchup::CATCHUP_REP_TIMEOUT_MS;
+    use super::transport::{ChannelCommandSender, ChannelNode, ChannelTransport};
+    use std::time::{Duration, Instant};
+    use utils::crypto::hash::Hash;
 
     #[test]
     fn pool_service_new_works() {
@@ -566,34 +1331,242 @@ mod tests {
         assert!(true, "No crashes on PoolService::drop");
     }
 
+    #[test]
+    fn pool_service_validate_genesis_txns_works() {
+        let txns = format!("{}\n{}\n{}\n{}\n",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"192.168.1.35\",\"client_port\":9704,\"node_ip\":\"192.168.1.35\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node3\",\"client_ip\":\"192.168.1.35\",\"client_port\":9706,\"node_ip\":\"192.168.1.35\",\"node_port\":9705,\"services\":[\"VALIDATOR\"]},\"dest\":\"DKVxG2fXXTU8yT5N7hGEbXB3dfdAnYv1JczDUHpmDxya\",\"identifier\":\"2yAeV5ftuasWNgQwVYzeHeTuM7LwwNtPR3Zg9N4JiDgF\",\"txnId\":\"7e9f355dffa78ed24668f0e0e369fd8c224076571c51e2ea8be5f26479edebe4\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node4\",\"client_ip\":\"192.168.1.35\",\"client_port\":9708,\"node_ip\":\"192.168.1.35\",\"node_port\":9707,\"services\":[\"VALIDATOR\"]},\"dest\":\"4PS3EDQ3dW1tci1Bp6543CfuuebjFrg36kLAUcskGfaA\",\"identifier\":\"FTE95CVthRtrBnK2PYCBbC9LghTcGwi9Zfi1Gz2dnyNx\",\"txnId\":\"aa5e817d7cc626170eca175822029339a444eb0ee8f0bd20d3b0b76e566fb008\",\"type\":\"0\"}");
+
+        let res = PoolService::_validate_genesis_txns(txns.as_bytes());
+
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn pool_service_validate_genesis_txns_works_for_invalid_json() {
+        let txns = format!("{}\n{}\n",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}",
+                               "{\"data\":");
+
+        let res = PoolService::_validate_genesis_txns(txns.as_bytes());
+
+        match res {
+            Err(PoolError::InvalidData(ref msg)) => assert!(msg.contains("line 2"), "unexpected message {}", msg),
+            _ => panic!("Unexpected result {:?}", res),
+        };
+    }
+
+    #[test]
+    fn pool_service_validate_genesis_txns_works_for_invalid_dest() {
+        let txns = format!("{}\n{}\n",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"192.168.1.35\",\"client_port\":9704,\"node_ip\":\"192.168.1.35\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQts\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}");
+
+        let res = PoolService::_validate_genesis_txns(txns.as_bytes());
+
+        match res {
+            Err(PoolError::InvalidData(ref msg)) => assert!(msg.contains("line 2"), "unexpected message {}", msg),
+            _ => panic!("Unexpected result {:?}", res),
+        };
+    }
+
+    #[test]
+    fn pool_service_validate_genesis_txns_works_for_duplicated_alias() {
+        let txns = format!("{}\n{}\n",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9704,\"node_ip\":\"192.168.1.35\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}");
+
+        let res = PoolService::_validate_genesis_txns(txns.as_bytes());
+
+        match res {
+            Err(PoolError::InvalidData(ref msg)) => assert!(msg.contains("duplicated node alias"), "unexpected message {}", msg),
+            _ => panic!("Unexpected result {:?}", res),
+        };
+    }
+
+    #[test]
+    fn pool_service_validate_genesis_txns_works_for_invalid_ip() {
+        let txns = format!("{}\n{}\n",
+                               "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"192.168.1\",\"client_port\":9704,\"node_ip\":\"192.168.1.35\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}");
+
+        let res = PoolService::_validate_genesis_txns(txns.as_bytes());
+
+        match res {
+            Err(PoolError::InvalidData(ref msg)) => assert!(msg.contains("line 1"), "unexpected message {}", msg),
+            _ => panic!("Unexpected result {:?}", res),
+        };
+    }
+
+    #[test]
+    fn pool_service_validate_genesis_txns_works_for_no_active_nodes() {
+        let txns = format!("{}\n{}\n",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node1\",\"services\":[]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"1\",\"type\":\"0\"}");
+
+        let res = PoolService::_validate_genesis_txns(txns.as_bytes());
+
+        match res {
+            Err(PoolError::InvalidData(ref msg)) => assert!(msg.contains("0 active validator nodes"), "unexpected message {}", msg),
+            _ => panic!("Unexpected result {:?}", res),
+        };
+    }
+
+    #[test]
+    fn pool_service_validate_genesis_txns_works_for_not_enough_active_nodes() {
+        let txns = format!("{}\n{}\n{}\n",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"192.168.1.35\",\"client_port\":9704,\"node_ip\":\"192.168.1.35\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}",
+                               "{\"data\":{\"alias\":\"Node3\",\"client_ip\":\"192.168.1.35\",\"client_port\":9706,\"node_ip\":\"192.168.1.35\",\"node_port\":9705,\"services\":[\"VALIDATOR\"]},\"dest\":\"DKVxG2fXXTU8yT5N7hGEbXB3dfdAnYv1JczDUHpmDxya\",\"identifier\":\"2yAeV5ftuasWNgQwVYzeHeTuM7LwwNtPR3Zg9N4JiDgF\",\"txnId\":\"7e9f355dffa78ed24668f0e0e369fd8c224076571c51e2ea8be5f26479edebe4\",\"type\":\"0\"}");
+
+        let res = PoolService::_validate_genesis_txns(txns.as_bytes());
+
+        match res {
+            Err(PoolError::InvalidData(ref msg)) => assert!(msg.contains("3 active validator nodes, at least 4 expected"), "unexpected message {}", msg),
+            _ => panic!("Unexpected result {:?}", res),
+        };
+    }
+
+    #[test]
+    fn pool_service_validate_genesis_txns_works_for_invalid_verkey() {
+        let txns = format!("{}\n",
+                               "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"verkey\":\"invalid_verkey\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}");
+
+        let res = PoolService::_validate_genesis_txns(txns.as_bytes());
+
+        match res {
+            Err(PoolError::InvalidData(ref msg)) => assert!(msg.contains("verkey \"invalid_verkey\""), "unexpected message {}", msg),
+            _ => panic!("Unexpected result {:?}", res),
+        };
+    }
+
+    #[test]
+    fn pool_service_load_or_create_client_keys_works() {
+        let pool_name = "test_client_keys";
+        let path = EnvironmentUtils::pool_path(pool_name);
+        fs::create_dir_all(path.as_path()).unwrap();
+
+        let keys = PoolService::_load_or_create_client_keys(pool_name, DEFAULT_CLIENT_IDENTITY).unwrap();
+        let same_keys = PoolService::_load_or_create_client_keys(pool_name, DEFAULT_CLIENT_IDENTITY).unwrap();
+        let other_keys = PoolService::_load_or_create_client_keys(pool_name, "other_identity").unwrap();
+
+        assert_eq!(keys, same_keys);
+        assert_ne!(keys, other_keys);
+        fs::remove_dir_all(path.as_path()).unwrap();
+    }
+
+    #[test]
+    fn pool_service_load_or_create_client_keys_works_for_reopen() {
+        let pool_name = "test_client_keys_reopen";
+        let path = EnvironmentUtils::pool_path(pool_name);
+        let _ = fs::remove_dir_all(path.as_path());
+        fs::create_dir_all(path.as_path()).unwrap();
+
+        let keys = PoolService::_load_or_create_client_keys(pool_name, DEFAULT_CLIENT_IDENTITY).unwrap();
+
+        let mut keys_path = path.clone();
+        keys_path.push("client_keys");
+        keys_path.push(DEFAULT_CLIENT_IDENTITY);
+        keys_path.set_extension("json");
+        assert!(keys_path.exists());
+        assert!(!keys_path.with_extension("json.tmp").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(keys_path.as_path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let reopened_keys = PoolService::_load_or_create_client_keys(pool_name, DEFAULT_CLIENT_IDENTITY).unwrap();
+
+        assert_eq!(keys, reopened_keys);
+        fs::remove_dir_all(path.as_path()).unwrap();
+    }
+
+    #[test]
+    fn pool_service_load_or_create_client_keys_works_for_invalid_identity() {
+        let pool_name = "test_client_keys_invalid_identity";
+        let path = EnvironmentUtils::pool_path(pool_name);
+        fs::create_dir_all(path.as_path()).unwrap();
+
+        let res = PoolService::_load_or_create_client_keys(pool_name, "../other");
+
+        assert_match!(Err(PoolError::InvalidConfiguration(_)), res);
+        fs::remove_dir_all(path.as_path()).unwrap();
+    }
+
     #[test]
     fn pool_send_tx_works() {
         let name = "test";
-        let zmq_ctx = zmq::Context::new();
-        let recv_cmd_sock = zmq_ctx.socket(zmq::SocketType::PAIR).unwrap();
-        let send_cmd_sock = zmq_ctx.socket(zmq::SocketType::PAIR).unwrap();
-        let inproc_sock_name: String = format!("inproc://pool_{}", name);
-        recv_cmd_sock.bind(inproc_sock_name.as_str()).unwrap();
-        send_cmd_sock.connect(inproc_sock_name.as_str()).unwrap();
+        let (cmd_sender, mut transport) = ChannelTransport::new();
         let pool = Pool {
             worker: Some(thread::spawn(|| { Ok(()) })),
             name: name.to_string(),
             id: 0,
-            cmd_sock: send_cmd_sock,
+            cmd_sender: Box::new(cmd_sender),
+            status: Arc::new(Mutex::new(PoolStatus::new())),
+            auth_checks: false,
         };
         let test_data = "str_instead_of_tx_json";
-        pool.send_tx(0, test_data);
-        assert_eq!(recv_cmd_sock.recv_string(zmq::DONTWAIT).unwrap().unwrap(), test_data);
+        pool.send_tx(0, test_data).unwrap();
+        let events = transport.poll(0).unwrap();
+        assert_eq!(events.len(), 1);
+        match events[0] {
+            TransportEvent::Command(ref cmd) => {
+                assert_eq!(cmd[0], CMD_REQUEST.as_bytes());
+                assert_eq!(cmd[2], test_data.as_bytes());
+            }
+            ref event => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn pool_worker_parse_cmd_works_for_request_with_cancel_body() {
+        let mut buf = [0u8; 4];
+        LittleEndian::write_i32(&mut buf, 7);
+
+        let action = PoolWorker::parse_cmd(vec![CMD_REQUEST.as_bytes().to_vec(), buf.to_vec(), "cancel".as_bytes().to_vec()]).unwrap();
+        assert_eq!(action, ZMQLoopAction::RequestToSend(RequestToSend { id: 7, request: "cancel".to_string() }));
+
+        let action = PoolWorker::parse_cmd(vec![CMD_CANCEL.as_bytes().to_vec(), buf.to_vec()]).unwrap();
+        assert_eq!(action, ZMQLoopAction::CancelRequest(7));
+    }
+
+    #[test]
+    fn pool_worker_parse_cmd_works_for_invalid_format() {
+        let res = PoolWorker::parse_cmd(vec!["{}".as_bytes().to_vec(), vec![0u8; 4]]);
+        assert_match!(Err(PoolError::InvalidState(_)), res);
+
+        let res = PoolWorker::parse_cmd(vec![CMD_CANCEL.as_bytes().to_vec(), vec![0u8; 3]]);
+        assert_match!(Err(PoolError::InvalidState(_)), res);
+    }
+
+    impl PoolWorker {
+        fn _build_node_state(txns: &[String]) -> Result<Vec<GenTransaction>, PoolError> {
+            let mut nodes: Vec<GenTransaction> = Vec::new();
+            for gen_txn in txns {
+                PoolWorker::_merge_node_txn(&mut nodes, gen_txn)?;
+            }
+            Ok(nodes)
+        }
     }
 
     impl Default for PoolWorker {
         fn default() -> Self {
             PoolWorker {
                 pool_id: 0,
-                cmd_sock: zmq::Context::new().socket(zmq::SocketType::PAIR).unwrap(),
+                client_keys: ClientKeyPair::new().unwrap(),
+                transport: Box::new(ChannelTransport::new().1),
                 open_cmd_id: 0,
                 name: "".to_string(),
+                pool_ledger: None,
+                node_state: Vec::new(),
                 handler: PoolWorkerHandler::CatchupHandler(Default::default()),
+                status: Arc::new(Mutex::new(PoolStatus::new())),
+                replica: None,
+                replica_refresh: None,
             }
         }
     }
@@ -602,11 +1575,13 @@ mod tests {
         fn default() -> Self {
             CatchupHandler {
                 f: 0,
-                ledger_status_same: 0,
-                merkle_tree: MerkleTree::from_vec(Vec::new()).unwrap(),
+                ledger_id: POOL_LEDGER_ID,
+                ledger_status_same: HashSet::new(),
+                merkle_tree: MerkleTree::new().unwrap(),
                 nodes: Vec::new(),
                 new_mt_size: 0,
-                new_mt_vote: 0,
+                new_mt_root: Vec::new(),
+                new_mt_votes: HashMap::new(),
                 pending_catchup: None,
             }
         }
@@ -622,6 +1597,18 @@ mod tests {
         }
     }
 
+    fn _channel_pool_worker(gen_txns: &[GenTransaction]) -> (ChannelCommandSender, PoolWorker, Vec<ChannelNode>) {
+        let (cmd_sender, mut transport) = ChannelTransport::new();
+        let nodes = gen_txns.iter()
+            .map(|gt| transport.add_node(RemoteNode::new(gt.clone()).unwrap().zaddr.as_str()))
+            .collect();
+        let pw = PoolWorker {
+            transport: Box::new(transport),
+            ..Default::default()
+        };
+        (cmd_sender, pw, nodes)
+    }
+
     #[test]
     fn pool_worker_restore_merkle_tree_works_from_genesis_txns() {
         let txns_src = format!("{}\n{}\n{}\n{}\n",
@@ -639,56 +1626,275 @@ mod tests {
         f.flush().unwrap();
         f.sync_all().unwrap();
 
-        let merkle_tree = PoolWorker::_restore_merkle_tree("test").unwrap();
+        fs::remove_file(path.with_extension("mt")).ok();
+        let storage = PoolWorker::_pool_ledger_storage("test").unwrap();
+        let merkle_tree = storage.restore_tree().unwrap();
 
         assert_eq!(merkle_tree.count(), 4, "test restored MT size");
         assert_eq!(merkle_tree.root_hash_hex(), "1285070cf01debc1155cef8dfd5ba54c05abb919a4c08c8632b079fb1e1e5e7c", "test restored MT root hash");
+        let reloaded = PoolWorker::_pool_ledger_storage("test").unwrap().restore_tree().unwrap();
+        assert_eq!(reloaded, merkle_tree, "test reloaded MT");
     }
 
     #[test]
     fn pool_worker_connect_to_known_nodes_works() {
-        let mut pw: PoolWorker = Default::default();
-        let (gt, handle) = nodes_emulator::start();
-        let mut merkle_tree: MerkleTree = MerkleTree::from_vec(Vec::new()).unwrap();
-        merkle_tree.append(gt.to_json().unwrap()).unwrap();
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, nodes) = _channel_pool_worker(&[gt.clone()]);
+        let txns = vec![gt.to_json().unwrap()];
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+
+        assert_eq!(pw.handler.nodes().len(), 1);
+        assert_eq!("pi", nodes[0].received.try_recv().unwrap());
+        assert!(nodes[0].received.try_recv().is_err());
+    }
+
+    #[test]
+    fn pool_worker_connect_to_known_nodes_works_for_status() {
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, _nodes) = _channel_pool_worker(&[gt.clone()]);
+        let txns = vec![gt.to_json().unwrap()];
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+
+        let status = pw.update_status(|_| {});
+        assert_eq!(status.phase, PoolPhase::CatchingUp);
+        assert_eq!(status.ledger_size, 1);
+        assert_eq!(status.root_hash, merkle_tree.root_hash().as_slice().to_base58());
+        assert_eq!(status.f, 0);
+        assert_eq!(status.nodes, vec![NodeStatus {
+            alias: gt.data.alias.clone(),
+            state: NodeConnectionState::Connecting,
+        }]);
+    }
+
+    #[test]
+    fn pool_worker_poll_transport_works_for_terminate() {
+        let (cmd_sender, mut pw, _nodes) = _channel_pool_worker(&[]);
+
+        cmd_sender.send(&[CMD_EXIT.as_bytes()]).unwrap();
+        let actions: Vec<ZMQLoopAction> = pw.poll_transport().unwrap();
+
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0], ZMQLoopAction::Terminate);
+    }
+
+    #[test]
+    fn pool_worker_poll_transport_works_for_node_message() {
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, nodes) = _channel_pool_worker(&[gt.clone()]);
+        let txns = vec![gt.to_json().unwrap()];
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+
+        nodes[0].sender.send("po".to_string()).unwrap();
+        let actions: Vec<ZMQLoopAction> = pw.poll_transport().unwrap();
+
+        assert_eq!(actions, vec![ZMQLoopAction::MessageToProcess(MessageToProcess {
+            node_idx: 0,
+            message: "po".to_string(),
+        })]);
+        assert_eq!(pw.update_status(|_| {}).nodes[0].state, NodeConnectionState::Connected);
+    }
 
-        pw.connect_to_known_nodes(Some(&merkle_tree)).unwrap();
+    #[test]
+    fn pool_worker_poll_transport_works_for_send_error() {
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, mut nodes) = _channel_pool_worker(&[gt.clone()]);
+        let txns = vec![gt.to_json().unwrap()];
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+        nodes.clear();
+
+        assert!(pw.handler.nodes()[0].send_str(pw.transport.as_ref(), "pi").is_err());
+        let actions: Vec<ZMQLoopAction> = pw.poll_transport().unwrap();
+
+        assert!(actions.is_empty());
+        assert_eq!(pw.update_status(|_| {}).nodes[0].state, NodeConnectionState::Unreachable);
+    }
 
-        let emulator_msgs: Vec<String> = handle.join().unwrap();
-        assert_eq!(1, emulator_msgs.len());
-        assert_eq!("pi", emulator_msgs[0]);
+    #[test]
+    fn pool_worker_check_nodes_liveness_works_for_silent_node() {
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, nodes) = _channel_pool_worker(&[gt.clone()]);
+        let txns = vec![gt.to_json().unwrap()];
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+        assert_eq!("pi", nodes[0].received.try_recv().unwrap());
+
+        pw.check_nodes_liveness();
+        assert!(nodes[0].received.try_recv().is_err());
+
+        pw.handler.nodes_mut()[0].last_seen = Instant::now() - Duration::from_millis(PING_INTERVAL_MS);
+        assert_eq!(pw.get_timeout(), 0);
+        pw.check_nodes_liveness();
+        assert_eq!("pi", nodes[0].received.try_recv().unwrap());
+        assert!(pw.get_timeout() > 0);
+
+        pw.handler.nodes_mut()[0].pinged_at = Some(Instant::now() - Duration::from_millis(PONG_TIMEOUT_MS));
+        pw.check_nodes_liveness();
+        assert_eq!("pi", nodes[0].received.try_recv().unwrap());
+        assert_eq!(pw.update_status(|_| {}).nodes[0].state, NodeConnectionState::Unreachable);
+
+        nodes[0].sender.send("po".to_string()).unwrap();
+        let actions: Vec<ZMQLoopAction> = pw.poll_transport().unwrap();
+
+        assert!(actions.is_empty());
+        assert!(pw.handler.nodes()[0].pinged_at.is_none());
+        assert_eq!(pw.update_status(|_| {}).nodes[0].state, NodeConnectionState::Connected);
     }
 
     #[test]
-    fn pool_worker_poll_zmq_works_for_terminate() {
-        let ctx = zmq::Context::new();
-        let mut pw = PoolWorker {
-            cmd_sock: ctx.socket(zmq::SocketType::PAIR).expect("socket"),
+    fn pool_worker_catchup_works_for_same_ledger() {
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, nodes) = _channel_pool_worker(&[gt.clone()]);
+        let txns = vec![gt.to_json().unwrap()];
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+        pw.handler = PoolWorkerHandler::CatchupHandler(CatchupHandler {
+            merkle_tree: merkle_tree.clone(),
             ..Default::default()
+        });
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+        assert_eq!("pi", nodes[0].received.try_recv().unwrap());
+
+        nodes[0].sender.send("po".to_string()).unwrap();
+        assert_eq!(pw.poll_transport().unwrap().len(), 1);
+        let res = pw.handler.process_msg(pw.transport.as_ref(), &"po".to_string(), 0).unwrap();
+        assert!(res.is_none());
+
+        let ledger_status = nodes[0].received.try_recv().unwrap();
+        match Message::from_json(ledger_status.as_str()).unwrap() {
+            Message::LedgerStatus(ls) => {
+                assert_eq!(ls.txnSeqNo, 1);
+                assert_eq!(ls.merkleRoot, merkle_tree.root_hash().as_slice().to_base58());
+            }
+            msg => panic!("Unexpected message {:?}", msg),
+        }
+
+        let res = pw.handler.process_msg(pw.transport.as_ref(), &ledger_status, 0).unwrap();
+
+        let update = res.unwrap();
+        assert_eq!(update.merkle_tree.root_hash(), merkle_tree.root_hash());
+        assert!(update.txns.is_empty());
+    }
+
+    #[test]
+    fn pool_worker_finish_catchup_works_for_domain_replica() {
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, nodes) = _channel_pool_worker(&[gt.clone()]);
+        let txns = vec![gt.to_json().unwrap()];
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+        pw.handler = PoolWorkerHandler::CatchupHandler(CatchupHandler {
+            merkle_tree: merkle_tree.clone(),
+            ..Default::default()
+        });
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+        assert_eq!("pi", nodes[0].received.try_recv().unwrap());
+        pw.replica = Some(DomainReplica::restore("pool_worker_finish_catchup_works_for_domain_replica",
+                                                 Duration::from_secs(300)).unwrap());
+
+        pw.finish_catchup(LedgerUpdate {
+            merkle_tree: merkle_tree,
+            txns: Vec::new(),
+        }).unwrap();
+
+        match pw.handler {
+            PoolWorkerHandler::CatchupHandler(ref ch) => {
+                assert_eq!(ch.ledger_id, DOMAIN_LEDGER_ID);
+                assert_eq!(ch.nodes.len(), 1);
+            }
+            PoolWorkerHandler::TransactionHandler(_) => panic!("Domain ledger catchup isn't started"),
+        }
+        match Message::from_json(nodes[0].received.try_recv().unwrap().as_str()).unwrap() {
+            Message::LedgerStatus(ls) => {
+                assert_eq!(ls.ledgerId, DOMAIN_LEDGER_ID);
+                assert_eq!(ls.txnSeqNo, 0);
+            }
+            msg => panic!("Unexpected message {:?}", msg),
+        }
+    }
+
+    fn _replica_pool_worker(pool_name: &str, txns: &[&str]) -> (PoolWorker, Vec<ChannelNode>) {
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, nodes) = _channel_pool_worker(&[gt.clone()]);
+        pw.node_state = PoolWorker::_build_node_state(&[gt.to_json().unwrap()]).unwrap();
+        pw.handler = PoolWorkerHandler::TransactionHandler(Default::default());
+        pw.connect_to_known_nodes(&MerkleTree::new().unwrap()).unwrap();
+        assert_eq!("pi", nodes[0].received.try_recv().unwrap());
+
+        fs::remove_dir_all(EnvironmentUtils::pool_path(pool_name)).ok();
+        let mut replica = DomainReplica::restore(pool_name, Duration::from_secs(300)).unwrap();
+        if !txns.is_empty() {
+            let txns: Vec<String> = txns.iter().map(|txn| txn.to_string()).collect();
+            replica.update(MerkleTree::from_vec(txns.clone()).unwrap(), txns).unwrap();
+        }
+        pw.replica = Some(replica);
+        (pw, nodes)
+    }
+
+    #[test]
+    fn pool_worker_check_replica_refresh_works_for_stale_replica() {
+        let (mut pw, nodes) = _replica_pool_worker("pool_worker_check_replica_refresh_works_for_stale_replica", &[]);
+        assert_eq!(pw.get_timeout(), 0);
+
+        pw.check_replica_refresh();
+
+        assert!(pw.replica_refresh.is_some());
+        assert_eq!(pw.update_status(|_| {}).phase, PoolPhase::Refreshing);
+        assert!(pw.get_timeout() > 0);
+        let root = match Message::from_json(nodes[0].received.try_recv().unwrap().as_str()).unwrap() {
+            Message::LedgerStatus(ls) => {
+                assert_eq!(ls.ledgerId, DOMAIN_LEDGER_ID);
+                assert_eq!(ls.txnSeqNo, 0);
+                ls.merkleRoot
+            }
+            msg => panic!("Unexpected message {:?}", msg),
         };
-        let pair_socket_addr = "inproc://test_pool_worker_poll_zmq_works_for_terminate";
-        let send_cmd_sock = ctx.socket(zmq::SocketType::PAIR).expect("socket");
-        pw.cmd_sock.bind(pair_socket_addr).expect("bind");
-        send_cmd_sock.connect(pair_socket_addr).expect("connect");
 
-        let handle: thread::JoinHandle<Vec<ZMQLoopAction>> = thread::spawn(move || {
-            pw.poll_zmq()
+        let ls = Message::LedgerStatus(LedgerStatus {
+            txnSeqNo: 0,
+            merkleRoot: root,
+            ledgerId: DOMAIN_LEDGER_ID,
         });
-        send_cmd_sock.send_str("exit", zmq::DONTWAIT).expect("send");
-        let actions: Vec<ZMQLoopAction> = handle.join().unwrap();
+        pw.process_msg(&ls.to_json().unwrap(), 0).unwrap();
 
-        assert_eq!(actions.len(), 1);
-        assert_eq!(actions[0], ZMQLoopAction::Terminate);
+        assert!(pw.replica_refresh.is_none());
+        assert_eq!(pw.update_status(|_| {}).phase, PoolPhase::Ready);
+        assert!(pw.replica.as_ref().unwrap().is_fresh());
+        assert!(pw.get_timeout() > 0);
+    }
+
+    #[test]
+    fn pool_worker_check_replica_refresh_works_for_fresh_replica() {
+        let (mut pw, nodes) = _replica_pool_worker("pool_worker_check_replica_refresh_works_for_fresh_replica", &[]);
+        pw.replica.as_mut().unwrap().update(MerkleTree::new().unwrap(), Vec::new()).unwrap();
+
+        pw.check_replica_refresh();
+
+        assert!(pw.replica_refresh.is_none());
+        assert!(nodes[0].received.try_recv().is_err());
     }
 
     #[test]
-    fn pool_worker_get_zmq_poll_items_works() {
-        let pw: PoolWorker = Default::default();
+    fn pool_worker_process_msg_works_for_reply_invalidating_replica() {
+        let nym_txn = r#"{"dest":"Th7MpTaRZVRYnPiabds81Y","identifier":"V4SGRU86Z58d6TV7PBUe6f","type":"1","verkey":"~7TYfekw4GUagBnBVCqPjiC"}"#;
+        let (mut pw, _nodes) = _replica_pool_worker("pool_worker_process_msg_works_for_reply_invalidating_replica", &[nym_txn]);
+        let get_nym = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"dest":"Th7MpTaRZVRYnPiabds81Y","type":"105"},"reqId":1}"#;
+        assert!(pw.replica.as_ref().unwrap().try_answer(get_nym).is_some());
 
-        let poll_items = pw.get_zmq_poll_items();
+        let reply = r#"{"op":"REPLY","result":{"dest":"Th7MpTaRZVRYnPiabds81Y","identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":2,"seqNo":2,"type":"1","verkey":"~NcYxiDXkpYi6ov5FcYDi1e"}}"#;
+        pw.process_msg(&reply.to_string(), 0).unwrap();
 
-        assert_eq!(poll_items.len(), pw.handler.nodes().len() + 1 );
-        //TODO compare poll items
+        assert!(pw.replica.as_ref().unwrap().try_answer(get_nym).is_none());
     }
 
     #[test]
@@ -702,24 +1908,147 @@ mod tests {
     }
 
     #[test]
-    fn transaction_handler_process_reply_works() {
-        let mut th: TransactionHandler = Default::default();
-        th.f = 1;
-        let pc = super::types::CommandProcess {
-            cmd_ids: Vec::new(),
-            reply_cnt: th.f,
-            nack_cnt: 0,
-        };
-        let req_id = 1;
-        th.pending_commands.insert(req_id, pc);
-        let reply = super::types::Reply {
-            result: super::types::Response {
+    fn pool_worker_build_node_state_works_for_node_update() {
+        let mut txns: Vec<String> = Vec::new();
+        txns.push("{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}".to_string());
+        txns.push("{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"192.168.1.35\",\"client_port\":9704,\"node_ip\":\"192.168.1.35\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}".to_string());
+        txns.push("{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.36\",\"client_port\":9712},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"1\",\"type\":\"0\"}".to_string());
+
+        let node_state = PoolWorker::_build_node_state(&txns).unwrap();
+
+        assert_eq!(node_state.len(), 2);
+        assert_eq!(node_state[0].data.alias, "Node1");
+        assert_eq!(node_state[0].data.client_ip, Some("192.168.1.36".to_string()));
+        assert_eq!(node_state[0].data.client_port, Some(9712));
+        assert_eq!(node_state[0].data.node_port, Some(9701));
+        assert!(node_state[0].is_active());
+        assert!(node_state[1].is_active());
+    }
+
+    #[test]
+    fn pool_worker_build_node_state_works_for_demoted_node() {
+        let mut txns: Vec<String> = Vec::new();
+        txns.push("{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"192.168.1.35\",\"client_port\":9702,\"node_ip\":\"192.168.1.35\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}".to_string());
+        txns.push("{\"data\":{\"alias\":\"Node1\",\"services\":[]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"1\",\"type\":\"0\"}".to_string());
+
+        let node_state = PoolWorker::_build_node_state(&txns).unwrap();
+
+        assert_eq!(node_state.len(), 1);
+        assert!(!node_state[0].is_active());
+    }
+
+    #[test]
+    fn pool_worker_connect_to_known_nodes_works_for_demoted_node() {
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        let (_cmd_sender, mut pw, nodes) = _channel_pool_worker(&[gt.clone()]);
+        let mut demote_txn = gt.clone();
+        demote_txn.data.services = Some(Vec::new());
+        let txns = vec![gt.to_json().unwrap(), demote_txn.to_json().unwrap()];
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+
+        assert_eq!(pw.handler.nodes().len(), 0);
+        assert!(nodes[0].received.try_recv().is_err());
+    }
+
+    #[test]
+    fn pool_worker_connect_to_known_nodes_works_for_unreachable_node() {
+        let gen_txns: Vec<GenTransaction> = (1..5).map(|i| nodes_emulator::gen_txn(&format!("n{}", i), 9700 + i)).collect();
+        let (_cmd_sender, mut pw, _nodes) = _channel_pool_worker(&gen_txns[..3]);
+        let txns: Vec<String> = gen_txns.iter().map(|gt| gt.to_json().unwrap()).collect();
+        pw.node_state = PoolWorker::_build_node_state(&txns).unwrap();
+        let merkle_tree = MerkleTree::from_vec(txns).unwrap();
+
+        pw.connect_to_known_nodes(&merkle_tree).unwrap();
+
+        assert_eq!(pw.handler.nodes().len(), 3);
+        assert_eq!(pw.update_status(|_| {}).f, 1);
+    }
+
+    const IDENTIFIER: &'static str = "Th7MpTaRZVRYnPiabds81Y";
+
+    fn _reply(identifier: &str, req_id: u64, data: &str) -> (Reply, String) {
+        let reply = Reply {
+            result: Response {
                 req_id: req_id,
+                identifier: identifier.to_string(),
             },
         };
+        let raw_msg = format!(r#"{{"op":"REPLY","result":{{"data":"{}","identifier":"{}","reqId":{}}}}}"#, data, identifier, req_id);
+        (reply, raw_msg)
+    }
+
+    fn _transaction_handler_with_pending_request(f: usize, req_id: u64) -> TransactionHandler {
+        let mut th: TransactionHandler = Default::default();
+        th.f = f;
+        th.pending_commands.insert((IDENTIFIER.to_string(), req_id), CommandProcess {
+            cmd_ids: Vec::new(),
+            replies: HashMap::new(),
+            nacks: HashSet::new(),
+        });
+        th
+    }
+
+    #[test]
+    fn transaction_handler_process_reply_works() {
+        let mut th = _transaction_handler_with_pending_request(1, 1);
+        let (reply, raw_msg) = _reply(IDENTIFIER, 1, "data");
+
+        th.process_reply(&reply, &raw_msg, 0).unwrap();
+        assert_eq!(th.pending_commands.len(), 1);
+
+        th.process_reply(&reply, &raw_msg, 1).unwrap();
+        assert_eq!(th.pending_commands.len(), 0);
+    }
+
+    #[test]
+    fn transaction_handler_process_reply_works_for_other_identifier() {
+        let mut th = _transaction_handler_with_pending_request(0, 1);
+        let (reply, raw_msg) = _reply("V4SGRU86Z58d6TV7PBUe6f", 1, "data");
+
+        th.process_reply(&reply, &raw_msg, 0).unwrap();
+
+        assert_eq!(th.pending_commands.len(), 1);
+    }
+
+    #[test]
+    fn transaction_handler_process_reply_works_for_repeated_reply_of_same_node() {
+        let mut th = _transaction_handler_with_pending_request(1, 1);
+        let (reply, raw_msg) = _reply(IDENTIFIER, 1, "data");
+
+        th.process_reply(&reply, &raw_msg, 0).unwrap();
+        th.process_reply(&reply, &raw_msg, 0).unwrap();
+
+        assert_eq!(th.pending_commands.len(), 1);
+    }
+
+    #[test]
+    fn transaction_handler_process_reply_works_for_different_results() {
+        let mut th = _transaction_handler_with_pending_request(1, 1);
+        let (reply, raw_msg) = _reply(IDENTIFIER, 1, "data");
+        let (byzantine_reply, byzantine_raw_msg) = _reply(IDENTIFIER, 1, "byzantine");
+
+        th.process_reply(&byzantine_reply, &byzantine_raw_msg, 0).unwrap();
+        th.process_reply(&reply, &raw_msg, 1).unwrap();
+        assert_eq!(th.pending_commands.len(), 1);
+
+        th.process_reply(&reply, &raw_msg, 2).unwrap();
+        assert_eq!(th.pending_commands.len(), 0);
+    }
+
+    #[test]
+    fn transaction_handler_process_msg_works_for_nacks_of_distinct_nodes() {
+        let mut th = _transaction_handler_with_pending_request(1, 1);
+        let nack = format!(r#"{{"op":"REQNACK","identifier":"{}","reqId":1,"reason":"invalid"}}"#, IDENTIFIER);
+        let reject = format!(r#"{{"op":"REJECT","identifier":"{}","reqId":1,"reason":"invalid"}}"#, IDENTIFIER);
 
-        th.process_reply(&reply, &"".to_string());
+        th.process_msg(Message::from_raw_str(&nack).unwrap(), &nack, 0).unwrap();
+        th.process_msg(Message::from_raw_str(&nack).unwrap(), &nack, 0).unwrap();
+        assert_eq!(th.pending_commands.len(), 1);
 
+        th.process_msg(Message::from_raw_str(&reject).unwrap(), &reject, 1).unwrap();
         assert_eq!(th.pending_commands.len(), 0);
     }
 
@@ -731,87 +2060,411 @@ mod tests {
         let cmd_id = 1;
         let req = SimpleRequest {
             req_id: req_id,
+            identifier: IDENTIFIER.to_string(),
+            protocol_version: PROTOCOL_VERSION,
         };
         let cmd = req.to_json().unwrap();
 
-        th.try_send_request(&cmd, cmd_id);
+        th.try_send_request(&ChannelTransport::new().1, &cmd, cmd_id).unwrap();
 
         assert_eq!(th.pending_commands.len(), 1);
-        let pending_cmd = th.pending_commands.get(&req_id).unwrap();
+        let pending_cmd = th.pending_commands.get(&(IDENTIFIER.to_string(), req_id)).unwrap();
         let exp_command_process = CommandProcess {
-            nack_cnt: 0,
-            reply_cnt: 0,
+            nacks: HashSet::new(),
+            replies: HashMap::new(),
             cmd_ids: vec!(cmd_id),
         };
         assert_eq!(pending_cmd, &exp_command_process);
     }
 
+    #[test]
+    fn transaction_handler_try_send_request_works_for_same_req_id_of_other_identifier() {
+        let mut th: TransactionHandler = Default::default();
+        let transport = ChannelTransport::new().1;
+
+        for (cmd_id, identifier) in vec![IDENTIFIER, "V4SGRU86Z58d6TV7PBUe6f", IDENTIFIER].into_iter().enumerate() {
+            let req = SimpleRequest {
+                req_id: 2,
+                identifier: identifier.to_string(),
+                protocol_version: PROTOCOL_VERSION,
+            };
+            th.try_send_request(&transport, &req.to_json().unwrap(), cmd_id as i32).unwrap();
+        }
+
+        assert_eq!(th.pending_commands.len(), 2);
+        assert_eq!(th.pending_commands.get(&(IDENTIFIER.to_string(), 2)).unwrap().cmd_ids, vec!(0, 2));
+        assert_eq!(th.pending_commands.get(&("V4SGRU86Z58d6TV7PBUe6f".to_string(), 2)).unwrap().cmd_ids, vec!(1));
+    }
+
+    #[test]
+    fn transaction_handler_parse_request_works() {
+        let cmd = format!(r#"{{"reqId":1,"identifier":"{}","operation":{{"type":"105"}},"protocolVersion":{}}}"#,
+                          IDENTIFIER, PROTOCOL_VERSION);
+
+        let request = TransactionHandler::parse_request(&cmd).unwrap();
+
+        assert_eq!(request.req_id, 1);
+        assert_eq!(request.identifier, IDENTIFIER);
+    }
+
+    #[test]
+    fn transaction_handler_parse_request_works_for_missed_envelope_fields() {
+        for cmd in vec![r#"{"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"105"},"protocolVersion":1}"#,
+                        r#"{"reqId":1,"operation":{"type":"105"},"protocolVersion":1}"#,
+                        r#"{"reqId":1,"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"105"}}"#] {
+            assert_match!(Err(PoolError::InvalidData(_)), TransactionHandler::parse_request(cmd));
+        }
+    }
+
+    #[test]
+    fn transaction_handler_parse_request_works_for_unsupported_protocol_version() {
+        let cmd = format!(r#"{{"reqId":1,"identifier":"{}","operation":{{"type":"105"}},"protocolVersion":{}}}"#,
+                          IDENTIFIER, PROTOCOL_VERSION + 1);
+
+        assert_match!(Err(PoolError::InvalidData(_)), TransactionHandler::parse_request(&cmd));
+    }
+
+    #[test]
+    fn transaction_handler_cancel_request_works() {
+        let mut th: TransactionHandler = Default::default();
+        let req_key = (IDENTIFIER.to_string(), 3);
+        th.pending_commands.insert(req_key.clone(), CommandProcess {
+            nacks: HashSet::new(),
+            replies: HashMap::new(),
+            cmd_ids: vec!(1, 2),
+        });
+
+        th.cancel_request(1);
+
+        assert_eq!(th.pending_commands.get(&req_key).unwrap().cmd_ids, vec!(2));
+
+        th.cancel_request(2);
+
+        assert!(th.pending_commands.is_empty());
+    }
+
     #[test]
     fn catchup_handler_start_catchup_works() {
         let mut ch: CatchupHandler = Default::default();
-        let (gt, handle) = nodes_emulator::start();
-        ch.merkle_tree.append(gt.to_json().unwrap()).unwrap();
-        let mut rn: RemoteNode = RemoteNode::from(gt);
-        rn.connect(&zmq::Context::new());
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        ch.merkle_tree.append(&gt.to_json().unwrap()).unwrap();
+        let mut rn: RemoteNode = RemoteNode::new(gt).unwrap();
+        let (_cmd_sender, mut transport) = ChannelTransport::new();
+        let node = transport.add_node(rn.zaddr.as_str());
+        rn.connect(&mut transport, &ClientKeyPair::new().unwrap()).unwrap();
         ch.nodes.push(rn);
         ch.new_mt_size = 2;
 
-        ch.start_catchup();
+        ch.start_catchup(&transport).unwrap();
 
-        let emulator_msgs: Vec<String> = handle.join().unwrap();
-        assert_eq!(1, emulator_msgs.len());
         let expected_resp: CatchupReq = CatchupReq {
             ledgerId: 0,
             seqNoStart: 2,
             seqNoEnd: 2,
             catchupTill: 2,
         };
-        let act_resp = CatchupReq::from_json(emulator_msgs[0].as_str()).unwrap();
+        let act_resp = CatchupReq::from_json(node.received.try_recv().unwrap().as_str()).unwrap();
         assert_eq!(expected_resp, act_resp);
+        assert!(node.received.try_recv().is_err());
     }
 
     #[test]
-    fn remote_node_connect_works_and_can_ping_pong() {
+    fn zmq_transport_connect_works_and_can_ping_pong() {
         let (gt, handle) = nodes_emulator::start();
-        let mut rn: RemoteNode = RemoteNode::from(gt);
-        let ctx = zmq::Context::new();
-        rn.connect(&ctx);
-        rn.zsock.as_ref().expect("sock").send_str("pi", zmq::DONTWAIT).expect("send");
-        rn.zsock.as_ref().expect("sock").poll(zmq::POLLIN, nodes_emulator::POLL_TIMEOUT).expect("poll");
-        assert_eq!("po", rn.zsock.as_ref().expect("sock").recv_string(zmq::DONTWAIT).expect("recv").expect("string").as_str());
-        handle.join().expect("join");
+        let mut rn: RemoteNode = RemoteNode::new(gt).unwrap();
+        let (_cmd_sender, mut transport) = ZMQTransport::new("test_zmq_transport_ping_pong").unwrap();
+        rn.connect(&mut transport, &ClientKeyPair::new().unwrap()).unwrap();
+        rn.send_str(&transport, "pi").unwrap();
+
+        let events = transport.poll(nodes_emulator::POLL_TIMEOUT).unwrap();
+
+        assert_eq!(events.len(), 1);
+        match events[0] {
+            TransportEvent::NodeMessage(conn_id, ref msg) => {
+                assert_eq!(Some(conn_id), rn.conn_id);
+                assert_eq!(msg, "po");
+            }
+            ref event => panic!("Unexpected event {:?}", event),
+        }
+        assert_eq!(handle.join().expect("join"), vec!["pi".to_string()]);
+    }
+
+    #[test]
+    fn catchup_handler_process_msg_works_for_ledger_status_with_other_root() {
+        let mut ch: CatchupHandler = Default::default();
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        ch.merkle_tree.append(&gt.to_json().unwrap()).unwrap();
+        ch.nodes.push(RemoteNode::new(gt).unwrap());
+        let ls = LedgerStatus {
+            txnSeqNo: 1,
+            merkleRoot: "invalid_root".to_string(),
+            ledgerId: 0,
+        };
+
+        let res = ch.process_msg(&ChannelTransport::new().1, Message::LedgerStatus(ls), &"".to_string(), 0).unwrap();
+
+        assert!(res.is_none());
+        assert!(ch.ledger_status_same.is_empty());
+    }
+
+    #[test]
+    fn catchup_handler_start_catchup_fails_for_nothing_to_catchup() {
+        let mut ch: CatchupHandler = Default::default();
+        let gt = nodes_emulator::gen_txn("n1", 9701);
+        ch.merkle_tree.append(&gt.to_json().unwrap()).unwrap();
+        ch.nodes.push(RemoteNode::new(gt).unwrap());
+        ch.new_mt_size = 1;
+
+        let res = ch.start_catchup(&ChannelTransport::new().1);
+
+        assert_match!(Err(PoolError::InvalidState(_)), res);
+        assert!(ch.pending_catchup.is_none());
+    }
+
+    fn _catchup_handler_with_nodes(cnt: usize) -> (CatchupHandler, ChannelTransport, Vec<ChannelNode>, Vec<GenTransaction>) {
+        let (_cmd_sender, mut transport) = ChannelTransport::new();
+        let mut ch: CatchupHandler = Default::default();
+        let mut nodes: Vec<ChannelNode> = Vec::new();
+        let mut gen_txns: Vec<GenTransaction> = Vec::new();
+        for i in 0..cnt {
+            let gt = nodes_emulator::gen_txn(format!("n{}", i + 1).as_str(), 9701 + i as u32);
+            let mut rn: RemoteNode = RemoteNode::new(gt.clone()).unwrap();
+            nodes.push(transport.add_node(rn.zaddr.as_str()));
+            rn.connect(&mut transport, &ClientKeyPair::new().unwrap()).unwrap();
+            ch.nodes.push(rn);
+            gen_txns.push(gt);
+        }
+        (ch, transport, nodes, gen_txns)
+    }
+
+    fn _catchup_rep(seq_no: usize, txn: &GenTransaction) -> CatchupRep {
+        let mut txns: HashMap<String, serde_json::Value> = HashMap::new();
+        txns.insert(seq_no.to_string(), serde_json::from_str(&txn.to_json().unwrap()).unwrap());
+        CatchupRep {
+            ledgerId: 0,
+            consProof: Vec::new(),
+            txns: txns,
+        }
+    }
+
+    #[test]
+    fn catchup_handler_process_msg_works_for_consistency_proof() {
+        let (mut ch, transport, nodes, gen_txns) = _catchup_handler_with_nodes(1);
+        ch.merkle_tree.append(&gen_txns[0].to_json().unwrap()).unwrap();
+        let new_txn = nodes_emulator::gen_txn("n2", 9702).to_json().unwrap();
+        let mut new_mt = ch.merkle_tree.clone();
+        new_mt.append(&new_txn).unwrap();
+        let old_root = ch.merkle_tree.root_hash().as_slice().to_base58();
+        let new_root = new_mt.root_hash().as_slice().to_base58();
+        let cons_proof = |hashes: Vec<String>| ConsistencyProof {
+            seqNoStart: 1,
+            seqNoEnd: 2,
+            ledgerId: 0,
+            hashes: hashes,
+            oldMerkleRoot: old_root.clone(),
+            newMerkleRoot: new_root.clone(),
+        };
+        let invalid_proof = cons_proof(Vec::new());
+        let valid_proof = cons_proof(vec![Hash::hash_leaf(&new_txn).unwrap().to_vec().to_base58()]);
+
+        ch.process_msg(&transport, Message::ConsistencyProof(invalid_proof), &"".to_string(), 0).unwrap();
+
+        assert!(ch.pending_catchup.is_none());
+
+        ch.process_msg(&transport, Message::ConsistencyProof(valid_proof), &"".to_string(), 0).unwrap();
+
+        assert!(ch.pending_catchup.is_some());
+        assert_eq!(ch.new_mt_size, 2);
+        assert_eq!(&ch.new_mt_root, new_mt.root_hash());
+        let req = CatchupReq::from_json(nodes[0].received.try_recv().unwrap().as_str()).unwrap();
+        assert_eq!(req.seqNoStart, 2);
+        assert_eq!(req.seqNoEnd, 2);
+    }
+
+    #[test]
+    fn catchup_handler_process_msg_works_for_domain_ledger_from_empty() {
+        let (mut ch, transport, nodes, _) = _catchup_handler_with_nodes(1);
+        ch.ledger_id = DOMAIN_LEDGER_ID;
+        let nym_txn = "{\"dest\":\"Th7MpTaRZVRYnPiabds81Y\",\"identifier\":\"V4SGRU86Z58d6TV7PBUe6f\",\"type\":\"1\",\"verkey\":\"~7TYfekw4GUagBnBVCqPjiC\"}";
+        let old_root = ch.merkle_tree.root_hash().as_slice().to_base58();
+        let new_root = Hash::hash_leaf(&nym_txn.to_string()).unwrap().to_vec().to_base58();
+        let cons_proof = |ledger_id: usize| ConsistencyProof {
+            seqNoStart: 0,
+            seqNoEnd: 1,
+            ledgerId: ledger_id,
+            hashes: Vec::new(),
+            oldMerkleRoot: old_root.clone(),
+            newMerkleRoot: new_root.clone(),
+        };
+        let pool_proof = cons_proof(POOL_LEDGER_ID);
+        let domain_proof = cons_proof(DOMAIN_LEDGER_ID);
+
+        ch.process_msg(&transport, Message::ConsistencyProof(pool_proof), &"".to_string(), 0).unwrap();
+
+        assert!(ch.pending_catchup.is_none());
+
+        ch.process_msg(&transport, Message::ConsistencyProof(domain_proof), &"".to_string(), 0).unwrap();
+
+        let req = CatchupReq::from_json(nodes[0].received.try_recv().unwrap().as_str()).unwrap();
+        assert_eq!(req, CatchupReq {
+            ledgerId: DOMAIN_LEDGER_ID,
+            seqNoStart: 1,
+            seqNoEnd: 1,
+            catchupTill: 1,
+        });
+
+        let mut txns: HashMap<String, serde_json::Value> = HashMap::new();
+        txns.insert("1".to_string(), serde_json::from_str(nym_txn).unwrap());
+        let rep = CatchupRep {
+            ledgerId: DOMAIN_LEDGER_ID,
+            consProof: Vec::new(),
+            txns: txns,
+        };
+
+        let update = ch.process_msg(&transport, Message::CatchupRep(rep), &"".to_string(), 0).unwrap().unwrap();
+
+        assert_eq!(update.merkle_tree.count(), 1);
+        assert_eq!(update.merkle_tree.root_hash().as_slice().to_base58(), new_root);
+        assert_eq!(update.txns, vec![nym_txn.to_string()]);
+    }
+
+    #[test]
+    fn catchup_handler_process_catchup_rep_works_for_invalid_reply() {
+        let (mut ch, transport, nodes, gen_txns) = _catchup_handler_with_nodes(2);
+        ch.merkle_tree.append(&gen_txns[0].to_json().unwrap()).unwrap();
+        let new_txn = nodes_emulator::gen_txn("n3", 9703);
+        let mut new_mt = ch.merkle_tree.clone();
+        new_mt.append(&new_txn.to_json().unwrap()).unwrap();
+        ch.new_mt_size = 2;
+        ch.new_mt_root = new_mt.root_hash().clone();
+        ch.start_catchup(&transport).unwrap();
+        nodes[0].received.try_recv().unwrap();
+        assert!(nodes[1].received.try_recv().is_err());
+
+        let res = ch.process_catchup_rep(&transport, _catchup_rep(2, &gen_txns[1]), 0).unwrap();
+
+        assert!(res.is_none());
+        let req = CatchupReq::from_json(nodes[1].received.try_recv().unwrap().as_str()).unwrap();
+        assert_eq!(req.seqNoStart, 2);
+
+        let res = ch.process_catchup_rep(&transport, _catchup_rep(2, &new_txn), 0).unwrap();
+
+        assert!(res.is_none());
+
+        let res = ch.process_catchup_rep(&transport, _catchup_rep(2, &new_txn), 1).unwrap();
+
+        let update = res.unwrap();
+        assert_eq!(update.merkle_tree.root_hash(), new_mt.root_hash());
+        assert_eq!(update.txns, vec![new_txn.to_json().unwrap()]);
+        assert!(ch.pending_catchup.is_none());
+    }
+
+    #[test]
+    fn catchup_handler_process_timeout_works() {
+        let (mut ch, transport, nodes, gen_txns) = _catchup_handler_with_nodes(2);
+        ch.merkle_tree.append(&gen_txns[0].to_json().unwrap()).unwrap();
+        ch.new_mt_size = 2;
+        ch.start_catchup(&transport).unwrap();
+        nodes[0].received.try_recv().unwrap();
+
+        assert!(ch.get_timeout() > 0);
+        ch.process_timeout(&transport).unwrap();
+        assert!(nodes[1].received.try_recv().is_err());
+
+        ch.pending_catchup.as_mut().unwrap().slices[0].sent_at = Instant::now() - Duration::from_millis(CATCHUP_REP_TIMEOUT_MS);
+        assert_eq!(ch.get_timeout(), 0);
+        ch.process_timeout(&transport).unwrap();
+        let req = CatchupReq::from_json(nodes[1].received.try_recv().unwrap().as_str()).unwrap();
+        assert_eq!(req.seqNoStart, 2);
+
+        ch.pending_catchup.as_mut().unwrap().slices[0].sent_at = Instant::now() - Duration::from_millis(CATCHUP_REP_TIMEOUT_MS);
+        let res = ch.process_timeout(&transport);
+        assert_match!(Err(PoolError::InvalidState(_)), res);
+    }
+
+    #[test]
+    fn pool_worker_handler_process_msg_works_for_invalid_msg() {
+        let mut handler = PoolWorkerHandler::CatchupHandler(Default::default());
+
+        let res = handler.process_msg(&ChannelTransport::new().1, &"{\"op\":\"UNKNOWN\"}".to_string(), 0).unwrap();
+
+        assert!(res.is_none());
+    }
+
+    #[test]
+    fn catchup_rep_min_tx_works() {
+        let gt: serde_json::Value = serde_json::from_str(&nodes_emulator::gen_txn("n1", 9701).to_json().unwrap()).unwrap();
+        let mut txns: HashMap<String, serde_json::Value> = HashMap::new();
+        txns.insert("9".to_string(), gt.clone());
+        txns.insert("10".to_string(), gt.clone());
+        let rep = CatchupRep {
+            ledgerId: 0,
+            consProof: Vec::new(),
+            txns: txns,
+        };
+        assert_eq!(rep.min_tx().unwrap(), 9);
+
+        let mut invalid_txns: HashMap<String, serde_json::Value> = HashMap::new();
+        invalid_txns.insert("first".to_string(), gt);
+        let invalid_rep = CatchupRep {
+            ledgerId: 0,
+            consProof: Vec::new(),
+            txns: invalid_txns,
+        };
+        assert_match!(Err(PoolError::InvalidData(_)), invalid_rep.min_tx());
+    }
+
+    #[test]
+    fn remote_node_new_fails_for_invalid_dest() {
+        let mut gt = nodes_emulator::gen_txn("n1", 9701);
+        gt.dest = "invalid_base58_0OIl".to_string();
+
+        let res = RemoteNode::new(gt);
+
+        assert_match!(Err(PoolError::InvalidData(_)), res);
     }
 
     mod nodes_emulator {
         extern crate sodiumoxide;
 
-        use services::pool::rust_base58::ToBase58;
+        use services::pool::rust_base58::{FromBase58, ToBase58};
         use std::thread;
         use super::*;
 
         pub static POLL_TIMEOUT: i64 = 1000; /* in ms */
 
-        pub fn start() -> (GenTransaction, thread::JoinHandle<Vec<String>>) {
+        pub fn gen_txn(alias: &str, client_port: u32) -> GenTransaction {
+            _gen_txn_with_key(alias, client_port).0
+        }
+
+        fn _gen_txn_with_key(alias: &str, client_port: u32) -> (GenTransaction, sodiumoxide::crypto::sign::ed25519::SecretKey) {
             let (pk, sk) = sodiumoxide::crypto::sign::ed25519::gen_keypair();
-            let pkc = ED25519::pk_to_curve25519(&Vec::from(&pk.0 as &[u8]));
-            let skc = ED25519::sk_to_curve25519(&Vec::from(&sk.0 as &[u8]));
-            let ctx = zmq::Context::new();
-            let s: zmq::Socket = ctx.socket(zmq::SocketType::ROUTER).unwrap();
             let gt = GenTransaction {
                 identifier: "".to_string(),
                 data: NodeData {
-                    alias: "n1".to_string(),
-                    services: Vec::new(),
-                    client_port: 9701,
-                    client_ip: "0.0.0.0".to_string(),
-                    node_ip: "".to_string(),
-                    node_port: 0,
+                    alias: alias.to_string(),
+                    services: Some(vec!["VALIDATOR".to_string()]),
+                    client_port: Some(client_port),
+                    client_ip: Some("0.0.0.0".to_string()),
+                    node_ip: Some("".to_string()),
+                    node_port: Some(0),
                 },
                 txn_id: "".to_string(),
                 txn_type: "0".to_string(),
                 dest: (&pk.0 as &[u8]).to_base58(),
+                verkey: None,
             };
-            let addr = format!("tcp://{}:{}", gt.data.client_ip, gt.data.client_port);
+            (gt, sk)
+        }
+
+        pub fn start() -> (GenTransaction, thread::JoinHandle<Vec<String>>) {
+            let (gt, sk) = _gen_txn_with_key("n1", 9701);
+            let pk = gt.dest.from_base58().unwrap();
+            let pkc = ED25519::pk_to_curve25519(&pk);
+            let skc = ED25519::sk_to_curve25519(&Vec::from(&sk.0 as &[u8]));
+            let ctx = zmq::Context::new();
+            let s: zmq::Socket = ctx.socket(zmq::SocketType::ROUTER).unwrap();
+            let addr = format!("tcp://{}:{}", gt.data.client_ip.as_ref().unwrap(), gt.data.client_port.as_ref().unwrap());
             s.set_curve_publickey(zmq::z85_encode(pkc.as_slice()).unwrap().as_str()).expect("set public key");
             s.set_curve_secretkey(zmq::z85_encode(skc.as_slice()).unwrap().as_str()).expect("set secret key");
             s.set_curve_server(true).expect("set curve server");
@@ -0,0 +1,483 @@
+extern crate serde_json;
+
+use std::cmp;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use self::serde_json::{Map, Value};
+use errors::pool::PoolError;
+use services::ledger::merkletree::merkletree::MerkleTree;
+use super::storage::LedgerStorage;
+use utils::environment::EnvironmentUtils;
+
+const NYM: &'static str = "1";
+const SCHEMA: &'static str = "101";
+const CLAIM_DEF: &'static str = "102";
+const GET_NYM: &'static str = "105";
+const GET_SCHEMA: &'static str = "107";
+const GET_CLAIM_DEF: &'static str = "108";
+
+/// Data the replica answers requests for, the same key is built from the write txn and the read request
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum ReplicaKey {
+    /// dest
+    Nym(String),
+    /// identifier, name and version
+    Schema(String, String, String),
+    /// schema seq_no, signature type and identifier
+    ClaimDef(u64, String, String),
+}
+
+/// Local copy of the domain ledger. It is updated only with txns verified by catchup
+/// against merkle root agreed by f + 1 nodes, so read requests can be answered
+/// from it while it is fresh enough. Only indexes of txns answering the requests are kept
+/// in memory, txns themselves are read from the stored ledger.
+pub struct DomainReplica {
+    max_age: Duration,
+    merkle_tree: MerkleTree,
+    storage: LedgerStorage,
+    /// Seq_nos of txns by the data they write
+    index: HashMap<ReplicaKey, Vec<usize>>,
+    /// Data written through the pool after the replica was synced, with ledger size
+    /// the replica has to reach to answer for it again
+    invalidated: HashMap<ReplicaKey, usize>,
+    synced_at: Option<Instant>,
+}
+
+impl DomainReplica {
+    /// Restores replica stored with pool ledger configuration.
+    /// Restored replica isn't fresh until it is synced with the pool.
+    pub fn restore(pool_name: &str, max_age: Duration) -> Result<DomainReplica, PoolError> {
+        let storage = LedgerStorage::open(DomainReplica::_path(pool_name))?;
+        let merkle_tree = storage.restore_tree()?;
+        let mut replica = DomainReplica {
+            max_age: max_age,
+            merkle_tree: merkle_tree,
+            storage: storage,
+            index: HashMap::new(),
+            invalidated: HashMap::new(),
+            synced_at: None,
+        };
+        for (idx, txn) in replica.storage.txns()?.enumerate() {
+            let txn = DomainReplica::_parse_txn(&txn?)?;
+            replica._index(idx + 1, &txn);
+        }
+        Ok(replica)
+    }
+
+    pub fn merkle_tree(&self) -> &MerkleTree {
+        &self.merkle_tree
+    }
+
+    /// Appends txns caught up from the pool to the replica and stores them with the new tree
+    pub fn update(&mut self, merkle_tree: MerkleTree, txns: Vec<String>) -> Result<(), PoolError> {
+        if merkle_tree.count() != self.merkle_tree.count() + txns.len() {
+            return Err(PoolError::InvalidState(format!("Tree of size {} doesn't match {} txns appended to replica of size {}",
+                                                       merkle_tree.count(), txns.len(), self.merkle_tree.count())));
+        }
+        let parsed_txns = txns.iter()
+            .map(|txn| DomainReplica::_parse_txn(txn))
+            .collect::<Result<Vec<Value>, PoolError>>()?;
+        self.storage.append(&txns, &merkle_tree)?;
+
+        let count = self.merkle_tree.count();
+        for (idx, txn) in parsed_txns.iter().enumerate() {
+            self._index(count + idx + 1, txn);
+        }
+        self.merkle_tree = merkle_tree;
+        let size = self.merkle_tree.count();
+        self.invalidated.retain(|_, &mut required_size| required_size > size);
+        self.synced_at = Some(Instant::now());
+        Ok(())
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        self.max_age > Duration::from_secs(0) &&
+            self.synced_at.map_or(false, |synced_at| synced_at.elapsed() <= self.max_age)
+    }
+
+    /// Time until the replica has to be synced with the pool again, zero if it's stale already.
+    /// None if the replica isn't used to answer requests, so it isn't synced after catchup.
+    pub fn expires_in(&self) -> Option<Duration> {
+        if self.max_age == Duration::from_secs(0) {
+            return None;
+        }
+        let elapsed = match self.synced_at {
+            Some(synced_at) => synced_at.elapsed(),
+            None => return Some(Duration::from_secs(0)),
+        };
+        Some(if elapsed < self.max_age { self.max_age - elapsed } else { Duration::from_secs(0) })
+    }
+
+    /// Stops answering for data written by NYM, SCHEMA or CLAIM_DEF txn from the reply
+    /// until the replica is synced up to the txn
+    pub fn invalidate(&mut self, reply: &str) {
+        let reply: Value = match serde_json::from_str(reply) {
+            Ok(reply) => reply,
+            Err(_) => return,
+        };
+        if reply["op"] != "REPLY" {
+            return;
+        }
+        let result = &reply["result"];
+        if let Some(key) = DomainReplica::_txn_key(result) {
+            let required_size = match result["seqNo"].as_u64() {
+                Some(seq_no) => seq_no as usize,
+                None => self.merkle_tree.count() + 1,
+            };
+            debug!("Domain ledger replica is invalidated for {:?} until it has {} txns", key, required_size);
+            let entry = self.invalidated.entry(key).or_insert(required_size);
+            *entry = cmp::max(*entry, required_size);
+        }
+    }
+
+    /// Builds reply to GET_NYM, GET_SCHEMA or GET_CLAIM_DEF request from the replica.
+    /// Returns None if the replica isn't fresh, request isn't supported or requested
+    /// data isn't in the replica or was invalidated, so the request has to be sent to the pool.
+    pub fn try_answer(&self, request: &str) -> Option<String> {
+        if !self.is_fresh() {
+            return None;
+        }
+        let request: Value = match serde_json::from_str(request) {
+            Ok(request) => request,
+            Err(_) => return None,
+        };
+        let operation = &request["operation"];
+        let key = match DomainReplica::_query_key(operation) {
+            Some(key) => key,
+            None => return None,
+        };
+        if self.invalidated.contains_key(&key) {
+            return None;
+        }
+        let seq_nos = match self.index.get(&key) {
+            Some(seq_nos) => seq_nos,
+            None => return None,
+        };
+        let found = match key {
+            ReplicaKey::Nym(_) => self._get_nym(seq_nos),
+            ReplicaKey::Schema(..) => self._get_last(seq_nos, |txn| DomainReplica::_json_field(&txn["data"])),
+            ReplicaKey::ClaimDef(..) => self._get_last(seq_nos, |txn| Some(txn["data"].clone())),
+        };
+        let (seq_no, data) = match found {
+            Ok(Some(found)) => found,
+            Ok(None) => return None,
+            Err(err) => {
+                warn!("Domain ledger replica can't be read: {}", err);
+                return None;
+            }
+        };
+        let mut result = match operation.as_object() {
+            Some(operation) => operation.clone(),
+            None => return None,
+        };
+        result.insert("identifier".to_string(), request["identifier"].clone());
+        result.insert("reqId".to_string(), request["reqId"].clone());
+        result.insert("seqNo".to_string(), Value::from(seq_no as u64));
+        result.insert("data".to_string(), data);
+
+        let mut reply = Map::new();
+        reply.insert("op".to_string(), Value::String("REPLY".to_string()));
+        reply.insert("result".to_string(), Value::Object(result));
+        serde_json::to_string(&Value::Object(reply)).ok()
+    }
+
+    fn _index(&mut self, seq_no: usize, txn: &Value) {
+        if let Some(key) = DomainReplica::_txn_key(txn) {
+            self.index.entry(key).or_insert_with(Vec::new).push(seq_no);
+        }
+    }
+
+    /// Key of data written by NYM, SCHEMA or CLAIM_DEF txn
+    fn _txn_key(txn: &Value) -> Option<ReplicaKey> {
+        match (txn["type"].as_str(), txn["identifier"].as_str()) {
+            (Some(NYM), _) => txn["dest"].as_str().map(|dest| ReplicaKey::Nym(dest.to_string())),
+            (Some(SCHEMA), Some(identifier)) => DomainReplica::_json_field(&txn["data"]).and_then(|data|
+                match (data["name"].as_str(), data["version"].as_str()) {
+                    (Some(name), Some(version)) => Some(ReplicaKey::Schema(identifier.to_string(), name.to_string(), version.to_string())),
+                    _ => None,
+                }),
+            (Some(CLAIM_DEF), Some(identifier)) => txn["ref"].as_u64().map(|schema_seq_no|
+                ReplicaKey::ClaimDef(schema_seq_no, txn["signature_type"].to_string(), identifier.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Key of data requested by GET_NYM, GET_SCHEMA or GET_CLAIM_DEF operation
+    fn _query_key(operation: &Value) -> Option<ReplicaKey> {
+        match operation["type"].as_str() {
+            Some(GET_NYM) => operation["dest"].as_str().map(|dest| ReplicaKey::Nym(dest.to_string())),
+            Some(GET_SCHEMA) => match (operation["dest"].as_str(), DomainReplica::_json_field(&operation["data"])) {
+                (Some(dest), Some(query)) => match (query["name"].as_str(), query["version"].as_str()) {
+                    (Some(name), Some(version)) => Some(ReplicaKey::Schema(dest.to_string(), name.to_string(), version.to_string())),
+                    _ => None,
+                },
+                _ => None,
+            },
+            Some(GET_CLAIM_DEF) => match (operation["ref"].as_u64(), operation["origin"].as_str()) {
+                (Some(schema_seq_no), Some(origin)) =>
+                    Some(ReplicaKey::ClaimDef(schema_seq_no, operation["signature_type"].to_string(), origin.to_string())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn _txn(&self, seq_no: usize) -> Result<Value, PoolError> {
+        DomainReplica::_parse_txn(&self.storage.txn(seq_no)?)
+    }
+
+    /// Folds NYM txns for the dest. Data is returned as json string like nodes do.
+    fn _get_nym(&self, seq_nos: &[usize]) -> Result<Option<(usize, Value)>, PoolError> {
+        let mut data = Map::new();
+        for seq_no in seq_nos {
+            let txn = self._txn(*seq_no)?;
+            if data.is_empty() {
+                data.insert("dest".to_string(), txn["dest"].clone());
+                data.insert("identifier".to_string(), txn["identifier"].clone());
+                data.insert("role".to_string(), Value::Null);
+                data.insert("verkey".to_string(), Value::Null);
+            }
+            for key in &["role", "verkey"] {
+                if let Some(value) = txn.get(*key) {
+                    data.insert(key.to_string(), value.clone());
+                }
+            }
+        }
+        match (seq_nos.last(), serde_json::to_string(&Value::Object(data))) {
+            (Some(seq_no), Ok(data)) => Ok(Some((*seq_no, Value::String(data)))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Data of the last txn, SCHEMA and CLAIM_DEF txns replace the previous ones
+    fn _get_last<F>(&self, seq_nos: &[usize], data: F) -> Result<Option<(usize, Value)>, PoolError>
+        where F: Fn(&Value) -> Option<Value> {
+        match seq_nos.last() {
+            Some(seq_no) => Ok(data(&self._txn(*seq_no)?).map(|data| (*seq_no, data))),
+            None => Ok(None),
+        }
+    }
+
+    /// Txn data can be either json object or json string with object
+    fn _json_field(value: &Value) -> Option<Value> {
+        match value {
+            &Value::Object(_) => Some(value.clone()),
+            &Value::String(ref value) => serde_json::from_str::<Value>(value).ok().and_then(|value|
+                if value.is_object() { Some(value) } else { None }),
+            _ => None,
+        }
+    }
+
+    fn _parse_txn(txn: &str) -> Result<Value, PoolError> {
+        serde_json::from_str(txn)
+            .map_err(|err| PoolError::InvalidData(format!("Invalid domain ledger txn: {}", err)))
+    }
+
+    fn _path(pool_name: &str) -> PathBuf {
+        let mut path = EnvironmentUtils::pool_path(pool_name);
+        path.push("replica");
+        path.push("domain");
+        path.set_extension("txn");
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const NYM_TXN: &'static str = r#"{"dest":"Th7MpTaRZVRYnPiabds81Y","identifier":"V4SGRU86Z58d6TV7PBUe6f","role":"2","type":"1","verkey":"~7TYfekw4GUagBnBVCqPjiC"}"#;
+    const NYM_UPDATE_TXN: &'static str = r#"{"dest":"Th7MpTaRZVRYnPiabds81Y","identifier":"Th7MpTaRZVRYnPiabds81Y","type":"1","verkey":"~NcYxiDXkpYi6ov5FcYDi1e"}"#;
+    const SCHEMA_TXN: &'static str = r#"{"data":"{\"attr_names\":[\"name\",\"age\"],\"name\":\"gvt\",\"version\":\"1.0\"}","identifier":"Th7MpTaRZVRYnPiabds81Y","type":"101"}"#;
+    const CLAIM_DEF_TXN: &'static str = r#"{"data":{"primary":{"n":"1"}},"identifier":"Th7MpTaRZVRYnPiabds81Y","ref":3,"signature_type":"CL","type":"102"}"#;
+
+    fn _replica(pool_name: &str, txns: &[&str]) -> DomainReplica {
+        fs::remove_dir_all(EnvironmentUtils::pool_path(pool_name)).ok();
+        let txns: Vec<String> = txns.iter().map(|txn| txn.to_string()).collect();
+        let mut replica = DomainReplica::restore(pool_name, Duration::from_secs(300)).unwrap();
+        replica.update(MerkleTree::from_vec(txns.clone()).unwrap(), txns).unwrap();
+        replica
+    }
+
+    fn _result(reply: &str) -> Value {
+        let reply: Value = serde_json::from_str(reply).unwrap();
+        assert_eq!(reply["op"], "REPLY");
+        reply["result"].clone()
+    }
+
+    #[test]
+    fn domain_replica_try_answer_works_for_get_nym() {
+        let replica = _replica("domain_replica_try_answer_works_for_get_nym", &[NYM_TXN, SCHEMA_TXN, NYM_UPDATE_TXN]);
+        let request = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"dest":"Th7MpTaRZVRYnPiabds81Y","type":"105"},"reqId":1}"#;
+
+        let result = _result(&replica.try_answer(request).unwrap());
+
+        assert_eq!(result["type"], "105");
+        assert_eq!(result["dest"], "Th7MpTaRZVRYnPiabds81Y");
+        assert_eq!(result["identifier"], "V4SGRU86Z58d6TV7PBUe6f");
+        assert_eq!(result["reqId"], 1);
+        assert_eq!(result["seqNo"], 3);
+        let data: Value = serde_json::from_str(result["data"].as_str().unwrap()).unwrap();
+        assert_eq!(data["identifier"], "V4SGRU86Z58d6TV7PBUe6f");
+        assert_eq!(data["role"], "2");
+        assert_eq!(data["verkey"], "~NcYxiDXkpYi6ov5FcYDi1e");
+    }
+
+    #[test]
+    fn domain_replica_try_answer_works_for_get_schema() {
+        let replica = _replica("domain_replica_try_answer_works_for_get_schema", &[NYM_TXN, SCHEMA_TXN]);
+        let request = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"data":{"name":"gvt","version":"1.0"},"dest":"Th7MpTaRZVRYnPiabds81Y","type":"107"},"reqId":2}"#;
+
+        let result = _result(&replica.try_answer(request).unwrap());
+
+        assert_eq!(result["seqNo"], 2);
+        assert_eq!(result["data"]["name"], "gvt");
+        assert_eq!(result["data"]["attr_names"], json_array(&["name", "age"]));
+    }
+
+    #[test]
+    fn domain_replica_try_answer_works_for_get_claim_def() {
+        let replica = _replica("domain_replica_try_answer_works_for_get_claim_def", &[NYM_TXN, SCHEMA_TXN, CLAIM_DEF_TXN]);
+        let request = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"origin":"Th7MpTaRZVRYnPiabds81Y","ref":3,"signature_type":"CL","type":"108"},"reqId":3}"#;
+
+        let result = _result(&replica.try_answer(request).unwrap());
+
+        assert_eq!(result["seqNo"], 3);
+        assert_eq!(result["origin"], "Th7MpTaRZVRYnPiabds81Y");
+        assert_eq!(result["data"]["primary"]["n"], "1");
+    }
+
+    #[test]
+    fn domain_replica_try_answer_works_for_missed_data() {
+        let replica = _replica("domain_replica_try_answer_works_for_missed_data", &[NYM_TXN]);
+        let request = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"dest":"V4SGRU86Z58d6TV7PBUe6f","type":"105"},"reqId":1}"#;
+
+        assert!(replica.try_answer(request).is_none());
+    }
+
+    #[test]
+    fn domain_replica_try_answer_works_for_not_fresh_replica() {
+        let mut replica = _replica("domain_replica_try_answer_works_for_not_fresh_replica", &[NYM_TXN]);
+        let request = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"dest":"Th7MpTaRZVRYnPiabds81Y","type":"105"},"reqId":1}"#;
+
+        replica.max_age = Duration::from_secs(0);
+        assert!(replica.try_answer(request).is_none());
+
+        replica.max_age = Duration::from_secs(300);
+        replica.synced_at = None;
+        assert!(replica.try_answer(request).is_none());
+    }
+
+    #[test]
+    fn domain_replica_try_answer_works_for_invalidated_data() {
+        let mut replica = _replica("domain_replica_try_answer_works_for_invalidated_data", &[NYM_TXN, SCHEMA_TXN]);
+        let get_nym = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"dest":"Th7MpTaRZVRYnPiabds81Y","type":"105"},"reqId":1}"#;
+        let get_schema = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"data":{"name":"gvt","version":"1.0"},"dest":"Th7MpTaRZVRYnPiabds81Y","type":"107"},"reqId":2}"#;
+
+        replica.invalidate(r#"{"op":"REPLY","result":{"dest":"Th7MpTaRZVRYnPiabds81Y","identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":3,"seqNo":3,"type":"1","verkey":"~NcYxiDXkpYi6ov5FcYDi1e"}}"#);
+
+        assert!(replica.try_answer(get_nym).is_none());
+        assert!(replica.try_answer(get_schema).is_some());
+
+        let txns = vec![NYM_TXN.to_string(), SCHEMA_TXN.to_string(), NYM_UPDATE_TXN.to_string()];
+        replica.update(MerkleTree::from_vec(txns).unwrap(), vec![NYM_UPDATE_TXN.to_string()]).unwrap();
+
+        let result = _result(&replica.try_answer(get_nym).unwrap());
+        assert_eq!(result["seqNo"], 3);
+    }
+
+    #[test]
+    fn domain_replica_invalidate_works_for_reply_without_seq_no() {
+        let mut replica = _replica("domain_replica_invalidate_works_for_reply_without_seq_no", &[NYM_TXN, SCHEMA_TXN]);
+        let get_schema = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"data":{"name":"gvt","version":"1.0"},"dest":"Th7MpTaRZVRYnPiabds81Y","type":"107"},"reqId":2}"#;
+
+        replica.invalidate(r#"{"op":"REQNACK","result":{"data":"{\"name\":\"gvt\",\"version\":\"1.0\"}","identifier":"Th7MpTaRZVRYnPiabds81Y","type":"101"}}"#);
+        assert!(replica.try_answer(get_schema).is_some());
+
+        replica.invalidate(r#"{"op":"REPLY","result":{"data":"{\"name\":\"gvt\",\"version\":\"1.0\"}","identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":3,"type":"101"}}"#);
+        assert!(replica.try_answer(get_schema).is_none());
+
+        let merkle_tree = replica.merkle_tree().clone();
+        replica.update(merkle_tree, Vec::new()).unwrap();
+        assert!(replica.try_answer(get_schema).is_none());
+    }
+
+    #[test]
+    fn domain_replica_expires_in_works() {
+        let mut replica = _replica("domain_replica_expires_in_works", &[NYM_TXN]);
+
+        assert!(replica.expires_in().unwrap() > Duration::from_secs(290));
+
+        replica.synced_at = Some(Instant::now() - Duration::from_secs(301));
+        assert_eq!(replica.expires_in(), Some(Duration::from_secs(0)));
+
+        replica.synced_at = None;
+        assert_eq!(replica.expires_in(), Some(Duration::from_secs(0)));
+
+        replica.max_age = Duration::from_secs(0);
+        assert_eq!(replica.expires_in(), None);
+    }
+
+    #[test]
+    fn domain_replica_try_answer_works_for_not_supported_request() {
+        let replica = _replica("domain_replica_try_answer_works_for_not_supported_request", &[NYM_TXN]);
+        let request = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"dest":"Th7MpTaRZVRYnPiabds81Y","type":"1"},"reqId":1}"#;
+
+        assert!(replica.try_answer(request).is_none());
+    }
+
+    #[test]
+    fn domain_replica_update_works() {
+        let pool_name = "domain_replica_update_works";
+        fs::remove_dir_all(EnvironmentUtils::pool_path(pool_name)).ok();
+        let mut replica = DomainReplica::restore(pool_name, Duration::from_secs(300)).unwrap();
+        assert_eq!(replica.merkle_tree().count(), 0);
+        assert!(!replica.is_fresh());
+
+        let txns = vec![NYM_TXN.to_string(), SCHEMA_TXN.to_string()];
+        let merkle_tree = MerkleTree::from_vec(txns.clone()).unwrap();
+        replica.update(merkle_tree.clone(), txns.clone()).unwrap();
+
+        assert!(replica.is_fresh());
+        let restored = DomainReplica::restore(pool_name, Duration::from_secs(300)).unwrap();
+        assert_eq!(restored.merkle_tree().root_hash(), merkle_tree.root_hash());
+        assert_eq!(restored.storage.count(), 2);
+        assert_eq!(restored.index.get(&ReplicaKey::Nym("Th7MpTaRZVRYnPiabds81Y".to_string())), Some(&vec![1]));
+        assert_eq!(restored.index.len(), 2);
+        assert!(!restored.is_fresh());
+
+        let mut new_txns = txns.clone();
+        new_txns.push(CLAIM_DEF_TXN.to_string());
+        let new_merkle_tree = MerkleTree::from_vec(new_txns).unwrap();
+        replica.update(new_merkle_tree.clone(), vec![CLAIM_DEF_TXN.to_string()]).unwrap();
+
+        let mut restored = DomainReplica::restore(pool_name, Duration::from_secs(300)).unwrap();
+        assert_eq!(restored.merkle_tree(), &new_merkle_tree);
+        assert_eq!(restored.storage.count(), 3);
+        assert_eq!(restored.index.len(), 3);
+
+        restored.synced_at = Some(Instant::now());
+        let request = r#"{"identifier":"V4SGRU86Z58d6TV7PBUe6f","operation":{"origin":"Th7MpTaRZVRYnPiabds81Y","ref":3,"signature_type":"CL","type":"108"},"reqId":3}"#;
+        let result = _result(&restored.try_answer(request).unwrap());
+        assert_eq!(result["seqNo"], 3);
+        assert_eq!(result["data"]["primary"]["n"], "1");
+    }
+
+    #[test]
+    fn domain_replica_update_works_for_txns_not_matching_tree() {
+        let mut replica = _replica("domain_replica_update_works_for_txns_not_matching_tree", &[NYM_TXN]);
+        let merkle_tree = MerkleTree::from_vec(vec![NYM_TXN.to_string(), SCHEMA_TXN.to_string()]).unwrap();
+
+        let res = replica.update(merkle_tree, Vec::new());
+
+        assert_match!(Err(PoolError::InvalidState(_)), res);
+        assert_eq!(replica.storage.count(), 1);
+    }
+
+    fn json_array(values: &[&str]) -> Value {
+        Value::Array(values.iter().map(|value| Value::String(value.to_string())).collect())
+    }
+}
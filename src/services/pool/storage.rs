@@ -0,0 +1,361 @@
+use std::{fs, io};
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use errors::pool::PoolError;
+use services::ledger::merkletree::merkletree::MerkleTree;
+use utils::crypto::hash::Hash;
+
+/// Ledger stored as txn file with one txn json per line, which is the leaf data of the
+/// merkle tree, and the tree file next to it, so the tree is reloaded without hashing all txns.
+/// Both files are only appended to. Only offsets of txns are kept in memory,
+/// txns are read from the txn file by seq_no when needed.
+pub struct LedgerStorage {
+    txn_path: PathBuf,
+    /// Offsets of txns in the txn file, txn with seq_no starts at offsets[seq_no - 1]
+    offsets: Vec<u64>,
+    /// Length of the txn file
+    len: u64,
+    /// Whether the last txn of the txn file is followed by newline
+    terminated: bool,
+}
+
+/// Iterator over txns of the ledger read from the txn file
+pub struct LedgerTxns {
+    lines: Option<io::Lines<io::BufReader<fs::File>>>,
+    left: usize,
+}
+
+impl Iterator for LedgerTxns {
+    type Item = Result<String, PoolError>;
+
+    fn next(&mut self) -> Option<Result<String, PoolError>> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        self.lines.as_mut()
+            .and_then(|lines| lines.next())
+            .map(|line| line.map_err(PoolError::from))
+    }
+}
+
+impl LedgerStorage {
+    /// Opens the ledger and reads offsets of its txns, ledger without txn file is empty
+    pub fn open(txn_path: PathBuf) -> Result<LedgerStorage, PoolError> {
+        let mut storage = LedgerStorage {
+            txn_path: txn_path,
+            offsets: Vec::new(),
+            len: 0,
+            terminated: true,
+        };
+        if !storage.txn_path.as_path().exists() {
+            return Ok(storage);
+        }
+        let mut reader = io::BufReader::new(fs::File::open(storage.txn_path.as_path())?);
+        let mut line: Vec<u8> = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
+            }
+            storage.offsets.push(storage.len);
+            storage.len += read as u64;
+            storage.terminated = line.last() == Some(&b'\n');
+        }
+        Ok(storage)
+    }
+
+    /// Number of txns in the ledger
+    pub fn count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Reads txn by its 1-based seq_no
+    pub fn txn(&self, seq_no: usize) -> Result<String, PoolError> {
+        match self.txns_from(seq_no)?.next() {
+            Some(txn) => txn,
+            None => Err(PoolError::InvalidState(format!("Txn {} is out of ledger of size {}", seq_no, self.count())))
+        }
+    }
+
+    /// Reads all txns in seq_no order
+    pub fn txns(&self) -> Result<LedgerTxns, PoolError> {
+        self.txns_from(1)
+    }
+
+    /// Reads txns in seq_no order starting with the 1-based seq_no
+    pub fn txns_from(&self, seq_no: usize) -> Result<LedgerTxns, PoolError> {
+        if seq_no == 0 || seq_no > self.count() {
+            return Ok(LedgerTxns { lines: None, left: 0 });
+        }
+        let mut f = fs::File::open(self.txn_path.as_path())?;
+        f.seek(SeekFrom::Start(self.offsets[seq_no - 1]))?;
+        Ok(LedgerTxns {
+            lines: Some(io::BufReader::new(f).lines()),
+            left: self.count() - seq_no + 1,
+        })
+    }
+
+    /// Loads the stored tree if it matches the txns and extends it with txns appended after it was stored,
+    /// otherwise rebuilds the tree from txns and stores it
+    pub fn restore_tree(&self) -> Result<MerkleTree, PoolError> {
+        match self._load_tree() {
+            Ok(Some(mut merkle_tree)) => {
+                let stored_count = merkle_tree.count();
+                for txn in self.txns_from(stored_count + 1)? {
+                    merkle_tree.append(&txn?)?;
+                }
+                if merkle_tree.count() > stored_count {
+                    self._append_tree(&merkle_tree, stored_count)?;
+                }
+                return Ok(merkle_tree);
+            }
+            Ok(None) => {}
+            Err(err) => warn!("Stored merkle tree {:?} is skipped: {}", self._tree_path(), err),
+        }
+        let mut merkle_tree = MerkleTree::new()?;
+        for txn in self.txns()? {
+            merkle_tree.append(&txn?)?;
+        }
+        if !merkle_tree.is_empty() {
+            self._store_tree(&merkle_tree)?;
+        }
+        Ok(merkle_tree)
+    }
+
+    /// Appends txns to the txn file and hashes they added to the tree to the tree file.
+    /// The tree is stored after txns, so interrupted append is detected on restore.
+    pub fn append(&mut self, txns: &[String], merkle_tree: &MerkleTree) -> Result<(), PoolError> {
+        let count = self.count();
+        if merkle_tree.count() != count + txns.len() {
+            return Err(PoolError::InvalidState(format!("Tree of size {} doesn't match {} txns appended to ledger of size {}",
+                                                       merkle_tree.count(), txns.len(), count)));
+        }
+        if txns.is_empty() {
+            return Ok(());
+        }
+        if let Some(dir) = self.txn_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        {
+            let mut f = fs::OpenOptions::new().create(true).append(true).open(self.txn_path.as_path())?;
+            if !self.terminated {
+                f.write_all(b"\n")?;
+                self.len += 1;
+                self.terminated = true;
+            }
+            for txn in txns {
+                f.write_all(txn.as_bytes())?;
+                f.write_all(b"\n")?;
+                self.offsets.push(self.len);
+                self.len += txn.len() as u64 + 1;
+            }
+            f.sync_all()?;
+        }
+        self._append_tree(merkle_tree, count)
+    }
+
+    /// Stored tree is used only if it isn't larger than the txn file and has the same leaf
+    /// for its last txn, txns appended after it are added by restore
+    fn _load_tree(&self) -> Result<Option<MerkleTree>, PoolError> {
+        let tree_path = self._tree_path();
+        if !tree_path.as_path().exists() {
+            return Ok(None);
+        }
+        let mut bytes: Vec<u8> = Vec::new();
+        fs::File::open(tree_path.as_path())?.read_to_end(&mut bytes)?;
+        let merkle_tree = MerkleTree::from_bytes(&bytes)?;
+        if merkle_tree.count() > self.count() {
+            return Err(PoolError::InvalidData(format!("Tree has {} leaves, but there are {} txns",
+                                                      merkle_tree.count(), self.count())));
+        }
+        if !merkle_tree.is_empty() {
+            let leaf_hash = Hash::hash_leaf(&self.txn(merkle_tree.count())?)?.to_vec();
+            if merkle_tree.leaf_hash(merkle_tree.count() - 1) != Some(leaf_hash.as_slice()) {
+                return Err(PoolError::InvalidData(format!("Tree doesn't match txn {}", merkle_tree.count())));
+            }
+        }
+        Ok(Some(merkle_tree))
+    }
+
+    /// Appends hashes of leaves after `stored_count` to the tree file. The whole tree is stored
+    /// if the tree file doesn't hold exactly `stored_count` leaves, e.g. after interrupted append.
+    fn _append_tree(&self, merkle_tree: &MerkleTree, stored_count: usize) -> Result<(), PoolError> {
+        let tree_path = self._tree_path();
+        let stored_len = match fs::metadata(tree_path.as_path()) {
+            Ok(metadata) => Some(metadata.len()),
+            Err(_) => None
+        };
+        if stored_count == 0 || stored_len != Some(MerkleTree::stored_len(stored_count) as u64) {
+            return self._store_tree(merkle_tree);
+        }
+        let mut f = fs::OpenOptions::new().append(true).open(tree_path.as_path())?;
+        f.write_all(&merkle_tree.appended_bytes(stored_count))?;
+        f.sync_all()?;
+        Ok(())
+    }
+
+    fn _store_tree(&self, merkle_tree: &MerkleTree) -> Result<(), PoolError> {
+        let tree_path = self._tree_path();
+        let mut tmp_path = tree_path.clone();
+        tmp_path.set_extension("mt.tmp");
+        {
+            let mut f = fs::File::create(tmp_path.as_path())?;
+            f.write_all(&merkle_tree.to_bytes())?;
+            f.sync_all()?;
+        }
+        fs::rename(tmp_path, tree_path)?;
+        Ok(())
+    }
+
+    fn _tree_path(&self) -> PathBuf {
+        let mut path = self.txn_path.clone();
+        path.set_extension("mt");
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::environment::EnvironmentUtils;
+
+    fn _storage(name: &str) -> LedgerStorage {
+        let mut path = EnvironmentUtils::tmp_file_path(name);
+        path.set_extension("txn");
+        fs::remove_file(path.as_path()).ok();
+        let mut tree_path = path.clone();
+        tree_path.set_extension("mt");
+        fs::remove_file(tree_path.as_path()).ok();
+        LedgerStorage::open(path).unwrap()
+    }
+
+    fn _reopen(storage: &LedgerStorage) -> LedgerStorage {
+        LedgerStorage::open(storage.txn_path.clone()).unwrap()
+    }
+
+    fn _txns(range: ::std::ops::Range<usize>) -> Vec<String> {
+        range.map(|i| format!(r#"{{"reqId":{},"type":"1"}}"#, i)).collect()
+    }
+
+    fn _read_file(path: PathBuf) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        fs::File::open(path.as_path()).unwrap().read_to_end(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn _write_file(path: PathBuf, bytes: &[u8]) {
+        fs::File::create(path.as_path()).unwrap().write_all(bytes).unwrap();
+    }
+
+    fn _read_txns(storage: &LedgerStorage) -> Vec<String> {
+        storage.txns().unwrap().map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn ledger_storage_append_and_restore_works() {
+        let mut storage = _storage("ledger_storage_append_and_restore_works");
+        let txns = _txns(0..5);
+        let merkle_tree = MerkleTree::from_vec(txns.clone()).unwrap();
+
+        storage.append(&txns[..3], &MerkleTree::from_vec(txns[..3].to_vec()).unwrap()).unwrap();
+        storage.append(&txns[3..], &merkle_tree).unwrap();
+
+        let restored = _reopen(&storage);
+        assert_eq!(restored.count(), 5);
+        assert_eq!(_read_txns(&restored), txns);
+        assert_eq!(restored.restore_tree().unwrap(), merkle_tree);
+        assert_eq!(fs::metadata(storage._tree_path()).unwrap().len(), MerkleTree::stored_len(5) as u64);
+    }
+
+    #[test]
+    fn ledger_storage_txn_works() {
+        let mut storage = _storage("ledger_storage_txn_works");
+        let txns = _txns(0..4);
+        storage.append(&txns, &MerkleTree::from_vec(txns.clone()).unwrap()).unwrap();
+
+        let restored = _reopen(&storage);
+        assert_eq!(restored.txn(1).unwrap(), txns[0]);
+        assert_eq!(restored.txn(3).unwrap(), txns[2]);
+        assert_eq!(restored.txns_from(3).unwrap().map(Result::unwrap).collect::<Vec<String>>(), txns[2..].to_vec());
+        assert_match!(Err(PoolError::InvalidState(_)), restored.txn(0));
+        assert_match!(Err(PoolError::InvalidState(_)), restored.txn(5));
+    }
+
+    #[test]
+    fn ledger_storage_open_works_for_txn_file_without_last_newline() {
+        let mut storage = _storage("ledger_storage_open_works_for_txn_file_without_last_newline");
+        let txns = _txns(0..3);
+        _write_file(storage.txn_path.clone(), format!("{}\n{}", txns[0], txns[1]).as_bytes());
+        storage = _reopen(&storage);
+        assert_eq!(storage.count(), 2);
+
+        storage.append(&txns[2..], &MerkleTree::from_vec(txns.clone()).unwrap()).unwrap();
+
+        assert_eq!(_read_txns(&_reopen(&storage)), txns);
+    }
+
+    #[test]
+    fn ledger_storage_restore_works_without_tree_file() {
+        let mut storage = _storage("ledger_storage_restore_works_without_tree_file");
+        let txns = _txns(0..3);
+        storage.append(&txns, &MerkleTree::from_vec(txns.clone()).unwrap()).unwrap();
+        fs::remove_file(storage._tree_path()).unwrap();
+
+        let merkle_tree = storage.restore_tree().unwrap();
+
+        assert_eq!(merkle_tree, MerkleTree::from_vec(txns.clone()).unwrap());
+        assert!(storage._tree_path().as_path().exists());
+    }
+
+    #[test]
+    fn ledger_storage_restore_works_for_tree_behind_txns() {
+        let mut storage = _storage("ledger_storage_restore_works_for_tree_behind_txns");
+        let txns = _txns(0..5);
+        storage.append(&txns[..3], &MerkleTree::from_vec(txns[..3].to_vec()).unwrap()).unwrap();
+        let stored_tree = _read_file(storage._tree_path());
+        storage.append(&txns[3..], &MerkleTree::from_vec(txns.clone()).unwrap()).unwrap();
+        // append interrupted before the tree is stored
+        _write_file(storage._tree_path(), &stored_tree);
+
+        let merkle_tree = _reopen(&storage).restore_tree().unwrap();
+
+        assert_eq!(merkle_tree, MerkleTree::from_vec(txns.clone()).unwrap());
+        assert_eq!(_read_file(storage._tree_path()), merkle_tree.to_bytes());
+    }
+
+    #[test]
+    fn ledger_storage_restore_works_for_tree_not_matching_txns() {
+        let mut storage = _storage("ledger_storage_restore_works_for_tree_not_matching_txns");
+        let txns = _txns(0..3);
+        storage.append(&txns, &MerkleTree::from_vec(txns.clone()).unwrap()).unwrap();
+        _write_file(storage._tree_path(), &MerkleTree::from_vec(_txns(1..4)).unwrap().to_bytes());
+
+        let merkle_tree = storage.restore_tree().unwrap();
+
+        assert_eq!(merkle_tree, MerkleTree::from_vec(txns.clone()).unwrap());
+    }
+
+    #[test]
+    fn ledger_storage_append_works_for_tree_not_matching_txns() {
+        let mut storage = _storage("ledger_storage_append_works_for_tree_not_matching_txns");
+
+        let res = storage.append(&_txns(0..2), &MerkleTree::from_vec(_txns(0..3)).unwrap());
+
+        assert_match!(Err(PoolError::InvalidState(_)), res);
+        assert_eq!(storage.count(), 0);
+    }
+
+    #[test]
+    fn ledger_storage_restore_works_for_empty_ledger() {
+        let storage = _storage("ledger_storage_restore_works_for_empty_ledger");
+
+        let merkle_tree = storage.restore_tree().unwrap();
+
+        assert_eq!(storage.count(), 0);
+        assert!(storage.txns().unwrap().next().is_none());
+        assert!(merkle_tree.is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+use std::cell::RefCell;
+use std::io;
+
+use errors::pool::PoolError;
+use super::types::ClientKeyPair;
+use super::zmq;
+
+pub type ConnectionId = usize;
+
+#[derive(Debug)]
+pub enum TransportEvent {
+    Command(Vec<Vec<u8>>),
+    NodeMessage(ConnectionId, String),
+    NodeError(ConnectionId, PoolError),
+}
+
+/// Pool side of the command channel between Pool and PoolWorker
+pub trait CommandSender {
+    fn send(&self, parts: &[&[u8]]) -> Result<(), PoolError>;
+}
+
+/// PoolWorker side of the command channel and connections to the nodes.
+/// Worker logic talks to the nodes only through this trait.
+pub trait Transport: Send {
+    fn connect(&mut self, zaddr: &str, verify_key: &[u8], client_keys: &ClientKeyPair) -> Result<ConnectionId, PoolError>;
+    fn disconnect(&mut self, conn_id: ConnectionId);
+    /// Failed send is also reported as NodeError event by the next poll
+    fn send(&self, conn_id: ConnectionId, msg: &str) -> Result<(), PoolError>;
+    /// Waits for commands and node messages. Timeout is in ms, -1 means wait infinitely
+    fn poll(&mut self, timeout: i64) -> Result<Vec<TransportEvent>, PoolError>;
+}
+
+pub struct ZMQCommandSender {
+    sock: zmq::Socket,
+}
+
+impl CommandSender for ZMQCommandSender {
+    fn send(&self, parts: &[&[u8]]) -> Result<(), PoolError> {
+        Ok(self.sock.send_multipart(parts, zmq::DONTWAIT)?)
+    }
+}
+
+/// Default transport: inproc PAIR sockets for commands and CurveZMQ DEALER sockets for nodes
+pub struct ZMQTransport {
+    ctx: zmq::Context,
+    cmd_sock: zmq::Socket,
+    connections: Vec<(ConnectionId, zmq::Socket)>,
+    next_conn_id: ConnectionId,
+    send_errors: RefCell<Vec<(ConnectionId, PoolError)>>,
+}
+
+impl ZMQTransport {
+    pub fn new(pool_name: &str) -> Result<(ZMQCommandSender, ZMQTransport), PoolError> {
+        let ctx = zmq::Context::new();
+        let recv_cmd_sock = ctx.socket(zmq::SocketType::PAIR)?;
+        let send_cmd_sock = ctx.socket(zmq::SocketType::PAIR)?;
+        let inproc_sock_name: String = format!("inproc://pool_{}", pool_name);
+
+        recv_cmd_sock.bind(inproc_sock_name.as_str())?;
+        send_cmd_sock.connect(inproc_sock_name.as_str())?;
+
+        Ok((ZMQCommandSender { sock: send_cmd_sock },
+            ZMQTransport {
+                ctx: ctx,
+                cmd_sock: recv_cmd_sock,
+                connections: Vec::new(),
+                next_conn_id: 0,
+                send_errors: RefCell::new(Vec::new()),
+            }))
+    }
+
+    fn recv_node_msg(sock: &zmq::Socket) -> Result<String, PoolError> {
+        sock.recv_string(zmq::DONTWAIT)?
+            .map_err(|_| PoolError::Io(io::Error::from(io::ErrorKind::InvalidData)))
+    }
+}
+
+impl Transport for ZMQTransport {
+    fn connect(&mut self, zaddr: &str, verify_key: &[u8], client_keys: &ClientKeyPair) -> Result<ConnectionId, PoolError> {
+        let s = self.ctx.socket(zmq::SocketType::DEALER)?;
+        s.set_identity(client_keys.public_key.as_bytes())?;
+        s.set_curve_secretkey(client_keys.secret_key.as_str())?;
+        s.set_curve_publickey(client_keys.public_key.as_str())?;
+        s.set_curve_serverkey(zmq::z85_encode(verify_key)
+            .map_err(|err| PoolError::InvalidData(format!("Can't encode node key: {:?}", err)))?.as_str())?;
+        s.set_linger(0)?; //TODO set correct timeout
+        s.connect(zaddr)?;
+        let conn_id = self.next_conn_id;
+        self.next_conn_id += 1;
+        self.connections.push((conn_id, s));
+        Ok(conn_id)
+    }
+
+    fn disconnect(&mut self, conn_id: ConnectionId) {
+        // Socket is closed on drop
+        self.connections.retain(|&(id, _)| id != conn_id);
+    }
+
+    fn send(&self, conn_id: ConnectionId, msg: &str) -> Result<(), PoolError> {
+        let &(_, ref sock) = self.connections.iter().find(|&&(id, _)| id == conn_id)
+            .ok_or(PoolError::InvalidState(format!("Unknown connection {}", conn_id)))?;
+        sock.send_str(msg, zmq::DONTWAIT)
+            .map_err(|err| {
+                self.send_errors.borrow_mut().push((conn_id, PoolError::InvalidState(format!("Can't send message: {}", err))));
+                PoolError::from(err)
+            })
+    }
+
+    fn poll(&mut self, timeout: i64) -> Result<Vec<TransportEvent>, PoolError> {
+        let mut events: Vec<TransportEvent> = self.send_errors.borrow_mut().drain(..)
+            .map(|(conn_id, err)| TransportEvent::NodeError(conn_id, err))
+            .collect();
+        // Don't wait if there are send errors to report
+        let timeout = if events.is_empty() { timeout } else { 0 };
+
+        let readable: Vec<bool> = {
+            let mut poll_items: Vec<zmq::PollItem> = Vec::new();
+            poll_items.push(self.cmd_sock.as_poll_item(zmq::POLLIN));
+            for &(_, ref sock) in &self.connections {
+                poll_items.push(sock.as_poll_item(zmq::POLLIN));
+            }
+            let r = zmq::poll(poll_items.as_mut_slice(), timeout)?;
+            trace!("zmq poll {:?}", r);
+            poll_items.iter().map(|item| item.is_readable()).collect()
+        };
+
+        for (i, &(conn_id, ref sock)) in self.connections.iter().enumerate() {
+            if readable[1 + i] {
+                match ZMQTransport::recv_node_msg(sock) {
+                    Ok(msg) => events.push(TransportEvent::NodeMessage(conn_id, msg)),
+                    Err(err) => events.push(TransportEvent::NodeError(conn_id, err)),
+                }
+            }
+        }
+        if readable[0] {
+            events.push(TransportEvent::Command(self.cmd_sock.recv_multipart(zmq::DONTWAIT)?));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+pub use self::channel::*;
+
+/// In-process transport over channels. Nodes are emulated by the test code
+/// through ChannelNode endpoints registered before the worker connects.
+#[cfg(test)]
+mod channel {
+    use std::collections::HashMap;
+    use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    pub struct ChannelCommandSender {
+        sender: Sender<Vec<Vec<u8>>>,
+    }
+
+    impl CommandSender for ChannelCommandSender {
+        fn send(&self, parts: &[&[u8]]) -> Result<(), PoolError> {
+            self.sender.send(parts.iter().map(|part| part.to_vec()).collect())
+                .map_err(|_| PoolError::InvalidState("Command channel is closed".to_string()))
+        }
+    }
+
+    pub struct ChannelNode {
+        pub received: Receiver<String>,
+        pub sender: Sender<String>,
+    }
+
+    struct ChannelEndpoint {
+        to_node: Sender<String>,
+        from_node: Receiver<String>,
+    }
+
+    pub struct ChannelTransport {
+        cmd_receiver: Receiver<Vec<Vec<u8>>>,
+        endpoints: HashMap<String, ChannelEndpoint>,
+        connections: Vec<(ConnectionId, String)>,
+        next_conn_id: ConnectionId,
+        send_errors: RefCell<Vec<(ConnectionId, PoolError)>>,
+    }
+
+    impl ChannelTransport {
+        pub fn new() -> (ChannelCommandSender, ChannelTransport) {
+            let (sender, receiver) = channel();
+            (ChannelCommandSender { sender: sender },
+             ChannelTransport {
+                 cmd_receiver: receiver,
+                 endpoints: HashMap::new(),
+                 connections: Vec::new(),
+                 next_conn_id: 0,
+                 send_errors: RefCell::new(Vec::new()),
+             })
+        }
+
+        pub fn add_node(&mut self, zaddr: &str) -> ChannelNode {
+            let (to_node, received) = channel();
+            let (sender, from_node) = channel();
+            self.endpoints.insert(zaddr.to_string(), ChannelEndpoint {
+                to_node: to_node,
+                from_node: from_node,
+            });
+            ChannelNode {
+                received: received,
+                sender: sender,
+            }
+        }
+
+        fn endpoint(&self, conn_id: ConnectionId) -> Result<&ChannelEndpoint, PoolError> {
+            self.connections.iter()
+                .find(|&&(id, _)| id == conn_id)
+                .and_then(|&(_, ref zaddr)| self.endpoints.get(zaddr))
+                .ok_or(PoolError::InvalidState(format!("Unknown connection {}", conn_id)))
+        }
+
+        fn try_recv(&self) -> Result<Vec<TransportEvent>, PoolError> {
+            let mut events: Vec<TransportEvent> = self.send_errors.borrow_mut().drain(..)
+                .map(|(conn_id, err)| TransportEvent::NodeError(conn_id, err))
+                .collect();
+            for &(conn_id, ref zaddr) in &self.connections {
+                if let Some(endpoint) = self.endpoints.get(zaddr) {
+                    if let Ok(msg) = endpoint.from_node.try_recv() {
+                        events.push(TransportEvent::NodeMessage(conn_id, msg));
+                    }
+                }
+            }
+            match self.cmd_receiver.try_recv() {
+                Ok(cmd) => events.push(TransportEvent::Command(cmd)),
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => {
+                    if events.is_empty() {
+                        return Err(PoolError::InvalidState("Command channel is closed".to_string()));
+                    }
+                }
+            }
+            Ok(events)
+        }
+    }
+
+    impl Transport for ChannelTransport {
+        fn connect(&mut self, zaddr: &str, _verify_key: &[u8], _client_keys: &ClientKeyPair) -> Result<ConnectionId, PoolError> {
+            if !self.endpoints.contains_key(zaddr) {
+                return Err(PoolError::InvalidState(format!("No node at {}", zaddr)));
+            }
+            let conn_id = self.next_conn_id;
+            self.next_conn_id += 1;
+            self.connections.push((conn_id, zaddr.to_string()));
+            Ok(conn_id)
+        }
+
+        fn disconnect(&mut self, conn_id: ConnectionId) {
+            self.connections.retain(|&(id, _)| id != conn_id);
+        }
+
+        fn send(&self, conn_id: ConnectionId, msg: &str) -> Result<(), PoolError> {
+            self.endpoint(conn_id)?.to_node.send(msg.to_string())
+                .map_err(|_| {
+                    self.send_errors.borrow_mut().push((conn_id, PoolError::InvalidState(format!("Connection {} is closed", conn_id))));
+                    PoolError::InvalidState(format!("Connection {} is closed", conn_id))
+                })
+        }
+
+        fn poll(&mut self, timeout: i64) -> Result<Vec<TransportEvent>, PoolError> {
+            let start = Instant::now();
+            loop {
+                let events = self.try_recv()?;
+                if !events.is_empty() {
+                    return Ok(events);
+                }
+                if timeout >= 0 && start.elapsed() >= Duration::from_millis(timeout as u64) {
+                    return Ok(events);
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_transport_works() {
+        let (cmd_sender, mut transport) = ChannelTransport::new();
+        let node = transport.add_node("tcp://127.0.0.1:9701");
+        let conn_id = transport.connect("tcp://127.0.0.1:9701", &[], &ClientKeyPair::new().unwrap()).unwrap();
+
+        transport.send(conn_id, "pi").unwrap();
+        assert_eq!(node.received.try_recv().unwrap(), "pi");
+
+        node.sender.send("po".to_string()).unwrap();
+        cmd_sender.send(&["exit".as_bytes()]).unwrap();
+        let events = transport.poll(0).unwrap();
+
+        assert_eq!(events.len(), 2);
+        match events[0] {
+            TransportEvent::NodeMessage(id, ref msg) => {
+                assert_eq!(id, conn_id);
+                assert_eq!(msg, "po");
+            }
+            ref event => panic!("Unexpected event {:?}", event),
+        }
+        match events[1] {
+            TransportEvent::Command(ref cmd) => assert_eq!(cmd, &vec!["exit".as_bytes().to_vec()]),
+            ref event => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn channel_transport_poll_works_for_timeout() {
+        let (_cmd_sender, mut transport) = ChannelTransport::new();
+
+        let events = transport.poll(10).unwrap();
+
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn channel_transport_poll_works_for_send_error() {
+        let (_cmd_sender, mut transport) = ChannelTransport::new();
+        let node = transport.add_node("tcp://127.0.0.1:9701");
+        let conn_id = transport.connect("tcp://127.0.0.1:9701", &[], &ClientKeyPair::new().unwrap()).unwrap();
+        drop(node);
+
+        assert_match!(Err(PoolError::InvalidState(_)), transport.send(conn_id, "pi"));
+        let events = transport.poll(0).unwrap();
+
+        assert_eq!(events.len(), 1);
+        match events[0] {
+            TransportEvent::NodeError(id, PoolError::InvalidState(_)) => assert_eq!(id, conn_id),
+            ref event => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    #[test]
+    fn channel_transport_connect_fails_for_unknown_node() {
+        let (_cmd_sender, mut transport) = ChannelTransport::new();
+
+        let res = transport.connect("tcp://127.0.0.1:9701", &[], &ClientKeyPair::new().unwrap());
+
+        assert_match!(Err(PoolError::InvalidState(_)), res);
+    }
+}
@@ -1,23 +1,50 @@
 extern crate serde_json;
 
 use std::cmp;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{HashMap, HashSet};
+use std::time;
 
+use errors::pool::PoolError;
 use services::ledger::merkletree::merkletree::MerkleTree;
+use super::transport::ConnectionId;
 use super::zmq;
 use utils::json::{JsonDecodable, JsonEncodable};
 
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct NodeData {
     pub alias: String,
-    pub client_ip: String,
-    pub client_port: u32,
-    pub node_ip: String,
-    pub node_port: u32,
-    pub services: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_port: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_port: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub services: Option<Vec<String>>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+impl NodeData {
+    /// Applies fields of NODE txn update on top of current node state.
+    /// Absent fields of update txn don't change current values.
+    pub fn merge(&mut self, update: NodeData) {
+        self.alias = update.alias;
+        if update.client_ip.is_some() { self.client_ip = update.client_ip; }
+        if update.client_port.is_some() { self.client_port = update.client_port; }
+        if update.node_ip.is_some() { self.node_ip = update.node_ip; }
+        if update.node_port.is_some() { self.node_port = update.node_port; }
+        if update.services.is_some() { self.services = update.services; }
+    }
+
+    pub fn is_validator(&self) -> bool {
+        self.services.as_ref()
+            .map(|services| services.iter().any(|service| service == "VALIDATOR"))
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct GenTransaction {
     pub data: NodeData,
     pub dest: String,
@@ -26,6 +53,22 @@ pub struct GenTransaction {
     pub txn_id: String,
     #[serde(rename = "type")]
     pub txn_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verkey: Option<String>,
+}
+
+impl GenTransaction {
+    /// Node is active (should be connected) only if it is validator and has client address
+    pub fn is_active(&self) -> bool {
+        self.data.is_validator() && self.data.client_ip.is_some() && self.data.client_port.is_some()
+    }
+
+    pub fn merge(&mut self, update: GenTransaction) {
+        self.data.merge(update.data);
+        self.identifier = update.identifier;
+        self.txn_id = update.txn_id;
+        if update.verkey.is_some() { self.verkey = update.verkey; }
+    }
 }
 
 impl JsonEncodable for GenTransaction {}
@@ -37,7 +80,7 @@ impl<'a> JsonDecodable<'a> for GenTransaction {}
 pub struct LedgerStatus {
     pub txnSeqNo: usize,
     pub merkleRoot: String,
-    pub ledgerId: u8,
+    pub ledgerId: usize,
 }
 
 #[allow(non_snake_case)]
@@ -64,29 +107,23 @@ pub struct CatchupReq {
 impl<'a> JsonDecodable<'a> for CatchupReq {}
 
 #[allow(non_snake_case)]
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct CatchupRep {
     pub ledgerId: usize,
     pub consProof: Vec<String>,
-    pub txns: HashMap<String, GenTransaction>,
+    /// Txns of any ledger, keyed by seq_no
+    pub txns: HashMap<String, serde_json::Value>,
 }
 
 impl CatchupRep {
-    pub fn min_tx(&self) -> usize {
-        assert!(!self.txns.is_empty());
-        (self.txns.keys().min().unwrap().parse::<usize>()).unwrap()
-    }
-}
-
-impl cmp::Ord for CatchupRep {
-    fn cmp(&self, other: &Self) -> cmp::Ordering {
-        other.min_tx().cmp(&self.min_tx())
-    }
-}
-
-impl cmp::PartialOrd for CatchupRep {
-    fn partial_cmp(&self, other: &CatchupRep) -> Option<cmp::Ordering> {
-        Some(self.cmp(other))
+    pub fn min_tx(&self) -> Result<usize, PoolError> {
+        let mut min = None;
+        for key in self.txns.keys() {
+            let seq_no = key.parse::<usize>()
+                .map_err(|_| PoolError::InvalidData(format!("Invalid txn seq_no {:?} in catchup reply", key)))?;
+            min = Some(min.map_or(seq_no, |min: usize| cmp::min(min, seq_no)));
+        }
+        min.ok_or(PoolError::InvalidData("Empty catchup reply".to_string()))
     }
 }
 
@@ -99,12 +136,15 @@ pub struct Reply {
 #[serde(rename_all = "camelCase")]
 pub struct Response {
     pub req_id: u64,
+    pub identifier: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SimpleRequest {
     pub req_id: u64,
+    pub identifier: String,
+    pub protocol_version: u64,
 }
 
 impl JsonEncodable for SimpleRequest {}
@@ -126,6 +166,8 @@ pub enum Message {
     ReqACK(Response),
     #[serde(rename = "REQNACK")]
     ReqNACK(Response),
+    #[serde(rename = "REJECT")]
+    Reject(Response),
     #[serde(rename = "REPLY")]
     Reply(Reply),
     Ping,
@@ -163,23 +205,168 @@ impl PoolConfig {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolOpenConfig {
+    pub client_identity: Option<String>,
+    /// Catch up domain ledger into local replica on open
+    pub domain_replica: Option<bool>,
+    /// Max age of the replica in seconds to answer GET_NYM, GET_SCHEMA and GET_CLAIM_DEF from it
+    pub replica_max_age: Option<u64>,
+    /// Check auth rules for submitter role before sending signed write requests
+    pub auth_checks: Option<bool>,
+}
+
+impl JsonEncodable for PoolOpenConfig {}
+
+impl<'a> JsonDecodable<'a> for PoolOpenConfig {}
+
+impl Default for PoolOpenConfig {
+    fn default() -> Self {
+        PoolOpenConfig {
+            client_identity: None,
+            domain_replica: None,
+            replica_max_age: None,
+            auth_checks: None,
+        }
+    }
+}
+
+/// CurveZMQ key pair (z85 encoded) used by client to authenticate itself to nodes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClientKeyPair {
+    pub public_key: String,
+    pub secret_key: String,
+}
+
+impl JsonEncodable for ClientKeyPair {}
+
+impl<'a> JsonDecodable<'a> for ClientKeyPair {}
+
+impl ClientKeyPair {
+    pub fn new() -> Result<ClientKeyPair, PoolError> {
+        let key_pair = zmq::CurveKeyPair::new()?;
+        Ok(ClientKeyPair {
+            public_key: key_pair.public_key,
+            secret_key: key_pair.secret_key,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolPhase {
+    CatchingUp,
+    Ready,
+    /// Pool is ready, stale domain ledger replica is caught up meanwhile
+    Refreshing,
+    Terminated,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeConnectionState {
+    /// Socket is connected, but node hasn't answered yet
+    Connecting,
+    /// Node answered at least once
+    Connected,
+    /// Sending or receiving of messages to the node failed
+    Unreachable,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NodeStatus {
+    pub alias: String,
+    pub state: NodeConnectionState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatus {
+    pub phase: PoolPhase,
+    pub ledger_size: usize,
+    pub root_hash: String,
+    pub f: usize,
+    pub nodes: Vec<NodeStatus>,
+}
+
+impl JsonEncodable for PoolStatus {}
+
+impl<'a> JsonDecodable<'a> for PoolStatus {}
+
+impl PoolStatus {
+    pub fn new() -> PoolStatus {
+        PoolStatus {
+            phase: PoolPhase::CatchingUp,
+            ledger_size: 0,
+            root_hash: "".to_string(),
+            f: 0,
+            nodes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolEventType {
+    PhaseChanged,
+    CatchupCompleted,
+    NodeConnected,
+    NodeDisconnected,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PoolEvent {
+    pub event: PoolEventType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    pub status: PoolStatus,
+}
+
+impl JsonEncodable for PoolEvent {}
+
+impl<'a> JsonDecodable<'a> for PoolEvent {}
+
+#[derive(Clone)]
 pub struct RemoteNode {
     pub name: String,
     pub public_key: Vec<u8>,
     pub verify_key: Vec<u8>,
     pub zaddr: String,
-    pub zsock: Option<zmq::Socket>,
+    pub conn_id: Option<ConnectionId>,
+    /// Last time any message was received from the node
+    pub last_seen: time::Instant,
+    /// Liveness ping that isn't answered yet
+    pub pinged_at: Option<time::Instant>,
+}
+
+/// Ledger agreed with f + 1 nodes by catchup
+pub struct LedgerUpdate {
+    pub merkle_tree: MerkleTree,
+    /// Txns appended to local ledger by catchup
+    pub txns: Vec<String>,
 }
 
 pub struct CatchUpProcess {
     pub merkle_tree: MerkleTree,
-    pub pending_reps: BinaryHeap<CatchupRep>,
+    /// Txns appended to the tree, which aren't in local ledger yet
+    pub txns: Vec<String>,
+    /// Slices that aren't appended yet, ordered by seqNoStart
+    pub slices: Vec<CatchupSlice>,
+}
+
+pub struct CatchupSlice {
+    pub req: CatchupReq,
+    pub node_idx: usize,
+    pub sent_at: time::Instant,
+    pub failed_nodes: Vec<usize>,
+    pub rep: Option<CatchupRep>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct CommandProcess {
-    pub nack_cnt: usize,
-    pub reply_cnt: usize,
+    /// Nodes refused the request by REQNACK or REJECT
+    pub nacks: HashSet<usize /* node idx */>,
+    /// Nodes grouped by the result they replied with
+    pub replies: HashMap<String /* result json */, HashSet<usize /* node idx */>>,
     pub cmd_ids: Vec<i32>,
 }
 
@@ -187,6 +374,7 @@ pub struct CommandProcess {
 pub enum ZMQLoopAction {
     RequestToSend(RequestToSend),
     MessageToProcess(MessageToProcess),
+    CancelRequest(i32),
     Terminate,
 }
 
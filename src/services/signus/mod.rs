@@ -90,6 +90,43 @@ impl SignusService {
         Ok(signed_msg)
     }
 
+    /// Adds signature of my_did to `signatures` map of the message keyed by its did.
+    /// Single `signature` already present is moved to the map under message identifier.
+    pub fn multi_sign(&self, my_did: &MyDid, doc: &str) -> Result<String, CryptoError> {
+        if !self.crypto_types.contains_key(&my_did.crypto_type.as_str()) {
+            return Err(CryptoError::UnknownType(my_did.crypto_type.clone()));
+        }
+
+        let signus = self.crypto_types.get(&my_did.crypto_type.as_str()).unwrap();
+
+        let sign_key = Base58::decode(&my_did.sign_key)?;
+        let mut msg: Value = serde_json::from_str(doc)?;
+
+        let mut signatures = match msg.as_object_mut() {
+            Some(fields) => {
+                let mut signatures = match fields.remove("signatures") {
+                    Some(Value::Object(signatures)) => signatures,
+                    Some(Value::Null) | None => serde_json::map::Map::new(),
+                    Some(_) => return Err(CryptoError::InvalidStructure(format!("Signatures must be an object")))
+                };
+                if let Some(signature) = fields.remove("signature") {
+                    let identifier = fields.get("identifier").and_then(Value::as_str)
+                        .ok_or(CryptoError::InvalidStructure(format!("Signed message has no identifier")))?;
+                    signatures.insert(identifier.to_string(), signature);
+                }
+                signatures
+            }
+            None => return Err(CryptoError::InvalidStructure(format!("Message must be an object")))
+        };
+
+        let signature = serialize_signature(msg.clone())?;
+        let signature = signus.sign(&sign_key, signature.as_bytes());
+        signatures.insert(my_did.did.clone(), Value::String(Base58::encode(&signature)));
+        msg["signatures"] = Value::Object(signatures);
+        let signed_msg: String = serde_json::to_string(&msg)?;
+        Ok(signed_msg)
+    }
+
     pub fn verify(&self, their_did: &TheirDid, signed_msg: &str) -> Result<bool, SignusError> {
         let xtype = their_did.crypto_type.clone().unwrap_or(DEFAULT_CRYPTO_TYPE.to_string());
 
@@ -121,6 +158,32 @@ impl SignusService {
         }
     }
 
+    /// Verifies signature of their_did kept in "signatures" of the message signed by multi_sign
+    pub fn verify_multi_signature(&self, their_did: &TheirDid, signed_msg: &str) -> Result<bool, SignusError> {
+        let xtype = their_did.crypto_type.clone().unwrap_or(DEFAULT_CRYPTO_TYPE.to_string());
+
+        if !self.crypto_types.contains_key(&xtype.as_str()) {
+            return Err(SignusError::CryptoError(CryptoError::UnknownType(xtype)));
+        }
+
+        let verkey = their_did.verkey.as_ref()
+            .ok_or(SignusError::CryptoError(CryptoError::InvalidStructure(format!("Verkey key not found"))))?;
+
+        let signus = self.crypto_types.get(&xtype.as_str()).unwrap();
+
+        let verkey = Base58::decode(verkey)?;
+        let signed_msg: Value = serde_json::from_str(signed_msg)?;
+
+        let signature = match signed_msg["signatures"] {
+            Value::Object(ref signatures) => signatures.get(&their_did.did).and_then(Value::as_str)
+                .ok_or(SignusError::CryptoError(CryptoError::InvalidStructure(
+                    format!("Signature of {} not found", their_did.did))))?,
+            _ => return Err(SignusError::CryptoError(CryptoError::InvalidStructure(format!("Signatures key not found"))))
+        };
+        let signature = Base58::decode(signature)?;
+        Ok(signus.verify(&verkey, &serialize_signature(signed_msg.clone())?.as_bytes(), &signature))
+    }
+
     pub fn encrypt(&self, my_did: &MyDid, their_did: &TheirDid, doc: &str) -> Result<(String, String), SignusError> {
         if !self.crypto_types.contains_key(&my_did.crypto_type.as_str()) {
             return Err(SignusError::CryptoError(CryptoError::UnknownType(my_did.crypto_type.clone())));
@@ -313,6 +376,93 @@ mod tests {
         assert!(valid);
     }
 
+    #[test]
+    fn multi_sign_verify_works() {
+        let service = SignusService::new();
+
+        let did_info = MyDidInfo {
+            did: None,
+            seed: None,
+            crypto_type: None
+        };
+        let author = service.create_my_did(&did_info).unwrap();
+        let endorser = service.create_my_did(&did_info).unwrap();
+        let message = format!(r#"{{
+            "reqId":1495034346617224651,
+            "identifier":"{}",
+            "operation":{{
+                "type":"1",
+                "dest":"4efZu2SXufS556yss7W5k6Po37jt4371RM4whbPKBKdB"
+            }}
+        }}"#, author.did);
+
+        let signed = service.sign(&author, &message).unwrap();
+        let multi_signed = service.multi_sign(&endorser, &signed).unwrap();
+        let multi_signed: Value = serde_json::from_str(&multi_signed).unwrap();
+        assert!(multi_signed["signature"].is_null());
+        assert_eq!(multi_signed["signatures"].as_object().unwrap().len(), 2);
+
+        for my_did in vec![author, endorser] {
+            let mut msg = multi_signed.clone();
+            msg["signature"] = multi_signed["signatures"][&my_did.did].clone();
+            let their_did = TheirDid {
+                did: my_did.did.clone(),
+                crypto_type: Some(DEFAULT_CRYPTO_TYPE.to_string()),
+                pk: None,
+                verkey: Some(my_did.ver_key.clone())
+            };
+            assert!(service.verify(&their_did, &msg.to_string()).unwrap());
+        }
+    }
+
+    #[test]
+    fn verify_multi_signature_works() {
+        let service = SignusService::new();
+
+        let did_info = MyDidInfo {
+            did: None,
+            seed: None,
+            crypto_type: None
+        };
+        let author = service.create_my_did(&did_info).unwrap();
+        let endorser = service.create_my_did(&did_info).unwrap();
+        let message = format!(r#"{{"reqId":1495034346617224651,"identifier":"{}","operation":{{"type":"1","dest":"4efZu2SXufS556yss7W5k6Po37jt4371RM4whbPKBKdB"}}}}"#,
+                              author.did);
+
+        let signed = service.sign(&author, &message).unwrap();
+        let multi_signed = service.multi_sign(&endorser, &signed).unwrap();
+
+        for my_did in vec![&author, &endorser] {
+            let their_did = TheirDid::new(my_did.did.clone(), None, None, Some(my_did.ver_key.clone()));
+            assert!(service.verify_multi_signature(&their_did, &multi_signed).unwrap());
+        }
+
+        let mut tampered: Value = serde_json::from_str(&multi_signed).unwrap();
+        tampered["operation"]["dest"] = Value::String("VsKV7grR1BUE29mG2Fm2kX".to_string());
+        let their_did = TheirDid::new(endorser.did.clone(), None, None, Some(endorser.ver_key.clone()));
+        assert!(!service.verify_multi_signature(&their_did, &tampered.to_string()).unwrap());
+
+        let unknown = service.create_my_did(&did_info).unwrap();
+        let their_did = TheirDid::new(unknown.did.clone(), None, None, Some(unknown.ver_key.clone()));
+        assert_match!(Err(SignusError::CryptoError(CryptoError::InvalidStructure(_))),
+                      service.verify_multi_signature(&their_did, &multi_signed));
+    }
+
+    #[test]
+    fn multi_sign_works_for_not_object() {
+        let service = SignusService::new();
+
+        let did_info = MyDidInfo {
+            did: None,
+            seed: None,
+            crypto_type: None
+        };
+        let my_did = service.create_my_did(&did_info).unwrap();
+
+        let res = service.multi_sign(&my_did, "[1, 2]");
+        assert_match!(Err(CryptoError::InvalidStructure(_)), res);
+    }
+
     #[test]
     fn try_verify_with_invalid_verkey() {
         let service = SignusService::new();
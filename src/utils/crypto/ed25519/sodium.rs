@@ -105,6 +105,22 @@ impl ED25519 {
         to.iter().cloned().collect()
     }
 
+    pub fn try_pk_to_curve25519(pk: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if pk.len() != 32 {
+            return Err(CryptoError::InvalidStructure(format!("Invalid ed25519 public key length {}", pk.len())));
+        }
+        let mut from: [u8; 32] = [0; 32];
+        from.clone_from_slice(pk);
+        let mut to: [u8; 32] = [0; 32];
+        let res = unsafe {
+            crypto_sign_ed25519_pk_to_curve25519(&mut to, &from)
+        };
+        if res != 0 {
+            return Err(CryptoError::InvalidStructure("Ed25519 public key can't be converted to curve25519".to_string()));
+        }
+        Ok(to.iter().cloned().collect())
+    }
+
     fn _clone_into_array<A, T>(slice: &[T]) -> A
         where A: Sized + Default + AsMut<[T]>, T: Clone
     {
@@ -162,6 +178,20 @@ mod tests {
         assert_eq!(pkc_exp, pkc_test);
     }
 
+    #[test]
+    fn try_pk_to_curve25519_works() {
+        let pk = vec!(236, 191, 114, 144, 108, 87, 211, 244, 148, 23, 20, 175, 122, 6, 159, 254, 85, 99, 145, 152, 178, 133, 230, 236, 192, 69, 35, 136, 141, 194, 243, 134);
+        let pkc_test = ED25519::try_pk_to_curve25519(&pk).unwrap();
+        let pkc_exp = vec!(8, 45, 124, 147, 248, 201, 112, 171, 11, 51, 29, 248, 34, 127, 197, 241, 60, 158, 84, 47, 4, 176, 238, 166, 110, 39, 207, 58, 127, 110, 76, 42);
+        assert_eq!(pkc_exp, pkc_test);
+    }
+
+    #[test]
+    fn try_pk_to_curve25519_works_for_invalid_length() {
+        let pk = vec!(236, 191, 114, 144);
+        assert!(ED25519::try_pk_to_curve25519(&pk).is_err());
+    }
+
     #[test]
     fn sk_to_curve25519_works() {
         let sk = vec!(78, 67, 205, 99, 150, 131, 75, 110, 56, 154, 76, 61, 27, 142, 36, 141, 44, 223, 122, 199, 14, 230, 12, 163, 4, 255, 94, 230, 21, 242, 97, 200, 236, 191, 114, 144, 108, 87, 211, 244, 148, 23, 20, 175, 122, 6, 159, 254, 85, 99, 145, 152, 178, 133, 230, 236, 192, 69, 35, 136, 141, 194, 243, 134);
@@ -6,41 +6,51 @@ use errors::crypto::CryptoError;
 use utils::crypto::hash::Hash;
 use utils::crypto::base58::Base58;
 
+/// Top level fields holding signatures are never signed, so every signer of a multi-signed
+/// message gets the same bytes regardless of signatures already added by others.
+const SIGNATURE_FIELDS: &'static [&'static str] = &["signature", "signatures"];
+
+/// Serializes message to the canonical form signed by validator nodes:
+/// object fields are sorted by key and joined as `key:value` with `|`, array elements
+/// are joined with `,` (nested arrays are flattened), null is an empty string,
+/// booleans are `True`/`False` and `raw` values are replaced with base58 of their sha256.
 pub fn serialize_signature(v: Value) -> Result<String, CryptoError> {
-    match v {
-        Value::Bool(value) => Ok(value.to_string()),
-        Value::Number(value) => Ok(value.to_string()),
-        Value::String(value) => Ok(value),
-        Value::Array(array) => {
-            let mut result = "".to_string();
-            let length = array.len();
-            for (index, element) in array.iter().enumerate() {
-                result += &serialize_signature(element.clone())?;
-                if index < length - 1 {
-                    result += ",";
-                }
-            }
-            Ok(result)
+    _serialize_signature(&v, true)
+}
+
+fn _serialize_signature(v: &Value, is_top_level: bool) -> Result<String, CryptoError> {
+    match *v {
+        Value::Null => Ok("".to_string()),
+        Value::Bool(value) => Ok(if value { "True" } else { "False" }.to_string()),
+        Value::Number(ref value) => Ok(value.to_string()),
+        Value::String(ref value) => Ok(value.clone()),
+        Value::Array(ref array) => {
+            let elements = array.iter()
+                .map(|element| _serialize_signature(element, false))
+                .collect::<Result<Vec<String>, CryptoError>>()?;
+            Ok(elements.join(","))
         },
-        Value::Object(map) => {
-            let mut result = "".to_string();
-            let length = map.len();
-            for (index, key) in map.keys().enumerate() {
-                let mut value = map[key].clone();
-                if key == "raw" {
+        Value::Object(ref map) => {
+            let mut keys: Vec<&String> = map.keys()
+                .filter(|key| !is_top_level || !SIGNATURE_FIELDS.iter().any(|field| *field == key.as_str()))
+                .collect();
+            keys.sort();
+
+            let mut fields: Vec<String> = Vec::new();
+            for key in keys {
+                let value = if key == "raw" {
+                    let raw = map[key].as_str()
+                        .ok_or(CryptoError::InvalidStructure(format!("Raw value must be a string")))?;
                     let mut ctx = Hash::new_context()?;
-                    ctx.update(&value.as_str().ok_or(CryptoError::BackendError("Cannot update hash context".to_string()))?.as_bytes())?;
-                    let vector = Base58::encode(&ctx.finish2()?.to_vec());
-                    value = Value::String(vector);
-                }
-                result = result + key + ":" + &serialize_signature(value)?;
-                if index < length - 1 {
-                    result += "|";
-                }
+                    ctx.update(raw.as_bytes())?;
+                    Base58::encode(&ctx.finish2()?.to_vec())
+                } else {
+                    _serialize_signature(&map[key], false)?
+                };
+                fields.push(format!("{}:{}", key, value));
             }
-            Ok(result)
-        },
-        _ => Ok("".to_string())
+            Ok(fields.join("|"))
+        }
     }
 }
 
@@ -94,4 +104,62 @@ mod tests {
 
         assert_eq!(serialize_signature(msg).unwrap(), result)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn signature_serialize_works_for_signatures() {
+        let data = r#"{
+                        "identifier": "Th7MpTaRZVRYnPiabds81Y",
+                        "operation": {
+                            "type": "1",
+                            "signature": "inner"
+                        },
+                        "signature": "author signature",
+                        "signatures": {
+                            "V4SGRU86Z58d6TV7PBUe6f": "endorser signature"
+                        }
+                    }"#;
+        let msg: Value = serde_json::from_str(data).unwrap();
+
+        let result = "identifier:Th7MpTaRZVRYnPiabds81Y|operation:signature:inner|type:1";
+
+        assert_eq!(serialize_signature(msg).unwrap(), result)
+    }
+
+    /// Request json and bytes signed by validator nodes for it
+    const TEST_VECTORS: &'static [(&'static str, &'static str)] = &[
+        (r#"{"reqId":1491566332010860,"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"1","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4","role":"2"},"signature":"4X3skpoEK2DRgZxQ9PwuEvCJpL8JHdQ8X4HDDFyztgqE15DM2ZnkvrAh9bQY16egVinZTzwHqznmnkaFM4jjyDgd"}"#,
+         "identifier:Th7MpTaRZVRYnPiabds81Y|operation:dest:FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4|role:2|type:1|reqId:1491566332010860"),
+        (r#"{"reqId":1491566332010861,"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"1","dest":"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4","verkey":null,"role":null}}"#,
+         "identifier:Th7MpTaRZVRYnPiabds81Y|operation:dest:FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4|role:|type:1|verkey:|reqId:1491566332010861"),
+        (r#"{"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"109","force":true,"reinstall":false,"timeout":10}}"#,
+         "identifier:Th7MpTaRZVRYnPiabds81Y|operation:force:True|reinstall:False|timeout:10|type:109"),
+        (r#"{"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"0","data":{"services":["VALIDATOR"],"matrix":[[1,2],[],[3,[4,5]]]}}}"#,
+         "identifier:Th7MpTaRZVRYnPiabds81Y|operation:data:matrix:1,2,,3,4,5|services:VALIDATOR|type:0"),
+        (r#"{"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"101","data":{"name":"Ünïcödé schema","attr_names":["名前","é"]},"é":"x","z":"y"}}"#,
+         "identifier:Th7MpTaRZVRYnPiabds81Y|operation:data:attr_names:名前,é|name:Ünïcödé schema|type:101|z:y|é:x"),
+        (r#"{"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"1","signature":"inner"},"signature":"author","signatures":{"V4SGRU86Z58d6TV7PBUe6f":"endorser"}}"#,
+         "identifier:Th7MpTaRZVRYnPiabds81Y|operation:signature:inner|type:1"),
+        (r#"{"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{"type":"100","dest":"Th7MpTaRZVRYnPiabds81Y","raw":"{\"endpoint\":{\"ha\":\"127.0.0.1:5555\"}}"}}"#,
+         "identifier:Th7MpTaRZVRYnPiabds81Y|operation:dest:Th7MpTaRZVRYnPiabds81Y|raw:9sgMKE8XEsEFxAM4o8GVYxNiZqAXX4v4YvZ9kTb6mMt6|type:100"),
+        (r#"{"identifier":"Th7MpTaRZVRYnPiabds81Y","operation":{},"amount":1.5,"list":[]}"#,
+         "amount:1.5|identifier:Th7MpTaRZVRYnPiabds81Y|list:|operation:"),
+    ];
+
+    #[test]
+    fn signature_serialize_works_for_test_vectors() {
+        for &(request, expected) in TEST_VECTORS {
+            let msg: Value = serde_json::from_str(request).unwrap();
+            assert_eq!(serialize_signature(msg).unwrap(), expected, "request: {}", request);
+        }
+    }
+
+    #[test]
+    fn signature_serialize_works_for_not_string_raw() {
+        let msg: Value = serde_json::from_str(r#"{"operation":{"raw":{"endpoint":"127.0.0.1:5555"}}}"#).unwrap();
+
+        match serialize_signature(msg) {
+            Err(CryptoError::InvalidStructure(_)) => {}
+            res => panic!("Unexpected result: {:?}", res)
+        }
+    }
+}
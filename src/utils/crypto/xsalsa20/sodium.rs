@@ -5,6 +5,9 @@ use errors::crypto::CryptoError;
 use self::sodiumoxide::crypto::secretbox;
 use std::convert::AsMut;
 
+pub const XSALSA20_KEY_LEN: usize = secretbox::KEYBYTES;
+pub const XSALSA20_NONCE_LEN: usize = secretbox::NONCEBYTES;
+
 pub struct XSalsa20 {}
 
 impl XSalsa20 {
@@ -0,0 +1,452 @@
+// TODO: FIXME: It must be removed after code layout stabilization!
+#![allow(dead_code)]
+#![allow(unused_variables)]
+
+extern crate sovrin;
+
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+#[macro_use]
+extern crate lazy_static;
+
+#[macro_use]
+#[path = "utils/mod.rs"]
+mod utils;
+
+use sovrin::api::ErrorCode;
+
+use utils::ledger::LedgerUtils;
+use utils::pool::PoolUtils;
+use utils::pool_simulator::{NodeBehavior, PoolSimulator};
+use utils::signus::SignusUtils;
+use utils::test::TestUtils;
+use utils::wallet::WalletUtils;
+
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+fn start_pool_with_nym(pool_name: &str, base_port: u32, did: &str, verkey: &str, role: Option<&str>,
+                       config: Option<&str>) -> (PoolSimulator, i32) {
+    start_pool_with_nyms(pool_name, base_port, &[(did, verkey, role)], config)
+}
+
+fn start_pool_with_nyms(pool_name: &str, base_port: u32, nyms: &[(&str, &str, Option<&str>)],
+                        config: Option<&str>) -> (PoolSimulator, i32) {
+    let nyms: Vec<(String, String, String)> = nyms.iter()
+        .map(|&(did, verkey, role)| (did.to_string(), verkey.to_string(),
+                                     role.map(|role| format!("\"{}\"", role)).unwrap_or("null".to_string())))
+        .collect();
+    let mut pool_simulator = PoolSimulator::new(4, base_port);
+    pool_simulator.set_reply_handler(Box::new(move |request: &Value| {
+        let nym = nyms.iter().find(|&&(ref did, _, _)| request["operation"]["type"] == "105" && request["operation"]["dest"] == did.as_str());
+        let data = match nym {
+            Some(&(ref did, ref verkey, ref role)) =>
+                Value::from(format!("{{\"dest\":\"{}\",\"identifier\":\"{}\",\"role\":{},\"verkey\":\"{}\"}}", did, did, role, verkey)),
+            None => Value::Null
+        };
+        PoolSimulator::reply_result(request, vec![("data", data)])
+    }));
+    pool_simulator.start();
+
+    PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns()).unwrap();
+    let pool_handle = PoolUtils::open_pool_ledger_with_config(pool_name, config).unwrap();
+    (pool_simulator, pool_handle)
+}
+
+#[test]
+fn sign_request_and_submit_request_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "ledger_sign_and_submit_simulated";
+    let wallet_handle = WalletUtils::create_wallet(pool_name, "ledger_sign_and_submit_wallet", "default").unwrap();
+    let (did, verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let (mut pool_simulator, pool_handle) = start_pool_with_nym(pool_name, 9900, &did, &verkey, None, None);
+
+    let request = format!("{{\"reqId\":1491566332010870,\"protocolVersion\":1,\"identifier\":\"{}\",\"operation\":{{\"type\":\"1\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}}}", did);
+    let signed_request = LedgerUtils::sign_request(wallet_handle, &did, &request).unwrap();
+    let signed: Value = serde_json::from_str(&signed_request).unwrap();
+    assert!(signed["signature"].is_string());
+
+    let reply_json = PoolUtils::send_request(pool_handle, &signed_request).unwrap();
+    let reply: Value = serde_json::from_str(&reply_json).unwrap();
+    assert_eq!(reply["result"]["reqId"], 1491566332010870u64);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn submit_request_works_for_tampered_signed_request() {
+    TestUtils::cleanup_storage();
+    let pool_name = "ledger_submit_tampered_simulated";
+    let wallet_handle = WalletUtils::create_wallet(pool_name, "ledger_submit_tampered_wallet", "default").unwrap();
+    let (did, verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let (mut pool_simulator, pool_handle) = start_pool_with_nym(pool_name, 9910, &did, &verkey, None, None);
+
+    let request = format!("{{\"reqId\":1491566332010871,\"protocolVersion\":1,\"identifier\":\"{}\",\"operation\":{{\"type\":\"1\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}}}", did);
+    let signed_request = LedgerUtils::sign_request(wallet_handle, &did, &request).unwrap();
+    let mut tampered: Value = serde_json::from_str(&signed_request).unwrap();
+    tampered["operation"]["dest"] = Value::from("Th7MpTaRZVRYnPiabds81Y");
+
+    let res = PoolUtils::send_request(pool_handle, &tampered.to_string());
+    assert_match!(Err(ErrorCode::LedgerSecurityError), res);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn submit_request_works_for_multi_signed_request() {
+    TestUtils::cleanup_storage();
+    let pool_name = "ledger_submit_multi_signed_simulated";
+    let wallet_handle = WalletUtils::create_wallet(pool_name, "ledger_submit_multi_signed_wallet", "default").unwrap();
+    let (author_did, author_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let (endorser_did, endorser_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let (mut pool_simulator, pool_handle) = start_pool_with_nyms(pool_name, 9960,
+                                                                 &[(&author_did, &author_verkey, None),
+                                                                   (&endorser_did, &endorser_verkey, Some("101"))],
+                                                                 None);
+
+    let request = format!("{{\"reqId\":1491566332010875,\"protocolVersion\":1,\"identifier\":\"{}\",\"operation\":{{\"type\":\"1\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}}}", author_did);
+    let signed_request = LedgerUtils::sign_request(wallet_handle, &author_did, &request).unwrap();
+    let multi_signed_request = LedgerUtils::multi_sign_request(wallet_handle, &endorser_did, &signed_request).unwrap();
+
+    let reply_json = PoolUtils::send_request(pool_handle, &multi_signed_request).unwrap();
+    let reply: Value = serde_json::from_str(&reply_json).unwrap();
+    assert_eq!(reply["result"]["reqId"], 1491566332010875u64);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn submit_request_works_for_tampered_multi_signed_request() {
+    TestUtils::cleanup_storage();
+    let pool_name = "ledger_submit_tampered_multi_signed_simulated";
+    let wallet_handle = WalletUtils::create_wallet(pool_name, "ledger_submit_tampered_multi_signed_wallet", "default").unwrap();
+    let (author_did, author_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let (endorser_did, endorser_verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let (mut pool_simulator, pool_handle) = start_pool_with_nyms(pool_name, 9970,
+                                                                 &[(&author_did, &author_verkey, None),
+                                                                   (&endorser_did, &endorser_verkey, Some("101"))],
+                                                                 None);
+
+    let request = format!("{{\"reqId\":1491566332010876,\"protocolVersion\":1,\"identifier\":\"{}\",\"operation\":{{\"type\":\"1\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}}}", author_did);
+    let signed_request = LedgerUtils::sign_request(wallet_handle, &author_did, &request).unwrap();
+    let multi_signed_request = LedgerUtils::multi_sign_request(wallet_handle, &endorser_did, &signed_request).unwrap();
+    let multi_signed: Value = serde_json::from_str(&multi_signed_request).unwrap();
+
+    let mut tampered = multi_signed.clone();
+    tampered["operation"]["dest"] = Value::from("Th7MpTaRZVRYnPiabds81Y");
+    let res = PoolUtils::send_request(pool_handle, &tampered.to_string());
+    assert_match!(Err(ErrorCode::LedgerSecurityError), res);
+
+    // Endorser signature alone isn't enough, the author must sign the request too
+    let mut without_author = multi_signed.clone();
+    without_author["signatures"].as_object_mut().unwrap().remove(&author_did);
+    let res = PoolUtils::send_request(pool_handle, &without_author.to_string());
+    assert_match!(Err(ErrorCode::LedgerSecurityError), res);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn sign_request_works_for_other_submitter() {
+    TestUtils::cleanup_storage();
+    let wallet_handle = WalletUtils::create_wallet("ledger_sign_other_submitter", "ledger_sign_other_submitter_wallet", "default").unwrap();
+    let (did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+    let request = "{\"reqId\":1491566332010872,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let res = LedgerUtils::sign_request(wallet_handle, &did, request);
+    assert_match!(Err(ErrorCode::CommonInvalidParam4), res);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn multi_sign_request_works_for_author_and_endorser() {
+    TestUtils::cleanup_storage();
+    let wallet_handle = WalletUtils::create_wallet("ledger_multi_sign", "ledger_multi_sign_wallet", "default").unwrap();
+    let (author_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let (endorser_did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+    let request = format!("{{\"reqId\":1491566332010873,\"protocolVersion\":1,\"identifier\":\"{}\",\"operation\":{{\"type\":\"1\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}}}", author_did);
+    let signed_request = LedgerUtils::sign_request(wallet_handle, &author_did, &request).unwrap();
+    let multi_signed_request = LedgerUtils::multi_sign_request(wallet_handle, &endorser_did, &signed_request).unwrap();
+
+    let signed: Value = serde_json::from_str(&signed_request).unwrap();
+    let multi_signed: Value = serde_json::from_str(&multi_signed_request).unwrap();
+    assert!(multi_signed["signature"].is_null());
+    assert_eq!(multi_signed["signatures"][&author_did], signed["signature"]);
+    assert!(multi_signed["signatures"][&endorser_did].is_string());
+    assert_eq!(multi_signed["operation"], signed["operation"]);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn multi_sign_request_works_for_invalid_request() {
+    TestUtils::cleanup_storage();
+    let wallet_handle = WalletUtils::create_wallet("ledger_multi_sign_invalid", "ledger_multi_sign_invalid_wallet", "default").unwrap();
+    let (did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+    let res = LedgerUtils::multi_sign_request(wallet_handle, &did, "{\"reqId\":1491566332010874}");
+    assert_match!(Err(ErrorCode::CommonInvalidParam4), res);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn sign_and_submit_request_works_for_auth_checks() {
+    TestUtils::cleanup_storage();
+    let pool_name = "ledger_auth_checks_simulated";
+    let wallet_handle = WalletUtils::create_wallet(pool_name, "ledger_auth_checks_wallet", "default").unwrap();
+    let (did, verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let (mut pool_simulator, pool_handle) = start_pool_with_nym(pool_name, 9930, &did, &verkey, Some("101"),
+                                                                Some("{\"authChecks\":true}"));
+
+    let schema_request = format!("{{\"reqId\":1491566332010875,\"protocolVersion\":1,\"identifier\":\"{}\",\"operation\":{{\"type\":\"101\",\"data\":\"{{\\\"name\\\":\\\"gvt\\\",\\\"version\\\":\\\"1.0\\\",\\\"attr_names\\\":[\\\"name\\\"]}}\"}}}}", did);
+    let reply_json = LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &did, &schema_request).unwrap();
+    let reply: Value = serde_json::from_str(&reply_json).unwrap();
+    assert_eq!(reply["result"]["reqId"], 1491566332010875u64);
+
+    let node_request = format!("{{\"reqId\":1491566332010876,\"protocolVersion\":1,\"identifier\":\"{}\",\"operation\":{{\"type\":\"0\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"data\":{{\"alias\":\"Node5\"}}}}}}", did);
+    let res = LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &did, &node_request);
+    assert_match!(Err(ErrorCode::LedgerSecurityError), res);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+fn get_attrib_reply(dest: &str, data: &str) -> String {
+    format!("{{\"op\":\"REPLY\",\"result\":{{\"identifier\":\"{}\",\"reqId\":1491566332010875,\"type\":\"104\",\"dest\":\"{}\",\"data\":\"{}\"}}}}",
+            dest, dest, data)
+}
+
+#[test]
+fn store_encrypted_attrib_and_resolve_works() {
+    TestUtils::cleanup_storage();
+    let wallet_handle = WalletUtils::create_wallet("ledger_encrypted_attrib", "ledger_encrypted_attrib_wallet", "default").unwrap();
+    let (did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+    let raw = "{\"email\":\"alice@example.com\"}";
+
+    let request = LedgerUtils::store_encrypted_attrib(wallet_handle, &did, &did, raw).unwrap();
+    let signed_request = LedgerUtils::sign_request(wallet_handle, &did, &request).unwrap();
+
+    let signed: Value = serde_json::from_str(&signed_request).unwrap();
+    assert_eq!(signed["operation"]["type"], "100");
+    let enc = signed["operation"]["enc"].as_str().unwrap();
+    assert!(!enc.contains("alice"));
+
+    let resolved = LedgerUtils::resolve_encrypted_attrib(wallet_handle, &get_attrib_reply(&did, enc)).unwrap();
+    assert_eq!(resolved, raw);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn resolve_encrypted_attrib_works_for_unknown_key() {
+    TestUtils::cleanup_storage();
+    let wallet_handle = WalletUtils::create_wallet("ledger_encrypted_attrib_unknown", "ledger_encrypted_attrib_wallet", "default").unwrap();
+    let other_wallet_handle = WalletUtils::create_wallet("ledger_encrypted_attrib_unknown", "ledger_encrypted_attrib_other_wallet", "default").unwrap();
+    let (did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+    let request = LedgerUtils::store_encrypted_attrib(wallet_handle, &did, &did, "{\"name\":\"Alex\"}").unwrap();
+    let request: Value = serde_json::from_str(&request).unwrap();
+    let reply = get_attrib_reply(&did, request["operation"]["enc"].as_str().unwrap());
+
+    let res = LedgerUtils::resolve_encrypted_attrib(other_wallet_handle, &reply);
+    assert_match!(Err(ErrorCode::WalletNotFoundError), res);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn resolve_encrypted_attrib_works_for_invalid_data() {
+    TestUtils::cleanup_storage();
+    let wallet_handle = WalletUtils::create_wallet("ledger_encrypted_attrib_invalid_data", "ledger_encrypted_attrib_wallet", "default").unwrap();
+    let (did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+    let res = LedgerUtils::resolve_encrypted_attrib(wallet_handle, &get_attrib_reply(&did, "alice@example.com"));
+    assert_match!(Err(ErrorCode::LedgerInvalidTransaction), res);
+
+    let res = LedgerUtils::resolve_encrypted_attrib(wallet_handle, &get_attrib_reply(&did, "3mJr7AoUXx2Wqd"));
+    assert_match!(Err(ErrorCode::LedgerInvalidTransaction), res);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn store_encrypted_attrib_and_get_attrib_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "ledger_encrypted_attrib_simulated";
+    let wallet_handle = WalletUtils::create_wallet(pool_name, "ledger_encrypted_attrib_wallet", "default").unwrap();
+    let (did, verkey, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+    let nym = format!("{{\"dest\":\"{}\",\"identifier\":\"{}\",\"role\":null,\"verkey\":\"{}\"}}", did, did, verkey);
+    let attribs: Mutex<HashMap<String, Vec<String>>> = Mutex::new(HashMap::new());
+    let mut pool_simulator = PoolSimulator::new(4, 9980);
+    pool_simulator.set_reply_handler(Box::new(move |request: &Value| {
+        let operation = &request["operation"];
+        let dest = operation["dest"].as_str().unwrap_or("").to_string();
+        let mut attribs = attribs.lock().unwrap();
+        let data = match operation["type"].as_str() {
+            Some("105") if nym.contains(&dest) => Value::from(nym.clone()),
+            Some("100") => {
+                if let Some(enc) = operation["enc"].as_str() {
+                    let encs = attribs.entry(dest).or_insert(Vec::new());
+                    if !encs.iter().any(|stored| stored == enc) {
+                        encs.push(enc.to_string());
+                    }
+                }
+                Value::Null
+            }
+            Some("104") => match (attribs.get(&dest), operation["enc"].as_str()) {
+                (Some(encs), Some(enc)) if encs.iter().any(|stored| stored == enc) => Value::from(enc),
+                _ => Value::Null
+            },
+            _ => Value::Null
+        };
+        PoolSimulator::reply_result(request, vec![("dest", operation["dest"].clone()), ("data", data)])
+    }));
+    pool_simulator.start();
+    PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns()).unwrap();
+    let pool_handle = PoolUtils::open_pool_ledger_with_config(pool_name, None).unwrap();
+    let raw = "{\"email\":\"alice@example.com\"}";
+
+    let attrib_request = LedgerUtils::store_encrypted_attrib(wallet_handle, &did, &did, raw).unwrap();
+    LedgerUtils::sign_and_submit_request(pool_handle, wallet_handle, &did, &attrib_request).unwrap();
+
+    let attrib_request: Value = serde_json::from_str(&attrib_request).unwrap();
+    let enc = attrib_request["operation"]["enc"].as_str().unwrap();
+    let get_attrib_request = LedgerUtils::build_get_attrib_request(&did, &did, None, None, Some(enc)).unwrap();
+    let get_attrib_response = PoolUtils::send_request(pool_handle, &get_attrib_request).unwrap();
+
+    let resolved = LedgerUtils::resolve_encrypted_attrib(wallet_handle, &get_attrib_response).unwrap();
+    assert_eq!(resolved, raw);
+
+    let get_attrib_request = LedgerUtils::build_get_attrib_request(&did, &did, None, Some("email"), None).unwrap();
+    let get_attrib_response = PoolUtils::send_request(pool_handle, &get_attrib_request).unwrap();
+    let res = LedgerUtils::resolve_encrypted_attrib(wallet_handle, &get_attrib_response);
+    assert_match!(Err(ErrorCode::LedgerNotFound), res);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn build_get_attrib_request_works_for_invalid_data() {
+    let res = LedgerUtils::build_get_attrib_request("Th7MpTaRZVRYnPiabds81Y", "Th7MpTaRZVRYnPiabds81Y", None, None, None);
+    assert_match!(Err(ErrorCode::CommonInvalidParam4), res);
+
+    let res = LedgerUtils::build_get_attrib_request("Th7MpTaRZVRYnPiabds81Y", "Th7MpTaRZVRYnPiabds81Y", None, Some("email"), Some("enc"));
+    assert_match!(Err(ErrorCode::CommonInvalidParam6), res);
+}
+
+#[test]
+fn store_encrypted_attrib_works_for_invalid_raw() {
+    TestUtils::cleanup_storage();
+    let wallet_handle = WalletUtils::create_wallet("ledger_encrypted_attrib_invalid", "ledger_encrypted_attrib_wallet", "default").unwrap();
+    let (did, _, _) = SignusUtils::create_my_did(wallet_handle, "{}").unwrap();
+
+    let res = LedgerUtils::store_encrypted_attrib(wallet_handle, &did, &did, "[]");
+    assert_match!(Err(ErrorCode::CommonInvalidParam5), res);
+
+    TestUtils::cleanup_storage();
+}
+
+const SCHEMA_TXN: &'static str = r#"{"data":"{\"name\":\"gvt\",\"version\":\"1.0\",\"attr_names\":[\"name\",\"age\"]}","identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":1491566332010876,"seqNo":7,"txnTime":1500000000,"type":"101"}"#;
+
+#[test]
+fn get_txn_request_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "ledger_get_txn_simulated";
+    let mut pool_simulator = PoolSimulator::new(4, 9940);
+    pool_simulator.set_reply_handler(Box::new(|request: &Value| {
+        let data = if request["operation"]["type"] == "3" && request["operation"]["ledgerId"] == 1 && request["operation"]["data"] == 7 {
+            serde_json::from_str(SCHEMA_TXN).unwrap()
+        } else {
+            Value::Null
+        };
+        PoolSimulator::reply_result(request, vec![("seqNo", request["operation"]["data"].clone()), ("data", data)])
+    }));
+    pool_simulator.start();
+    PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns()).unwrap();
+    let pool_handle = PoolUtils::open_pool_ledger_with_config(pool_name, None).unwrap();
+
+    let request = LedgerUtils::build_get_txn_request("Th7MpTaRZVRYnPiabds81Y", Some("DOMAIN"), 7).unwrap();
+    let response = PoolUtils::send_request(pool_handle, &request).unwrap();
+    let txn_json = LedgerUtils::parse_get_txn_response(&response).unwrap();
+
+    let txn: Value = serde_json::from_str(&txn_json).unwrap();
+    assert_eq!(txn["seqNo"], 7);
+    assert_eq!(txn["txnTime"], 1500000000);
+    assert_eq!(txn["txnType"], "SCHEMA");
+    assert_eq!(txn["txn"]["type"], "101");
+    assert!(txn["txn"]["seqNo"].is_null());
+
+    let request = LedgerUtils::build_get_txn_request("Th7MpTaRZVRYnPiabds81Y", None, 8).unwrap();
+    let response = PoolUtils::send_request(pool_handle, &request).unwrap();
+    let res = LedgerUtils::parse_get_txn_response(&response);
+    assert_match!(Err(ErrorCode::LedgerNotFound), res);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn get_txn_request_works_for_simulated_pool_with_refusing_nodes() {
+    TestUtils::cleanup_storage();
+    let pool_name = "ledger_get_txn_simulated_refusing";
+    let mut pool_simulator = PoolSimulator::new(4, 10020);
+    for idx in 0..4 {
+        pool_simulator.set_behavior(idx, NodeBehavior::Refusing);
+    }
+    pool_simulator.start();
+    PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns()).unwrap();
+    let pool_handle = PoolUtils::open_pool_ledger_with_config(pool_name, None).unwrap();
+
+    let request = LedgerUtils::build_get_txn_request("Th7MpTaRZVRYnPiabds81Y", None, 7).unwrap();
+    let response = PoolUtils::send_request(pool_handle, &request).unwrap();
+    let nack: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(nack["op"], "REQNACK");
+
+    let res = LedgerUtils::parse_get_txn_response(&response);
+    assert_match!(Err(ErrorCode::LedgerInvalidTransaction), res);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn build_get_txn_request_works_for_invalid_ledger_type() {
+    let res = LedgerUtils::build_get_txn_request("Th7MpTaRZVRYnPiabds81Y", Some("CONFIG"), 1);
+    assert_match!(Err(ErrorCode::CommonInvalidParam3), res);
+}
+
+const INCLUDED_TXN: &'static str = r#"{"dest":"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb","identifier":"Th7MpTaRZVRYnPiabds81Y","reqId":2,"type":"1","seqNo":2,"auditPath":["DJAgU38cE3PhEsmewfFvpA7pn74HBaeBdzAWbhetP3mM","GQRPFmvDFELrBuKknNVpg2kwLDcARfT1bt1gW8oP1yPH"],"rootHash":"3QnE2Y5fevKkBi1QCYqDuj26J4LUqtQgZwLthLt6FnKk"}"#;
+const INCLUDED_TXN_AUDIT_PATH: &'static str = r#"["DJAgU38cE3PhEsmewfFvpA7pn74HBaeBdzAWbhetP3mM","GQRPFmvDFELrBuKknNVpg2kwLDcARfT1bt1gW8oP1yPH"]"#;
+const INCLUDED_TXN_ROOT_HASH: &'static str = "3QnE2Y5fevKkBi1QCYqDuj26J4LUqtQgZwLthLt6FnKk";
+
+#[test]
+fn verify_txn_inclusion_works() {
+    let valid = LedgerUtils::verify_txn_inclusion(INCLUDED_TXN, INCLUDED_TXN_AUDIT_PATH, INCLUDED_TXN_ROOT_HASH, 3).unwrap();
+    assert!(valid);
+}
+
+#[test]
+fn verify_txn_inclusion_works_for_tampered_txn() {
+    let tampered_txn = INCLUDED_TXN.replace("\"reqId\":2", "\"reqId\":3");
+
+    let valid = LedgerUtils::verify_txn_inclusion(&tampered_txn, INCLUDED_TXN_AUDIT_PATH, INCLUDED_TXN_ROOT_HASH, 3).unwrap();
+    assert!(!valid);
+}
+
+#[test]
+fn verify_txn_inclusion_works_for_invalid_audit_path() {
+    let res = LedgerUtils::verify_txn_inclusion(INCLUDED_TXN, "[1,2]", INCLUDED_TXN_ROOT_HASH, 3);
+    assert_match!(Err(ErrorCode::CommonInvalidParam3), res);
+}
@@ -14,11 +14,14 @@ extern crate lazy_static;
 #[path = "utils/mod.rs"]
 mod utils;
 
-#[cfg(feature = "local_nodes_pool")]
 use sovrin::api::ErrorCode;
 
 use utils::pool::PoolUtils;
+use utils::pool_simulator::{NodeBehavior, PoolSimulator};
 use utils::test::TestUtils;
+use utils::timeout::TimeoutUtils;
+
+use std::sync::mpsc::channel;
 
 #[test]
 fn create_pool_ledger_config_works() {
@@ -30,6 +33,20 @@ fn create_pool_ledger_config_works() {
     TestUtils::cleanup_storage();
 }
 
+#[test]
+fn create_pool_ledger_config_works_for_invalid_genesis_txns() {
+    TestUtils::cleanup_storage();
+
+    let genesis_txns = vec![
+        "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"10.0.0.2\",\"client_port\":9702,\"node_ip\":\"10.0.0.2\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}".to_string(),
+        "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"10.0.0.2\",\"client_port\":9704,\"node_ip\":\"10.0.0.2\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"invalid_dest\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}".to_string(),
+    ];
+    let res = PoolUtils::create_pool_ledger_config_for_txns("pool_create_invalid_genesis", &genesis_txns);
+    assert_match!(Err(ErrorCode::PoolLedgerInvalidDataFormat), res);
+
+    TestUtils::cleanup_storage();
+}
+
 #[test]
 #[cfg(feature = "local_nodes_pool")]
 fn open_pool_ledger_works() {
@@ -80,7 +97,7 @@ fn sovrin_submit_request_works() {
                 \"type\":\"105\",\
                 \"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"\
             },\
-            \"signature\":\"4o86XfkiJ4e2r3J6Ufoi17UU3W5Zi9sshV6FjBjkVw4sgEQFQov9dxqDEtLbAJAWffCWd5KfAk164QVo7mYwKkiV\"\
+            \"protocolVersion\":1\
         }\
         ";
     let resp = PoolUtils::send_request(pool_handle, request);
@@ -98,6 +115,388 @@ fn sovrin_submit_request_works() {
     TestUtils::cleanup_storage();
 }
 
+#[test]
+fn open_pool_ledger_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_open_simulated";
+    let mut pool_simulator = PoolSimulator::new(4, 9800);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let res = PoolUtils::open_pool_ledger(pool_name);
+    assert!(res.is_ok());
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn open_pool_ledger_works_for_simulated_pool_with_catchup_and_demoted_node() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_open_simulated_catchup";
+    let mut pool_simulator = PoolSimulator::new(4, 9810);
+    let demote_txn = pool_simulator.node_demote_txn(3);
+    pool_simulator.add_pool_txn(&demote_txn);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let res = PoolUtils::open_pool_ledger(pool_name);
+    assert!(res.is_ok());
+    let pool_handle = res.unwrap();
+
+    let request = "{\"reqId\":1491566332010861,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let resp = PoolUtils::send_request(pool_handle, request);
+    assert!(resp.is_ok());
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn open_pool_ledger_works_for_simulated_pool_with_lagging_and_silent_nodes() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_open_simulated_lagging";
+    let mut pool_simulator = PoolSimulator::new(4, 9820);
+    let update_txn = pool_simulator.node_update_txn(0, 9821);
+    pool_simulator.add_pool_txn(&update_txn);
+    pool_simulator.set_behavior(2, NodeBehavior::Lagging(4));
+    pool_simulator.set_behavior(3, NodeBehavior::Silent);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let res = PoolUtils::open_pool_ledger(pool_name);
+    assert!(res.is_ok());
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn open_pool_ledger_works_for_simulated_pool_with_catchup_from_byzantine_and_silent_nodes() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_open_simulated_catchup_rerequest";
+    let mut pool_simulator = PoolSimulator::new(4, 9880);
+    for &(node_idx, port) in &[(0, 9881), (2, 9885), (3, 9887)] {
+        let update_txn = pool_simulator.node_update_txn(node_idx, port);
+        pool_simulator.add_pool_txn(&update_txn);
+    }
+    pool_simulator.set_behavior(1, NodeBehavior::Byzantine);
+    pool_simulator.set_behavior(2, NodeBehavior::Silent);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger(pool_name).unwrap();
+
+    let status_json = PoolUtils::get_pool_status(pool_handle).unwrap();
+    let status: serde_json::Value = serde_json::from_str(status_json.as_str()).unwrap();
+    assert_eq!(status["ledgerSize"], 7);
+    assert_eq!(status["rootHash"], PoolSimulator::merkle_root(&pool_simulator.pool_ledger()));
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn open_pool_ledger_works_for_simulated_pool_with_domain_replica() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_open_simulated_domain_replica";
+    let mut pool_simulator = PoolSimulator::new(4, 9890);
+    pool_simulator.add_domain_txn("{\"dest\":\"Th7MpTaRZVRYnPiabds81Y\",\"identifier\":\"V4SGRU86Z58d6TV7PBUe6f\",\"role\":\"2\",\"type\":\"1\",\"verkey\":\"~7TYfekw4GUagBnBVCqPjiC\"}");
+    pool_simulator.add_domain_txn("{\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"type\":\"1\"}");
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger_with_config(pool_name, Some("{\"domainReplica\":true}")).unwrap();
+    pool_simulator.stop();
+
+    // Nodes are stopped, so the reply can be built only from the local replica
+    let request = "{\"reqId\":1491566332010863,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let reply_json = PoolUtils::send_request(pool_handle, request).unwrap();
+    let reply: serde_json::Value = serde_json::from_str(reply_json.as_str()).unwrap();
+    assert_eq!(reply["result"]["reqId"], 1491566332010863u64);
+    assert_eq!(reply["result"]["seqNo"], 2);
+    let data: serde_json::Value = serde_json::from_str(reply["result"]["data"].as_str().unwrap()).unwrap();
+    assert_eq!(data["dest"], "FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4");
+    assert_eq!(data["identifier"], "Th7MpTaRZVRYnPiabds81Y");
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn submit_request_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_submit_simulated";
+    let mut pool_simulator = PoolSimulator::new(4, 9830);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let res = PoolUtils::open_pool_ledger(pool_name);
+    assert!(res.is_ok());
+    let pool_handle = res.unwrap();
+
+    let request = "{\"reqId\":1491566332010862,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let resp = PoolUtils::send_request(pool_handle, request);
+
+    let exp_reply = Reply {
+        op: "REPLY".to_string(),
+        result: ReplyResult {
+            req_id: 1491566332010862,
+            data: None,
+            identifier: "Th7MpTaRZVRYnPiabds81Y".to_string(),
+        }
+    };
+    let act_reply: Reply = serde_json::from_str(resp.unwrap().as_str()).unwrap();
+    assert_eq!(act_reply, exp_reply);
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn submit_request_works_for_simulated_pool_and_missed_protocol_version() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_submit_simulated_missed_protocol_version";
+    let mut pool_simulator = PoolSimulator::new(4, 9920);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger(pool_name).unwrap();
+
+    let request = "{\"reqId\":1491566332010865,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let res = PoolUtils::send_request(pool_handle, request);
+    assert_match!(Err(ErrorCode::PoolLedgerInvalidDataFormat), res);
+
+    pool_simulator.stop();
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn open_pool_ledger_works_for_simulated_pool_with_byzantine_node() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_open_simulated_byzantine";
+    let mut pool_simulator = PoolSimulator::new(4, 9840);
+    pool_simulator.set_behavior(1, NodeBehavior::Byzantine);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let res = PoolUtils::open_pool_ledger(pool_name);
+    assert!(res.is_ok());
+    let pool_handle = res.unwrap();
+
+    let request = "{\"reqId\":1491566332010863,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let resp = PoolUtils::send_request(pool_handle, request).unwrap();
+    let resp: serde_json::Value = serde_json::from_str(resp.as_str()).unwrap();
+    assert_eq!(resp["result"]["data"], serde_json::Value::Null);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn submit_request_works_for_simulated_pool_with_byzantine_nodes_replying_first() {
+    TestUtils::cleanup_storage();
+    let pool_name = "submit_request_simulated_byzantine_first";
+    let mut pool_simulator = PoolSimulator::new(7, 10000);
+    for idx in 0..7 {
+        let behavior = if idx < 2 { NodeBehavior::Byzantine } else { NodeBehavior::Delayed(300) };
+        pool_simulator.set_behavior(idx, behavior);
+    }
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger(pool_name).unwrap();
+
+    let request = "{\"reqId\":1491566332010864,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let resp = PoolUtils::send_request(pool_handle, request).unwrap();
+    let resp: serde_json::Value = serde_json::from_str(resp.as_str()).unwrap();
+    assert_eq!(resp["result"]["data"], serde_json::Value::Null);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn cancel_request_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_cancel_request_simulated";
+    let mut pool_simulator = PoolSimulator::new(4, 9850);
+    for idx in 0..4 {
+        pool_simulator.set_behavior(idx, NodeBehavior::Mute);
+    }
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger(pool_name).unwrap();
+
+    let request = "{\"reqId\":1491566332010864,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let (command_handle, receiver) = PoolUtils::send_request_async(pool_handle, request).unwrap();
+
+    let res = PoolUtils::cancel_request(pool_handle, command_handle);
+    assert!(res.is_ok());
+    let (err, _) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+    assert_eq!(err, ErrorCode::LedgerRequestCancelled);
+
+    let res = PoolUtils::cancel_request(pool_handle, command_handle);
+    assert_match!(Err(ErrorCode::LedgerNotFound), res);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn submit_request_works_for_command_handle_of_pending_request() {
+    TestUtils::cleanup_storage();
+    let pool_name = "submit_request_pending_command_handle";
+    let mut pool_simulator = PoolSimulator::new(4, 10030);
+    for idx in 0..4 {
+        pool_simulator.set_behavior(idx, NodeBehavior::Mute);
+    }
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger(pool_name).unwrap();
+
+    let request = "{\"reqId\":1491566332010866,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"}}";
+    let (command_handle, receiver) = PoolUtils::send_request_async(pool_handle, request).unwrap();
+
+    // Callback of the pending request receives the result of the second one
+    let res = PoolUtils::send_request_with_command_handle(pool_handle, command_handle, request);
+    assert!(res.is_ok());
+    let (err, _) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+    assert_eq!(err, ErrorCode::CommonInvalidState);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn cancel_request_works_for_signed_request_during_signature_verification() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_cancel_signed_request_simulated";
+    let mut pool_simulator = PoolSimulator::new(4, 9950);
+    for idx in 0..4 {
+        pool_simulator.set_behavior(idx, NodeBehavior::Mute);
+    }
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger(pool_name).unwrap();
+
+    // GET_NYM of the submitter is never answered, so the request waits for signature verification
+    let request = "{\"reqId\":1491566332010865,\"protocolVersion\":1,\"identifier\":\"Th7MpTaRZVRYnPiabds81Y\",\"operation\":{\"type\":\"105\",\"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\"},\"signature\":\"4o86XfkiJ4e2r3J6Ufoi17UU3W5Zi9sshV6FjBjkVw4sgEQFQ5D6DH4nyaj8RZBLvuG9onkTK5vbuWKCmH8t5xq1\"}";
+    let (command_handle, receiver) = PoolUtils::send_request_async(pool_handle, request).unwrap();
+
+    let res = PoolUtils::cancel_request(pool_handle, command_handle);
+    assert!(res.is_ok());
+    let (err, _) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+    assert_eq!(err, ErrorCode::LedgerRequestCancelled);
+
+    let res = PoolUtils::cancel_request(pool_handle, command_handle);
+    assert_match!(Err(ErrorCode::LedgerNotFound), res);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn pool_get_status_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_get_status_simulated";
+    let mut pool_simulator = PoolSimulator::new(4, 9860);
+    let demote_txn = pool_simulator.node_demote_txn(3);
+    pool_simulator.add_pool_txn(&demote_txn);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger(pool_name).unwrap();
+
+    let status_json = PoolUtils::get_pool_status(pool_handle).unwrap();
+    let status: serde_json::Value = serde_json::from_str(status_json.as_str()).unwrap();
+    assert_eq!(status["phase"], "Ready");
+    assert_eq!(status["ledgerSize"], 5);
+    assert_eq!(status["f"], 0);
+    assert_eq!(status["nodes"].as_array().unwrap().len(), 3);
+    assert_eq!(status["rootHash"], PoolSimulator::merkle_root(&pool_simulator.pool_ledger()));
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn pool_get_status_works_for_invalid_handle() {
+    let res = PoolUtils::get_pool_status(-1);
+    assert_match!(Err(ErrorCode::PoolLedgerInvalidPoolHandle), res);
+}
+
+#[test]
+fn pool_subscribe_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "pool_subscribe_simulated";
+    let mut pool_simulator = PoolSimulator::new(4, 9870);
+    pool_simulator.set_behavior(3, NodeBehavior::Silent);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger_with_config(pool_name, Some("{\"domainReplica\":true,\"replicaMaxAge\":1}")).unwrap();
+
+    let (sender, receiver) = channel();
+    let res = PoolUtils::subscribe_pool_events(pool_handle, sender);
+    assert!(res.is_ok());
+
+    // Stale replica is refreshed and silent node doesn't answer pings
+    let mut refreshed = false;
+    let mut disconnected = false;
+    while !refreshed || !disconnected {
+        let event_json = receiver.recv_timeout(TimeoutUtils::medium_timeout()).unwrap();
+        let event: serde_json::Value = serde_json::from_str(event_json.as_str()).unwrap();
+        match event["event"].as_str().unwrap() {
+            "PhaseChanged" => refreshed |= event["status"]["phase"] == "Refreshing",
+            "NodeDisconnected" => {
+                assert_eq!(event["node"], "Node4");
+                disconnected = true;
+            }
+            _ => {}
+        }
+    }
+
+    let (sender, _) = channel();
+    let res = PoolUtils::subscribe_pool_events(-1, sender);
+    assert_match!(Err(ErrorCode::PoolLedgerInvalidPoolHandle), res);
+
+    TestUtils::cleanup_storage();
+}
+
+#[test]
+fn close_pool_ledger_works_for_simulated_pool() {
+    TestUtils::cleanup_storage();
+    let pool_name = "close_pool_ledger_simulated";
+    let mut pool_simulator = PoolSimulator::new(4, 9990);
+    pool_simulator.start();
+
+    let res = PoolUtils::create_pool_ledger_config_for_txns(pool_name, &pool_simulator.genesis_txns());
+    assert!(res.is_ok());
+    let pool_handle = PoolUtils::open_pool_ledger(pool_name).unwrap();
+    let (sender, receiver) = channel();
+    let res = PoolUtils::subscribe_pool_events(pool_handle, sender);
+    assert!(res.is_ok());
+
+    let res = PoolUtils::close(pool_handle);
+    assert!(res.is_ok());
+
+    while let Ok(event_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()) {
+        let event: serde_json::Value = serde_json::from_str(event_json.as_str()).unwrap();
+        assert_ne!(event["status"]["phase"], "Terminated");
+    }
+    let res = PoolUtils::get_pool_status(pool_handle);
+    assert_match!(Err(ErrorCode::PoolLedgerInvalidPoolHandle), res);
+    let res = PoolUtils::close(pool_handle);
+    assert_match!(Err(ErrorCode::PoolLedgerInvalidPoolHandle), res);
+
+    TestUtils::cleanup_storage();
+}
+
 #[derive(Deserialize, Eq, PartialEq, Debug)]
 struct Reply {
     op: String,
@@ -82,6 +82,100 @@ impl CallbackUtils {
         (command_handle, Some(send_tx_callback))
     }
 
+    pub fn closure_to_pool_get_status_cb(closure: Box<FnMut(ErrorCode, String) + Send>)
+                                         -> (i32,
+                                             Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                              status_json: *const c_char)>) {
+        lazy_static! {
+            static ref POOL_GET_STATUS_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn pool_get_status_callback(command_handle: i32, err: ErrorCode, status_json: *const c_char) {
+            let mut callbacks = POOL_GET_STATUS_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let status_json = unsafe { CStr::from_ptr(status_json).to_str().unwrap().to_string() };
+            cb(err, status_json)
+        }
+
+        let mut callbacks = POOL_GET_STATUS_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(pool_get_status_callback))
+    }
+
+    pub fn closure_to_pool_subscribe_cb(event_closure: Box<FnMut(ErrorCode, String) + Send>,
+                                        closure: Box<FnMut(ErrorCode) + Send>)
+                                        -> (i32,
+                                            Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                             event_json: *const c_char)>,
+                                            Option<extern fn(command_handle: i32, err: ErrorCode)>) {
+        lazy_static! {
+            static ref POOL_EVENT_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, String) + Send>>> = Default::default();
+            static ref POOL_SUBSCRIBE_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode) + Send>>> = Default::default();
+        }
+
+        extern "C" fn pool_event_callback(command_handle: i32, err: ErrorCode, event_json: *const c_char) {
+            let mut callbacks = POOL_EVENT_CALLBACKS.lock().unwrap();
+            // Event callback is called many times, so it isn't removed
+            let cb = callbacks.get_mut(&command_handle).unwrap();
+            let event_json = unsafe { CStr::from_ptr(event_json).to_str().unwrap().to_string() };
+            cb(err, event_json)
+        }
+
+        extern "C" fn pool_subscribe_callback(command_handle: i32, err: ErrorCode) {
+            let mut callbacks = POOL_SUBSCRIBE_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err)
+        }
+
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        POOL_EVENT_CALLBACKS.lock().unwrap().insert(command_handle, event_closure);
+        POOL_SUBSCRIBE_CALLBACKS.lock().unwrap().insert(command_handle, closure);
+
+        (command_handle, Some(pool_event_callback), Some(pool_subscribe_callback))
+    }
+
+    pub fn closure_to_close_pool_ledger_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
+                                                                                      Option<extern fn(command_handle: i32,
+                                                                                                       err: ErrorCode)>) {
+        lazy_static! {
+            static ref CLOSE_POOL_LEDGER_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode) + Send>>> = Default::default();
+        }
+
+        extern "C" fn close_pool_ledger_callback(command_handle: i32, err: ErrorCode) {
+            let mut callbacks = CLOSE_POOL_LEDGER_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err)
+        }
+
+        let mut callbacks = CLOSE_POOL_LEDGER_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(close_pool_ledger_callback))
+    }
+
+    pub fn closure_to_cancel_request_cb(closure: Box<FnMut(ErrorCode) + Send>) -> (i32,
+                                                                                   Option<extern fn(command_handle: i32,
+                                                                                                    err: ErrorCode)>) {
+        lazy_static! {
+            static ref CANCEL_REQUEST_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode) + Send>>> = Default::default();
+        }
+
+        extern "C" fn cancel_request_callback(command_handle: i32, err: ErrorCode) {
+            let mut callbacks = CANCEL_REQUEST_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err)
+        }
+
+        let mut callbacks = CANCEL_REQUEST_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(cancel_request_callback))
+    }
+
     pub fn closure_to_issuer_create_claim_definition_cb(closure: Box<FnMut(ErrorCode, String, String) + Send>) -> (i32,
                                                                                                                    Option<extern fn(command_handle: i32,
                                                                                                                                     err: ErrorCode,
@@ -386,6 +480,28 @@ impl CallbackUtils {
         (command_handle, Some(sign_callback))
     }
 
+    pub fn closure_to_sign_request_cb(closure: Box<FnMut(ErrorCode, String) + Send>)
+                                      -> (i32,
+                                          Option<extern fn(command_handle: i32, err: ErrorCode,
+                                                           signed_request_json: *const c_char)>) {
+        lazy_static! {
+            static ref SIGN_REQUEST_CALLBACKS: Mutex<HashMap<i32, Box<FnMut(ErrorCode, String) + Send>>> = Default::default();
+        }
+
+        extern "C" fn sign_request_callback(command_handle: i32, err: ErrorCode, signed_request_json: *const c_char) {
+            let mut callbacks = SIGN_REQUEST_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            let signed_request_json = unsafe { CStr::from_ptr(signed_request_json).to_str().unwrap().to_string() };
+            cb(err, signed_request_json);
+        }
+
+        let mut callbacks = SIGN_REQUEST_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(sign_request_callback))
+    }
+
     pub fn closure_to_verify_signature_cb(closure: Box<FnMut(ErrorCode, bool) + Send>) -> (i32,
                                                                                            Option<extern fn(command_handle: i32,
                                                                                                             err: ErrorCode,
@@ -448,4 +564,25 @@ impl CallbackUtils {
 
         (command_handle, Some(prover_get_claim_offers_callback))
     }
+
+    pub fn closure_to_verify_txn_inclusion_cb(closure: Box<FnMut(ErrorCode, bool) + Send>) -> (i32,
+                                                                                               Option<extern fn(command_handle: i32,
+                                                                                                                err: ErrorCode,
+                                                                                                                valid: bool)>) {
+        lazy_static! {
+            static ref VERIFY_TXN_INCLUSION_CALLBACKS: Mutex < HashMap < i32, Box < FnMut(ErrorCode, bool) + Send > >> = Default::default();
+        }
+
+        extern "C" fn closure_to_verify_txn_inclusion_callback(command_handle: i32, err: ErrorCode, valid: bool) {
+            let mut callbacks = VERIFY_TXN_INCLUSION_CALLBACKS.lock().unwrap();
+            let mut cb = callbacks.remove(&command_handle).unwrap();
+            cb(err, valid)
+        }
+
+        let mut callbacks = VERIFY_TXN_INCLUSION_CALLBACKS.lock().unwrap();
+        let command_handle = (COMMAND_HANDLE_COUNTER.fetch_add(1, Ordering::SeqCst) + 1) as i32;
+        callbacks.insert(command_handle, closure);
+
+        (command_handle, Some(closure_to_verify_txn_inclusion_callback))
+    }
 }
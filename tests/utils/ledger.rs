@@ -0,0 +1,259 @@
+use sovrin::api::ErrorCode;
+use sovrin::api::ledger::{
+    sovrin_build_get_attrib_request,
+    sovrin_build_get_txn_request,
+    sovrin_multi_sign_request,
+    sovrin_parse_get_txn_response,
+    sovrin_resolve_encrypted_attrib,
+    sovrin_sign_and_submit_request,
+    sovrin_sign_request,
+    sovrin_store_encrypted_attrib,
+    sovrin_verify_txn_inclusion
+};
+
+use utils::callback::CallbackUtils;
+use utils::timeout::TimeoutUtils;
+
+use std::ffi::CString;
+use std::ptr::null;
+use std::sync::mpsc::channel;
+
+pub struct LedgerUtils {}
+
+impl LedgerUtils {
+    pub fn sign_and_submit_request(pool_handle: i32, wallet_handle: i32, submitter_did: &str, request_json: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, request_result_json| {
+            sender.send((err, request_result_json)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_send_tx_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let request_json = CString::new(request_json).unwrap();
+
+        let err = sovrin_sign_and_submit_request(command_handle, pool_handle, wallet_handle,
+                                                 submitter_did.as_ptr(), request_json.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_result_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_result_json)
+    }
+
+    pub fn sign_request(wallet_handle: i32, submitter_did: &str, request_json: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, signed_request_json| {
+            sender.send((err, signed_request_json)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let request_json = CString::new(request_json).unwrap();
+
+        let err = sovrin_sign_request(command_handle, wallet_handle, submitter_did.as_ptr(), request_json.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, signed_request_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(signed_request_json)
+    }
+
+    pub fn multi_sign_request(wallet_handle: i32, submitter_did: &str, request_json: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, signed_request_json| {
+            sender.send((err, signed_request_json)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let request_json = CString::new(request_json).unwrap();
+
+        let err = sovrin_multi_sign_request(command_handle, wallet_handle, submitter_did.as_ptr(), request_json.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, signed_request_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(signed_request_json)
+    }
+
+    pub fn store_encrypted_attrib(wallet_handle: i32, submitter_did: &str, target_did: &str, raw: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let target_did = CString::new(target_did).unwrap();
+        let raw = CString::new(raw).unwrap();
+
+        let err = sovrin_store_encrypted_attrib(command_handle, wallet_handle, submitter_did.as_ptr(), target_did.as_ptr(), raw.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+
+    pub fn resolve_encrypted_attrib(wallet_handle: i32, get_attrib_response: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, raw| {
+            sender.send((err, raw)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_request_cb(cb);
+
+        let get_attrib_response = CString::new(get_attrib_response).unwrap();
+
+        let err = sovrin_resolve_encrypted_attrib(command_handle, wallet_handle, get_attrib_response.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, raw) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(raw)
+    }
+
+    pub fn build_get_attrib_request(submitter_did: &str, target_did: &str, hash: Option<&str>,
+                                    raw: Option<&str>, enc: Option<&str>) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let target_did = CString::new(target_did).unwrap();
+        let hash = hash.map(|hash| CString::new(hash).unwrap());
+        let raw = raw.map(|raw| CString::new(raw).unwrap());
+        let enc = enc.map(|enc| CString::new(enc).unwrap());
+
+        let err = sovrin_build_get_attrib_request(command_handle, submitter_did.as_ptr(), target_did.as_ptr(),
+                                                  hash.as_ref().map(|hash| hash.as_ptr()).unwrap_or(null()),
+                                                  raw.as_ref().map(|raw| raw.as_ptr()).unwrap_or(null()),
+                                                  enc.as_ref().map(|enc| enc.as_ptr()).unwrap_or(null()),
+                                                  cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+
+    pub fn build_get_txn_request(submitter_did: &str, ledger_type: Option<&str>, seq_no: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, request_json| {
+            sender.send((err, request_json)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_request_cb(cb);
+
+        let submitter_did = CString::new(submitter_did).unwrap();
+        let ledger_type = ledger_type.map(|ledger_type| CString::new(ledger_type).unwrap());
+
+        let err = sovrin_build_get_txn_request(command_handle, submitter_did.as_ptr(),
+                                               ledger_type.as_ref().map(|ledger_type| ledger_type.as_ptr()).unwrap_or(null()),
+                                               seq_no, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, request_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(request_json)
+    }
+
+    pub fn parse_get_txn_response(get_txn_response: &str) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, txn_json| {
+            sender.send((err, txn_json)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_sign_request_cb(cb);
+
+        let get_txn_response = CString::new(get_txn_response).unwrap();
+
+        let err = sovrin_parse_get_txn_response(command_handle, get_txn_response.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, txn_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(txn_json)
+    }
+
+    pub fn verify_txn_inclusion(txn_json: &str, audit_path: &str, root_hash: &str, tree_size: i32) -> Result<bool, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, valid| {
+            sender.send((err, valid)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_verify_txn_inclusion_cb(cb);
+
+        let txn_json = CString::new(txn_json).unwrap();
+        let audit_path = CString::new(audit_path).unwrap();
+        let root_hash = CString::new(root_hash).unwrap();
+
+        let err = sovrin_verify_txn_inclusion(command_handle, txn_json.as_ptr(), audit_path.as_ptr(), root_hash.as_ptr(), tree_size, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, valid) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(valid)
+    }
+}
@@ -3,7 +3,10 @@ pub mod callback;
 #[path = "../../src/utils/environment.rs"]
 pub mod environment;
 
+pub mod ledger;
 pub mod pool;
+pub mod pool_simulator;
+pub mod signus;
 pub mod wallet;
 pub mod anoncreds;
 
@@ -1,10 +1,9 @@
 extern crate time;
 
 use sovrin::api::ErrorCode;
-use sovrin::api::pool::{sovrin_create_pool_ledger_config};
-#[cfg(feature = "local_nodes_pool")]
-use sovrin::api::pool::sovrin_open_pool_ledger;
-use sovrin::api::ledger::sovrin_submit_request;
+use sovrin::api::pool::{sovrin_close_pool_ledger, sovrin_create_pool_ledger_config, sovrin_open_pool_ledger, sovrin_pool_get_status,
+                        sovrin_pool_subscribe};
+use sovrin::api::ledger::{sovrin_cancel_request, sovrin_submit_request};
 
 use utils::callback::CallbackUtils;
 use utils::environment::EnvironmentUtils;
@@ -13,15 +12,24 @@ use utils::timeout::TimeoutUtils;
 use std::fs;
 use std::ffi::CString;
 use std::io::Write;
-#[cfg(feature = "local_nodes_pool")]
 use std::ptr::null;
 use std::path::PathBuf;
-use std::sync::mpsc::channel;
+use std::sync::mpsc::{channel, Receiver, Sender};
 
 pub struct PoolUtils {}
 
 impl PoolUtils {
     pub fn create_pool_ledger_config(pool_name: &str) -> Result<(), ErrorCode> {
+        PoolUtils::create_genesis_txn_file(pool_name);
+        PoolUtils::_create_pool_ledger_config(pool_name)
+    }
+
+    pub fn create_pool_ledger_config_for_txns(pool_name: &str, genesis_txns: &Vec<String>) -> Result<(), ErrorCode> {
+        PoolUtils::create_genesis_txn_file_for_txns(pool_name, genesis_txns);
+        PoolUtils::_create_pool_ledger_config(pool_name)
+    }
+
+    fn _create_pool_ledger_config(pool_name: &str) -> Result<(), ErrorCode> {
         let (sender, receiver) = channel();
 
 
@@ -31,7 +39,6 @@ impl PoolUtils {
 
         let (command_handle, cb) = CallbackUtils::closure_to_create_pool_ledger_cb(cb);
 
-        PoolUtils::create_genesis_txn_file(pool_name);
         let pool_config = CString::new(PoolUtils::create_pool_config(pool_name)).unwrap();
         let pool_name = CString::new(pool_name).unwrap();
 
@@ -53,8 +60,11 @@ impl PoolUtils {
         Ok(())
     }
 
-    #[cfg(feature="local_nodes_pool")]
     pub fn open_pool_ledger(pool_name: &str) -> Result<i32, ErrorCode> {
+        PoolUtils::open_pool_ledger_with_config(pool_name, None)
+    }
+
+    pub fn open_pool_ledger_with_config(pool_name: &str, config: Option<&str>) -> Result<i32, ErrorCode> {
         let (sender, receiver) = channel();
 
 
@@ -65,17 +75,18 @@ impl PoolUtils {
         let (command_handle, cb) = CallbackUtils::closure_to_open_pool_ledger_cb(cb);
 
         let pool_name = CString::new(pool_name).unwrap();
+        let config = config.map(|config| CString::new(config).unwrap());
 
         let err = sovrin_open_pool_ledger(command_handle,
                                           pool_name.as_ptr(),
-                                          null(),
+                                          config.as_ref().map(|config| config.as_ptr()).unwrap_or(null()),
                                           cb);
 
         if err != ErrorCode::Success {
             return Err(err);
         }
 
-        let (err, pool_handle) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+        let (err, pool_handle) = receiver.recv_timeout(TimeoutUtils::medium_timeout()).unwrap();
 
         if err != ErrorCode::Success {
             return Err(err);
@@ -84,6 +95,78 @@ impl PoolUtils {
         Ok(pool_handle)
     }
 
+    pub fn get_pool_status(pool_handle: i32) -> Result<String, ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, status_json| {
+            sender.send((err, status_json)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_pool_get_status_cb(cb);
+
+        let err = sovrin_pool_get_status(command_handle, pool_handle, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, status_json) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(status_json)
+    }
+
+    pub fn subscribe_pool_events(pool_handle: i32, event_sender: Sender<String>) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let event_cb = Box::new(move |err, event_json| {
+            if err == ErrorCode::Success {
+                // Receiver may be already dropped by test, events are just skipped in this case
+                event_sender.send(event_json).ok();
+            }
+        });
+        let cb = Box::new(move |err| {
+            sender.send(err).unwrap();
+        });
+        let (command_handle, event_cb, cb) = CallbackUtils::closure_to_pool_subscribe_cb(event_cb, cb);
+
+        let err = sovrin_pool_subscribe(command_handle, pool_handle, event_cb, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn close(pool_handle: i32) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err| {
+            sender.send(err).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_close_pool_ledger_cb(cb);
+
+        let err = sovrin_close_pool_ledger(command_handle, pool_handle, cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::medium_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
     pub fn send_request(pool_handle: i32, request: &str) -> Result<String, ErrorCode> {
         let (sender, receiver) = channel();
         let cb_send = Box::new(move |err, resp| {
@@ -110,7 +193,80 @@ impl PoolUtils {
         Ok(resp)
     }
 
+    pub fn send_request_async(pool_handle: i32, request: &str) -> Result<(i32, Receiver<(ErrorCode, String)>), ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb_send = Box::new(move |err, resp| {
+            sender.send((err, resp)).unwrap();
+        });
+        let req = CString::new(request).unwrap();
+        let (command_handle, callback) = CallbackUtils::closure_to_send_tx_cb(cb_send);
+
+        let err = sovrin_submit_request(command_handle,
+                                        pool_handle,
+                                        req.as_ptr(),
+                                        callback);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((command_handle, receiver))
+    }
+
+    /// Submits request with command handle of other pending request,
+    /// so the result is passed to the callback registered for that handle
+    pub fn send_request_with_command_handle(pool_handle: i32, command_handle: i32, request: &str) -> Result<(), ErrorCode> {
+        let (_, callback) = CallbackUtils::closure_to_send_tx_cb(Box::new(|_, _| {}));
+        let req = CString::new(request).unwrap();
+
+        let err = sovrin_submit_request(command_handle,
+                                        pool_handle,
+                                        req.as_ptr(),
+                                        callback);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn cancel_request(pool_handle: i32, submit_command_handle: i32) -> Result<(), ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err| {
+            sender.send(err).unwrap();
+        });
+        let (command_handle, callback) = CallbackUtils::closure_to_cancel_request_cb(cb);
+
+        let err = sovrin_cancel_request(command_handle,
+                                        pool_handle,
+                                        submit_command_handle,
+                                        callback);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let err = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
     pub fn create_genesis_txn_file(pool_name: &str) -> PathBuf {
+        let genesis_txns = vec![
+            "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"10.0.0.2\",\"client_port\":9702,\"node_ip\":\"10.0.0.2\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}".to_string(),
+            "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"10.0.0.2\",\"client_port\":9704,\"node_ip\":\"10.0.0.2\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}".to_string(),
+            "{\"data\":{\"alias\":\"Node3\",\"client_ip\":\"10.0.0.2\",\"client_port\":9706,\"node_ip\":\"10.0.0.2\",\"node_port\":9705,\"services\":[\"VALIDATOR\"]},\"dest\":\"DKVxG2fXXTU8yT5N7hGEbXB3dfdAnYv1JczDUHpmDxya\",\"identifier\":\"2yAeV5ftuasWNgQwVYzeHeTuM7LwwNtPR3Zg9N4JiDgF\",\"txnId\":\"7e9f355dffa78ed24668f0e0e369fd8c224076571c51e2ea8be5f26479edebe4\",\"type\":\"0\"}".to_string(),
+            "{\"data\":{\"alias\":\"Node4\",\"client_ip\":\"10.0.0.2\",\"client_port\":9708,\"node_ip\":\"10.0.0.2\",\"node_port\":9707,\"services\":[\"VALIDATOR\"]},\"dest\":\"4PS3EDQ3dW1tci1Bp6543CfuuebjFrg36kLAUcskGfaA\",\"identifier\":\"FTE95CVthRtrBnK2PYCBbC9LghTcGwi9Zfi1Gz2dnyNx\",\"txnId\":\"aa5e817d7cc626170eca175822029339a444eb0ee8f0bd20d3b0b76e566fb008\",\"type\":\"0\"}".to_string()
+        ];
+        PoolUtils::create_genesis_txn_file_for_txns(pool_name, &genesis_txns)
+    }
+
+    pub fn create_genesis_txn_file_for_txns(pool_name: &str, genesis_txns: &Vec<String>) -> PathBuf {
         let path = EnvironmentUtils::tmp_file_path(format!("{}.txn", pool_name).as_str());
 
         if !path.parent().unwrap().exists() {
@@ -120,12 +276,10 @@ impl PoolUtils {
         }
 
         let mut f = fs::File::create(path.clone()).unwrap();
-        let data = format!("{}\n{}\n{}\n{}\n",
-                           "{\"data\":{\"alias\":\"Node1\",\"client_ip\":\"10.0.0.2\",\"client_port\":9702,\"node_ip\":\"10.0.0.2\",\"node_port\":9701,\"services\":[\"VALIDATOR\"]},\"dest\":\"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv\",\"identifier\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\",\"txnId\":\"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62\",\"type\":\"0\"}",
-                           "{\"data\":{\"alias\":\"Node2\",\"client_ip\":\"10.0.0.2\",\"client_port\":9704,\"node_ip\":\"10.0.0.2\",\"node_port\":9703,\"services\":[\"VALIDATOR\"]},\"dest\":\"8ECVSk179mjsjKRLWiQtssMLgp6EPhWXtaYyStWPSGAb\",\"identifier\":\"8QhFxKxyaFsJy4CyxeYX34dFH8oWqyBv1P4HLQCsoeLy\",\"txnId\":\"1ac8aece2a18ced660fef8694b61aac3af08ba875ce3026a160acbc3a3af35fc\",\"type\":\"0\"}",
-                           "{\"data\":{\"alias\":\"Node3\",\"client_ip\":\"10.0.0.2\",\"client_port\":9706,\"node_ip\":\"10.0.0.2\",\"node_port\":9705,\"services\":[\"VALIDATOR\"]},\"dest\":\"DKVxG2fXXTU8yT5N7hGEbXB3dfdAnYv1JczDUHpmDxya\",\"identifier\":\"2yAeV5ftuasWNgQwVYzeHeTuM7LwwNtPR3Zg9N4JiDgF\",\"txnId\":\"7e9f355dffa78ed24668f0e0e369fd8c224076571c51e2ea8be5f26479edebe4\",\"type\":\"0\"}",
-                           "{\"data\":{\"alias\":\"Node4\",\"client_ip\":\"10.0.0.2\",\"client_port\":9708,\"node_ip\":\"10.0.0.2\",\"node_port\":9707,\"services\":[\"VALIDATOR\"]},\"dest\":\"4PS3EDQ3dW1tci1Bp6543CfuuebjFrg36kLAUcskGfaA\",\"identifier\":\"FTE95CVthRtrBnK2PYCBbC9LghTcGwi9Zfi1Gz2dnyNx\",\"txnId\":\"aa5e817d7cc626170eca175822029339a444eb0ee8f0bd20d3b0b76e566fb008\",\"type\":\"0\"}");
-        f.write_all(data.as_bytes()).unwrap();
+        for txn in genesis_txns {
+            f.write_all(txn.as_bytes()).unwrap();
+            f.write_all(b"\n").unwrap();
+        }
         f.flush().unwrap();
         f.sync_all().unwrap();
         path
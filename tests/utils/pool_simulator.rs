@@ -0,0 +1,454 @@
+extern crate libc;
+extern crate openssl;
+extern crate rust_base58;
+extern crate sodiumoxide;
+extern crate zmq;
+
+use self::libc::c_int;
+use self::openssl::hash::{hash2, MessageDigest};
+use self::rust_base58::ToBase58;
+use self::sodiumoxide::crypto::sign::ed25519;
+use serde_json;
+use serde_json::Value;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+extern {
+    // libsodium functions aren't wrapped by sodiumoxide, the same binding as in src/utils/crypto/ed25519
+    fn crypto_sign_ed25519_pk_to_curve25519(curve25519_pk: *mut [u8; 32],
+                                            ed25519_pk: *const [u8; 32]) -> c_int;
+    fn crypto_sign_ed25519_sk_to_curve25519(curve25519_sk: *mut [u8; 32],
+                                            ed25519_sk: *const [u8; 64]) -> c_int;
+}
+
+static POLL_TIMEOUT: i64 = 100; /* in ms */
+static LEDGER_ID_POOL: u64 = 0;
+static LEDGER_ID_DOMAIN: u64 = 1;
+
+/// Behavior of simulated validator node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeBehavior {
+    /// Follows protocol and knows full pool ledger
+    Honest,
+    /// Follows protocol, but knows only first N txns of pool ledger
+    Lagging(usize),
+    /// Receives messages but never answers
+    Silent,
+    /// Follows pool ledger protocol, but never answers client requests
+    Mute,
+    /// Answers to every message with corrupted data (merkle roots, txns, replies)
+    Byzantine,
+    /// Follows protocol, but answers client requests after the delay in ms
+    Delayed(u64),
+    /// Follows pool ledger protocol, but refuses every client request with REQNACK
+    Refusing,
+}
+
+/// Handler to build `result` of REPLY message from client request json.
+pub type ReplyHandler = Fn(&Value) -> Value + Send + Sync;
+
+/// Local validator pool that speaks the same CurveZMQ ROUTER protocol as real Plenum nodes:
+/// ping/pong, LEDGER_STATUS, CONSISTENCY_PROOF, CATCHUP_REQ/CATCHUP_REP and REQACK/REPLY
+/// or REQNACK for client requests.
+pub struct PoolSimulator {
+    nodes: Vec<SimulatedNode>,
+    genesis_txns_cnt: usize,
+    pool_ledger: Vec<String>,
+    domain_ledger: Vec<String>,
+    reply_handler: Arc<ReplyHandler>,
+    stop: Arc<AtomicBool>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+struct SimulatedNode {
+    alias: String,
+    port: u32,
+    public_key: [u8; 32],
+    secret_key: [u8; 64],
+    behavior: NodeBehavior,
+}
+
+struct NodeWorker {
+    alias: String,
+    sock: zmq::Socket,
+    behavior: NodeBehavior,
+    ledger: Vec<String>,
+    domain_ledger: Vec<String>,
+    catchup_till: usize,
+    reply_handler: Arc<ReplyHandler>,
+}
+
+impl PoolSimulator {
+    /// Generates pool of `node_cnt` honest validators with client ports starting from `base_port`.
+    pub fn new(node_cnt: usize, base_port: u32) -> PoolSimulator {
+        let mut nodes: Vec<SimulatedNode> = Vec::new();
+        let mut pool_ledger: Vec<String> = Vec::new();
+        for i in 0..node_cnt {
+            let (pk, sk) = ed25519::gen_keypair();
+            let node = SimulatedNode {
+                alias: format!("Node{}", i + 1),
+                port: base_port + 2 * i as u32 + 1,
+                public_key: pk.0,
+                secret_key: sk.0,
+                behavior: NodeBehavior::Honest,
+            };
+            pool_ledger.push(node.node_txn(i));
+            nodes.push(node);
+        }
+        PoolSimulator {
+            nodes: nodes,
+            genesis_txns_cnt: node_cnt,
+            pool_ledger: pool_ledger,
+            domain_ledger: Vec::new(),
+            reply_handler: Arc::new(PoolSimulator::default_reply) as Arc<ReplyHandler>,
+            stop: Arc::new(AtomicBool::new(false)),
+            workers: Vec::new(),
+        }
+    }
+
+    pub fn set_behavior(&mut self, node_idx: usize, behavior: NodeBehavior) {
+        self.nodes[node_idx].behavior = behavior;
+    }
+
+    pub fn set_reply_handler(&mut self, handler: Box<ReplyHandler>) {
+        self.reply_handler = Arc::from(handler);
+    }
+
+    /// Appends txn to pool ledger known by nodes, but not included into genesis txns.
+    /// Client will receive this txn in catchup.
+    pub fn add_pool_txn(&mut self, txn: &str) {
+        self.pool_ledger.push(txn.to_string());
+    }
+
+    /// Appends txn to domain ledger known by all nodes.
+    pub fn add_domain_txn(&mut self, txn: &str) {
+        self.domain_ledger.push(txn.to_string());
+    }
+
+    /// Builds NODE txn that changes client port of the node (as steward would do).
+    pub fn node_update_txn(&self, node_idx: usize, client_port: u32) -> String {
+        let node = &self.nodes[node_idx];
+        format!("{{\"data\":{{\"alias\":\"{}\",\"client_ip\":\"127.0.0.1\",\"client_port\":{}}},\"dest\":\"{}\",\"identifier\":\"{}\",\"txnId\":\"{}\",\"type\":\"0\"}}",
+                node.alias, client_port, node.public_key.to_base58(), node.public_key.to_base58(),
+                PoolSimulator::_hash_hex(format!("update {} {}", node.alias, client_port).as_bytes()))
+    }
+
+    /// Builds NODE txn that removes VALIDATOR service from the node.
+    pub fn node_demote_txn(&self, node_idx: usize) -> String {
+        let node = &self.nodes[node_idx];
+        format!("{{\"data\":{{\"alias\":\"{}\",\"services\":[]}},\"dest\":\"{}\",\"identifier\":\"{}\",\"txnId\":\"{}\",\"type\":\"0\"}}",
+                node.alias, node.public_key.to_base58(), node.public_key.to_base58(),
+                PoolSimulator::_hash_hex(format!("demote {}", node.alias).as_bytes()))
+    }
+
+    pub fn genesis_txns(&self) -> Vec<String> {
+        self.pool_ledger[..self.genesis_txns_cnt].to_vec()
+    }
+
+    pub fn pool_ledger(&self) -> Vec<String> {
+        self.pool_ledger.clone()
+    }
+
+    pub fn merkle_root(txns: &[String]) -> String {
+        let leaves: Vec<Vec<u8>> = txns.iter().map(|txn| PoolSimulator::_hash_leaf(txn.as_bytes())).collect();
+        PoolSimulator::_mth(&leaves).to_base58()
+    }
+
+    pub fn start(&mut self) {
+        for node in &self.nodes {
+            let ledger = match node.behavior {
+                NodeBehavior::Lagging(cnt) => self.pool_ledger[..cnt].to_vec(),
+                _ => self.pool_ledger.clone(),
+            };
+            let mut worker = NodeWorker {
+                alias: node.alias.clone(),
+                sock: node.bind(),
+                behavior: node.behavior.clone(),
+                catchup_till: ledger.len(),
+                ledger: ledger,
+                domain_ledger: self.domain_ledger.clone(),
+                reply_handler: self.reply_handler.clone(),
+            };
+            let stop = self.stop.clone();
+            self.workers.push(thread::spawn(move || {
+                worker.run(stop);
+            }));
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+    }
+
+    /// Builds REPLY result for the request with reqId, identifier and type of the request
+    /// and given extra fields, e.g. data. Used by reply handlers.
+    pub fn reply_result(request: &Value, fields: Vec<(&str, Value)>) -> Value {
+        let mut result = serde_json::Map::new();
+        result.insert("reqId".to_string(), request["reqId"].clone());
+        result.insert("identifier".to_string(), request["identifier"].clone());
+        result.insert("type".to_string(), request["operation"]["type"].clone());
+        for (key, value) in fields {
+            result.insert(key.to_string(), value);
+        }
+        Value::Object(result)
+    }
+
+    fn default_reply(request: &Value) -> Value {
+        PoolSimulator::reply_result(request, vec![("data", Value::Null)])
+    }
+
+    fn _hash_hex(data: &[u8]) -> String {
+        let hash = hash2(MessageDigest::sha256(), data).unwrap();
+        hash.iter().map(|b| format!("{:02x}", b)).collect::<Vec<String>>().join("")
+    }
+
+    fn _hash_leaf(data: &[u8]) -> Vec<u8> {
+        let mut buf: Vec<u8> = vec![0x00];
+        buf.extend_from_slice(data);
+        hash2(MessageDigest::sha256(), buf.as_slice()).unwrap().to_vec()
+    }
+
+    fn _hash_nodes(left: &[u8], right: &[u8]) -> Vec<u8> {
+        let mut buf: Vec<u8> = vec![0x01];
+        buf.extend_from_slice(left);
+        buf.extend_from_slice(right);
+        hash2(MessageDigest::sha256(), buf.as_slice()).unwrap().to_vec()
+    }
+
+    /// Merkle tree hash as defined in RFC 6962 2.1
+    fn _mth(leaves: &[Vec<u8>]) -> Vec<u8> {
+        match leaves.len() {
+            0 => hash2(MessageDigest::sha256(), &[]).unwrap().to_vec(),
+            1 => leaves[0].clone(),
+            n => {
+                let k = PoolSimulator::_split_point(n);
+                PoolSimulator::_hash_nodes(&PoolSimulator::_mth(&leaves[..k]), &PoolSimulator::_mth(&leaves[k..]))
+            }
+        }
+    }
+
+    /// Consistency proof between first `m` leaves and all leaves as defined in RFC 6962 2.1.2
+    fn _consistency_proof(m: usize, leaves: &[Vec<u8>], complete: bool) -> Vec<Vec<u8>> {
+        let n = leaves.len();
+        if m == n {
+            return if complete { Vec::new() } else { vec![PoolSimulator::_mth(leaves)] };
+        }
+        let k = PoolSimulator::_split_point(n);
+        if m <= k {
+            let mut proof = PoolSimulator::_consistency_proof(m, &leaves[..k], complete);
+            proof.push(PoolSimulator::_mth(&leaves[k..]));
+            proof
+        } else {
+            let mut proof = PoolSimulator::_consistency_proof(m - k, &leaves[k..], false);
+            proof.push(PoolSimulator::_mth(&leaves[..k]));
+            proof
+        }
+    }
+
+    /// The largest power of two smaller than n
+    fn _split_point(n: usize) -> usize {
+        let mut k = 1;
+        while k << 1 < n {
+            k <<= 1;
+        }
+        k
+    }
+}
+
+impl Drop for PoolSimulator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl SimulatedNode {
+    fn node_txn(&self, idx: usize) -> String {
+        let dest = self.public_key.to_base58();
+        format!("{{\"data\":{{\"alias\":\"{}\",\"client_ip\":\"127.0.0.1\",\"client_port\":{},\"node_ip\":\"127.0.0.1\",\"node_port\":{},\"services\":[\"VALIDATOR\"]}},\"dest\":\"{}\",\"identifier\":\"{}\",\"txnId\":\"{}\",\"type\":\"0\"}}",
+                self.alias, self.port, self.port - 1, dest, dest,
+                PoolSimulator::_hash_hex(format!("genesis {} {}", idx, dest).as_bytes()))
+    }
+
+    fn bind(&self) -> zmq::Socket {
+        let mut pkc: [u8; 32] = [0; 32];
+        let mut skc: [u8; 32] = [0; 32];
+        unsafe {
+            crypto_sign_ed25519_pk_to_curve25519(&mut pkc, &self.public_key);
+            crypto_sign_ed25519_sk_to_curve25519(&mut skc, &self.secret_key);
+        }
+        let ctx = zmq::Context::new();
+        let sock: zmq::Socket = ctx.socket(zmq::SocketType::ROUTER).expect("socket");
+        sock.set_curve_publickey(zmq::z85_encode(&pkc).unwrap().as_str()).expect("set public key");
+        sock.set_curve_secretkey(zmq::z85_encode(&skc).unwrap().as_str()).expect("set secret key");
+        sock.set_curve_server(true).expect("set curve server");
+        sock.set_linger(0).expect("set linger");
+        sock.bind(format!("tcp://127.0.0.1:{}", self.port).as_str()).expect("bind");
+        sock
+    }
+}
+
+impl NodeWorker {
+    fn run(&mut self, stop: Arc<AtomicBool>) {
+        while !stop.load(Ordering::SeqCst) {
+            if self.sock.poll(zmq::POLLIN, POLL_TIMEOUT).expect("poll") == 0 {
+                continue;
+            }
+            let msg = self.sock.recv_multipart(zmq::DONTWAIT).expect("recv multipart");
+            let identity = msg[0].clone();
+            let msg = String::from_utf8(msg[1].clone()).expect("non-string message");
+            if self.behavior == NodeBehavior::Silent {
+                continue;
+            }
+            for resp in self.process_msg(msg.as_str()) {
+                self.sock.send_multipart(&[identity.as_slice(), resp.as_bytes()], zmq::DONTWAIT).expect("send multipart");
+            }
+        }
+    }
+
+    fn process_msg(&mut self, msg: &str) -> Vec<String> {
+        if msg == "pi" {
+            return vec!["po".to_string()];
+        }
+        let msg: Value = match serde_json::from_str(msg) {
+            Ok(msg) => msg,
+            Err(_) => return Vec::new(),
+        };
+        match msg["op"].as_str() {
+            Some("LEDGER_STATUS") => vec![self.process_ledger_status(&msg)],
+            Some("CATCHUP_REQ") => self.process_catchup_req(&msg),
+            Some(_) => Vec::new(),
+            None => self.process_request(&msg),
+        }
+    }
+
+    fn process_ledger_status(&self, msg: &Value) -> String {
+        let ledger_id = msg["ledgerId"].as_u64().unwrap_or(LEDGER_ID_POOL);
+        let client_size = msg["txnSeqNo"].as_u64().unwrap_or(0) as usize;
+        let leaves = self.leaves(ledger_id);
+        let merkle_root = self.merkle_root(&leaves[..]);
+        if client_size >= leaves.len() {
+            return NodeWorker::_object(vec![
+                ("op", Value::from("LEDGER_STATUS")),
+                ("ledgerId", Value::from(ledger_id)),
+                ("txnSeqNo", Value::from(leaves.len())),
+                ("merkleRoot", Value::from(merkle_root)),
+            ]).to_string();
+        }
+        let hashes: Vec<String> = if client_size == 0 {
+            Vec::new()
+        } else {
+            PoolSimulator::_consistency_proof(client_size, &leaves[..], true)
+                .iter().map(|hash| hash.to_base58()).collect()
+        };
+        NodeWorker::_object(vec![
+            ("op", Value::from("CONSISTENCY_PROOF")),
+            ("ledgerId", Value::from(ledger_id)),
+            ("seqNoStart", Value::from(client_size)),
+            ("seqNoEnd", Value::from(leaves.len())),
+            ("oldMerkleRoot", Value::from(self.merkle_root(&leaves[..client_size]))),
+            ("newMerkleRoot", Value::from(merkle_root)),
+            ("hashes", Value::from(hashes)),
+        ]).to_string()
+    }
+
+    fn process_catchup_req(&mut self, msg: &Value) -> Vec<String> {
+        let ledger_id = msg["ledgerId"].as_u64().unwrap_or(LEDGER_ID_POOL);
+        let start = msg["seqNoStart"].as_u64().unwrap_or(0) as usize;
+        let end = msg["seqNoEnd"].as_u64().unwrap_or(0) as usize;
+        self.catchup_till = msg["catchupTill"].as_u64().unwrap_or(0) as usize;
+
+        let mut txns = serde_json::Map::new();
+        let ledger = self.ledger(ledger_id).clone();
+        let end = ::std::cmp::min(end, ledger.len());
+        for seq_no in start..(end + 1) {
+            if seq_no == 0 {
+                continue;
+            }
+            let mut txn: Value = serde_json::from_str(ledger[seq_no - 1].as_str()).unwrap();
+            if self.behavior == NodeBehavior::Byzantine {
+                if txn["data"].is_object() {
+                    txn["data"]["client_port"] = Value::from(1);
+                } else {
+                    txn["identifier"] = Value::from("byzantine");
+                }
+            }
+            txns.insert(seq_no.to_string(), txn);
+        }
+        if txns.is_empty() {
+            return Vec::new();
+        }
+        let leaves = self.leaves(ledger_id);
+        let cons_proof: Vec<String> = if end > 0 && self.catchup_till <= leaves.len() {
+            PoolSimulator::_consistency_proof(end, &leaves[..self.catchup_till], true)
+                .iter().map(|hash| hash.to_base58()).collect()
+        } else {
+            Vec::new()
+        };
+        vec![NodeWorker::_object(vec![
+            ("op", Value::from("CATCHUP_REP")),
+            ("ledgerId", Value::from(ledger_id)),
+            ("txns", Value::Object(txns)),
+            ("consProof", Value::from(cons_proof)),
+        ]).to_string()]
+    }
+
+    fn process_request(&self, msg: &Value) -> Vec<String> {
+        match self.behavior {
+            NodeBehavior::Mute => return Vec::new(),
+            NodeBehavior::Delayed(delay_ms) => thread::sleep(Duration::from_millis(delay_ms)),
+            NodeBehavior::Refusing => {
+                return vec![NodeWorker::_object(vec![
+                    ("op", Value::from("REQNACK")),
+                    ("reqId", msg["reqId"].clone()),
+                    ("identifier", msg["identifier"].clone()),
+                    ("reason", Value::from("Request is refused by simulated node")),
+                ]).to_string()];
+            }
+            _ => {}
+        }
+        let req_ack = NodeWorker::_object(vec![
+            ("op", Value::from("REQACK")),
+            ("reqId", msg["reqId"].clone()),
+            ("identifier", msg["identifier"].clone()),
+        ]).to_string();
+        let mut result: Value = (self.reply_handler)(msg);
+        if self.behavior == NodeBehavior::Byzantine {
+            result["data"] = Value::from("byzantine");
+        }
+        let reply = NodeWorker::_object(vec![
+            ("op", Value::from("REPLY")),
+            ("result", result),
+        ]).to_string();
+        vec![req_ack, reply]
+    }
+
+    fn _object(fields: Vec<(&str, Value)>) -> Value {
+        let mut object = serde_json::Map::new();
+        for (key, value) in fields {
+            object.insert(key.to_string(), value);
+        }
+        Value::Object(object)
+    }
+
+    fn ledger(&self, ledger_id: u64) -> &Vec<String> {
+        if ledger_id == LEDGER_ID_DOMAIN { &self.domain_ledger } else { &self.ledger }
+    }
+
+    fn leaves(&self, ledger_id: u64) -> Vec<Vec<u8>> {
+        self.ledger(ledger_id).iter().map(|txn| PoolSimulator::_hash_leaf(txn.as_bytes())).collect()
+    }
+
+    fn merkle_root(&self, leaves: &[Vec<u8>]) -> String {
+        if self.behavior == NodeBehavior::Byzantine {
+            PoolSimulator::_hash_leaf(b"byzantine").to_base58()
+        } else {
+            PoolSimulator::_mth(leaves).to_base58()
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use sovrin::api::ErrorCode;
+use sovrin::api::signus::sovrin_create_and_store_my_did;
+
+use utils::callback::CallbackUtils;
+use utils::timeout::TimeoutUtils;
+
+use std::ffi::CString;
+use std::sync::mpsc::channel;
+
+pub struct SignusUtils {}
+
+impl SignusUtils {
+    pub fn create_my_did(wallet_handle: i32, my_did_json: &str) -> Result<(String, String, String), ErrorCode> {
+        let (sender, receiver) = channel();
+        let cb = Box::new(move |err, did, verkey, public_key| {
+            sender.send((err, did, verkey, public_key)).unwrap();
+        });
+        let (command_handle, cb) = CallbackUtils::closure_to_create_and_store_my_did_cb(cb);
+
+        let my_did_json = CString::new(my_did_json).unwrap();
+
+        let err = sovrin_create_and_store_my_did(command_handle, wallet_handle, my_did_json.as_ptr(), cb);
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        let (err, did, verkey, public_key) = receiver.recv_timeout(TimeoutUtils::short_timeout()).unwrap();
+
+        if err != ErrorCode::Success {
+            return Err(err);
+        }
+
+        Ok((did, verkey, public_key))
+    }
+}
@@ -62,7 +62,8 @@
                         @"            \"type\":\"105\","
                         @"            \"dest\":\"FYmoFw55GeQH7SRFa37dkx1d2dZ3zUF8ckg7wmL7ofN4\""
                         @"        },"
-                        @"        \"signature\":\"4o86XfkiJ4e2r3J6Ufoi17UU3W5Zi9sshV6FjBjkVw4sgEQFQov9dxqDEtLbAJAWffCWd5KfAk164QVo7mYwKkiV\""
+                        @"        \"protocolVersion\":1,"
+                        @"        \"signature\":\"kpNoAuN1X1VRx3PvLvVxq4Q6AABCqyMBuBBCXypwnZroUPjrx2J6B2acEiBb2UF82kZiCZ5z4bcMfgtninQvaWu\""
                         @"    }";
     
     __block NSString *result = nil;
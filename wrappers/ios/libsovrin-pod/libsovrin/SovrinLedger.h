@@ -46,6 +46,7 @@
                      completion:(void (^)(NSError* error, NSString* requestJSON)) handler;
 
 + (NSError*) buildGetSchemaRequest:(NSString*) submitterDid
+                              dest:(NSString*) dest
                               data:(NSString*) data
                         completion:(void (^)(NSError* error, NSString* requestJSON)) handler;
 
@@ -57,6 +58,8 @@
 
 + (NSError*) buildGetClaimDefTxn:(NSString*) submitterDid
                             xref:(NSString*) xref
+                   signatureType:(NSString*) signatureType
+                          origin:(NSString*) origin
                       completion:(void (^)(NSError* error, NSString* requestJSON)) handler;
 
 + (NSError*) buildNodeRequest:(NSString*) submitterDid
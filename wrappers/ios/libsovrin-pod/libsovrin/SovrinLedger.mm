@@ -177,6 +177,7 @@
 }
 
 + (NSError*) buildGetSchemaRequest:(NSString*) submitterDid
+                              dest:(NSString*) dest
                               data:(NSString*) data
                         completion:(void (^)(NSError* error, NSString* requestJSON)) handler
 {
@@ -186,6 +187,7 @@
     
     ret = sovrin_build_get_schema_request( handle,
                                            [submitterDid UTF8String],
+                                           [dest UTF8String],
                                            [data UTF8String],
                                            SovrinWrapperCommon3PSCallback );
     if( ret != Success )
@@ -221,6 +223,8 @@
 
 + (NSError*) buildGetClaimDefTxn:(NSString*) submitterDid
                             xref:(NSString*) xref
+                   signatureType:(NSString*) signatureType
+                          origin:(NSString*) origin
                       completion:(void (^)(NSError* error, NSString* requestJSON)) handler
 {
     sovrin_error_t ret;
@@ -230,6 +234,8 @@
     ret = sovrin_build_get_claim_def_txn( handle,
                                           [submitterDid UTF8String],
                                           [xref UTF8String],
+                                          [signatureType UTF8String],
+                                          [origin UTF8String],
                                           SovrinWrapperCommon3PSCallback );
     if( ret != Success )
     {
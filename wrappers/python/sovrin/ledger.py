@@ -12,6 +12,20 @@ class Ledger(object):
                                       request_result_json: str) -> None:
         pass
 
+    async def sign_request(command_handle: int,
+                           wallet_handle: int,
+                           submitter_did: str,
+                           request_json: str,
+                           signed_request_json: str) -> None:
+        pass
+
+    async def multi_sign_request(command_handle: int,
+                                 wallet_handle: int,
+                                 submitter_did: str,
+                                 request_json: str,
+                                 signed_request_json: str) -> None:
+        pass
+
     async def submit_request(command_handle: int,
                              pool_handle: int,
                              request_json: str,
@@ -43,10 +57,26 @@ class Ledger(object):
                                    request_json: str) -> None:
         pass
 
+    async def store_encrypted_attrib(command_handle: int,
+                                     wallet_handle: int,
+                                     submitter_did: str,
+                                     target_did: str,
+                                     raw: str,
+                                     request_json: str) -> None:
+        pass
+
+    async def resolve_encrypted_attrib(command_handle: int,
+                                       wallet_handle: int,
+                                       get_attrib_response: str,
+                                       raw: str) -> None:
+        pass
+
     async def build_get_attrib_request(command_handle: int,
                                        submitter_did: str,
                                        target_did: str,
-                                       data: str,
+                                       hash: str,
+                                       raw: str,
+                                       enc: str,
                                        request_json: str) -> None:
         pass
 
@@ -64,6 +94,7 @@ class Ledger(object):
 
     async def build_get_schema_request(command_handle: int,
                                        submitter_did: str,
+                                       dest: str,
                                        data: str,
                                        request_json: str) -> None:
         pass
@@ -78,6 +109,8 @@ class Ledger(object):
     async def build_get_claim_def_txn(command_handle: int,
                                       submitter_did: str,
                                       xref: str,
+                                      signature_type: str,
+                                      origin: str,
                                       request_json: str) -> None:
         pass
 
@@ -87,3 +120,50 @@ class Ledger(object):
                                  data: str,
                                  request_json: str) -> None:
         pass
+
+    async def build_pool_upgrade_request(command_handle: int,
+                                         submitter_did: str,
+                                         data: str,
+                                         request_json: str) -> None:
+        pass
+
+    async def build_get_txn_request(command_handle: int,
+                                    submitter_did: str,
+                                    ledger_type: str,
+                                    seq_no: int,
+                                    request_json: str) -> None:
+        pass
+
+    async def parse_get_txn_response(command_handle: int,
+                                     get_txn_response: str,
+                                     txn_json: str) -> None:
+        pass
+
+    async def parse_get_schema_response(command_handle: int,
+                                        get_schema_response: str,
+                                        schema_json: str) -> None:
+        pass
+
+    async def parse_get_claim_def_response(command_handle: int,
+                                           get_claim_def_response: str,
+                                           claim_def_json: str) -> None:
+        pass
+
+    async def parse_get_nym_response(command_handle: int,
+                                     get_nym_response: str,
+                                     nym_json: str) -> None:
+        pass
+
+    async def build_did_document(command_handle: int,
+                                 get_nym_response: str,
+                                 get_attrib_responses: str,
+                                 did_document_json: str) -> None:
+        pass
+
+    async def verify_txn_inclusion(command_handle: int,
+                                   txn_json: str,
+                                   audit_path: str,
+                                   root_hash: str,
+                                   tree_size: int,
+                                   valid: bool) -> None:
+        pass